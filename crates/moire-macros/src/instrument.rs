@@ -82,6 +82,20 @@ fn expand_impl(attr: TokenStream2, item: TokenStream2) -> TokenStream2 {
         let output_ty = split_tail.return_type.unwrap_or_else(|| quote!(()));
         let where_clause = split_tail.where_clause;
 
+        if returns_result(&output_ty) {
+            return quote_spanned! {
+                fn_span =>
+                #attributes_tokens
+                #pre_fn_without_async fn #name #pre_params_tokens #params_tokens -> impl ::core::future::Future<Output = #output_ty> #where_clause {
+                    ::moire::__internal::instrument_future(#fn_name, async move {
+                        let result = async move #body_tokens.await;
+                        ::moire::__internal::note_result(&result);
+                        result
+                    }, None, None).skip_entry_frames(1)
+                }
+            };
+        }
+
         return quote_spanned! {
             fn_span =>
             #attributes_tokens
@@ -195,6 +209,21 @@ fn contains_ident(tokens: &TokenStream2, needle: &str) -> bool {
     })
 }
 
+/// Whether the type is spelled `Result<..>` or `some::path::Result<..>`, so that
+/// an `Err` can be reported as the future's outcome.
+fn returns_result(ty: &TokenStream2) -> bool {
+    let mut last_segment = None;
+    for token in ty.clone() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => break,
+            TokenTree::Punct(punct) if punct.as_char() == ':' => {}
+            TokenTree::Ident(ident) => last_segment = Some(ident),
+            _ => return false,
+        }
+    }
+    last_segment.is_some_and(|ident| ident == "Result")
+}
+
 fn function_name(name: &Ident) -> String {
     let raw = name.to_string();
     raw.strip_prefix("r#").unwrap_or(&raw).to_owned()
@@ -231,6 +260,33 @@ mod tests {
         assert_eq!(output.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn reports_errors_of_result_returning_functions() {
+        let input = quote! {
+            async fn load(path: &str) -> std::io::Result<String> {
+                std::fs::read_to_string(path)
+            }
+        };
+
+        let output = expand_impl(TokenStream2::new(), input);
+        let expected = quote! {
+            fn load(path: &str) -> impl ::core::future::Future<Output = std::io::Result<String> > {
+                ::moire::__internal::instrument_future("load", async move {
+                    let result = async move {
+                        std::fs::read_to_string(path)
+                    }.await;
+                    ::moire::__internal::note_result(&result);
+                    result
+                }, None, None).skip_entry_frames(1)
+            }
+        };
+
+        assert_eq!(output.to_string(), expected.to_string());
+        assert!(returns_result(&quote!(Result<(), E>)));
+        assert!(!returns_result(&quote!(Option<Result<(), E>>)));
+        assert!(!returns_result(&quote!(MyResult)));
+    }
+
     #[test]
     fn rewrites_impl_future_functions() {
         let input = quote! {
@@ -3,6 +3,7 @@ use moire_types::{
 };
 
 use super::db::{runtime_db, runtime_stream_id};
+use super::deferred::flush_deferred_updates;
 
 pub trait SnapshotSink {
     fn entity(&mut self, entity: &Entity);
@@ -15,6 +16,7 @@ pub fn write_snapshot_to<S>(sink: &mut S)
 where
     S: SnapshotSink,
 {
+    flush_deferred_updates();
     let Ok(db) = runtime_db().lock() else {
         return;
     };
@@ -34,6 +36,7 @@ where
 
 pub fn pull_changes_since(from_seq_no: SeqNo, max_changes: u32) -> PullChangesResponse {
     let stream_id = runtime_stream_id();
+    flush_deferred_updates();
     let Ok(db) = runtime_db().lock() else {
         return PullChangesResponse {
             stream_id,
@@ -49,6 +52,7 @@ pub fn pull_changes_since(from_seq_no: SeqNo, max_changes: u32) -> PullChangesRe
 
 pub fn current_cursor() -> StreamCursor {
     let stream_id = runtime_stream_id();
+    flush_deferred_updates();
     let Ok(db) = runtime_db().lock() else {
         return StreamCursor {
             stream_id,
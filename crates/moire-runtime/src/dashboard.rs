@@ -1,16 +1,65 @@
 use moire_types::SeqNo;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex as StdMutex, OnceLock};
 use std::time::Duration;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::time::MissedTickBehavior;
 
 use moire_wire::{
     ClientMessage, ServerMessage, decode_server_message_default, encode_client_message_default,
+    non_empty_env,
 };
 
 use super::api::{ack_cut, pull_changes_since};
-use super::{DASHBOARD_PUSH_INTERVAL_MS, DASHBOARD_PUSH_MAX_CHANGES, DASHBOARD_RECONNECT_DELAY_MS};
+use super::diagnostics::{config_errors, report};
+use super::redact::redact_handshake;
+use super::settings::{apply_setting_change, current_settings, push_interval_ms};
+use super::transport::dashboard_token;
+use super::{DASHBOARD_PUSH_MAX_CHANGES, DASHBOARD_RECONNECT_DELAY_MS, SESSION_RESUME_TIMEOUT_MS};
+
+/// Where the push loop (and with it, snapshot replies) runs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PushLoopRuntime {
+    /// A background OS thread with its own current-thread runtime. Keeps reporting
+    /// when the application's runtime is wedged.
+    Dedicated,
+    /// The first application runtime that polls an instrumented future.
+    Application,
+}
+
+// r[impl config.dashboard-runtime]
+fn push_loop_runtime_from_env() -> PushLoopRuntime {
+    match std::env::var("MOIRE_DASHBOARD_RUNTIME") {
+        Ok(value) if value.trim().eq_ignore_ascii_case("app") => PushLoopRuntime::Application,
+        _ => PushLoopRuntime::Dedicated,
+    }
+}
+
+/// Where the change stream goes: a live dashboard, a recording file, or both.
+struct PushTargets {
+    addr: Option<String>,
+    record_path: Option<PathBuf>,
+}
+
+// r[impl config.dashboard-addr]
+// r[impl config.record-file]
+fn push_targets_from_env() -> PushTargets {
+    let (addr, file_target) = match non_empty_env("MOIRE_DASHBOARD") {
+        Some(value) => match value.strip_prefix("file:") {
+            Some(path) => (None, Some(PathBuf::from(path))),
+            None => (Some(value), None),
+        },
+        None => (None, None),
+    };
+    PushTargets {
+        addr,
+        record_path: non_empty_env("MOIRE_RECORD_FILE")
+            .map(PathBuf::from)
+            .or(file_target),
+    }
+}
+
+static DEFERRED_PUSH_LOOP: StdMutex<Option<(PushTargets, String)>> = StdMutex::new(None);
 
 pub(super) fn init_dashboard_push_loop(process_name: &str) {
     static STARTED: OnceLock<()> = OnceLock::new();
@@ -18,50 +67,97 @@ pub(super) fn init_dashboard_push_loop(process_name: &str) {
         return;
     }
 
-    // r[impl config.dashboard-addr]
-    let Some(addr) = std::env::var("MOIRE_DASHBOARD")
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-    else {
+    let targets = push_targets_from_env();
+    if targets.addr.is_none() && targets.record_path.is_none() {
         return;
-    };
+    }
 
     let process_name = String::from(process_name);
+    if let Some(addr) = &targets.addr {
+        super::heartbeat::init_heartbeat_thread(addr.clone());
+    }
 
-    if tokio::runtime::Handle::try_current().is_ok() {
-        tokio::spawn(async move {
-            run_dashboard_push_loop(addr, process_name).await;
-        });
+    if push_loop_runtime_from_env() == PushLoopRuntime::Application {
+        if let Some(handle) = super::application_runtime() {
+            handle.spawn(async move {
+                run_push_targets(targets, process_name).await;
+            });
+        } else if let Ok(mut deferred) = DEFERRED_PUSH_LOOP.lock() {
+            *deferred = Some((targets, process_name));
+        }
         return;
     }
 
-    std::thread::spawn(move || {
-        if let Ok(rt) = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
+    let _ = std::thread::Builder::new()
+        .name(String::from("moire-dashboard"))
+        .spawn(move || {
+            if let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                rt.block_on(async move {
+                    run_push_targets(targets, process_name).await;
+                });
+            }
+        });
+}
+
+/// Starts a push loop that was waiting for an application runtime to show up.
+pub(super) fn start_deferred_push_loop(handle: &tokio::runtime::Handle) {
+    let Some((targets, process_name)) = DEFERRED_PUSH_LOOP.lock().ok().and_then(|mut d| d.take())
+    else {
+        return;
+    };
+    handle.spawn(async move {
+        run_push_targets(targets, process_name).await;
+    });
+}
+
+async fn run_push_targets(targets: PushTargets, process_name: String) {
+    let live = async {
+        if let Some(addr) = &targets.addr {
+            run_dashboard_push_loop(addr, &process_name).await;
+        }
+    };
+    let recording = async {
+        if let Some(path) = &targets.record_path
+            && let Err(e) = run_record_file_loop(path, &process_name).await
         {
-            rt.block_on(async move {
-                run_dashboard_push_loop(addr, process_name).await;
-            });
+            report(format!("recording to {} stopped: {e}", path.display()));
         }
-    });
+    };
+    tokio::join!(live, recording);
 }
 
-async fn run_dashboard_push_loop(addr: String, process_name: String) {
+async fn run_dashboard_push_loop(addr: &str, process_name: &str) {
     loop {
-        let connected = run_dashboard_session(&addr, process_name.clone()).await;
+        let connected = run_dashboard_session(addr, process_name).await;
         let _ = connected;
         // r[impl config.dashboard-reconnect]
         tokio::time::sleep(Duration::from_millis(DASHBOARD_RECONNECT_DELAY_MS)).await;
     }
 }
 
-async fn run_dashboard_session(addr: &str, process_name: String) -> Result<(), String> {
-    let stream = TcpStream::connect(addr)
-        .await
-        .map_err(|e| format!("dashboard connect: {e}"))?;
-    let (mut reader, mut writer) = stream.into_split();
+/// Handshake bookkeeping for one push session.
+struct HandshakeState<'a> {
+    process_name: &'a str,
+    token: Option<String>,
+    last_sent_manifest_revision: u64,
+}
+
+impl<'a> HandshakeState<'a> {
+    fn new(process_name: &'a str, token: Option<String>) -> Self {
+        Self {
+            process_name,
+            token,
+            last_sent_manifest_revision: u64::MAX,
+        }
+    }
+}
+
+async fn run_dashboard_session(addr: &str, process_name: &str) -> Result<(), String> {
+    let stream = super::transport::connect(addr).await?;
+    let (mut reader, mut writer) = tokio::io::split(stream);
 
     // r[impl wire.magic]
     writer
@@ -69,50 +165,69 @@ async fn run_dashboard_session(addr: &str, process_name: String) -> Result<(), S
         .await
         .map_err(|e| format!("write protocol magic: {e}"))?;
 
-    let mut last_sent_manifest_revision = u64::MAX;
-    send_handshake_if_manifest_changed(
-        &mut writer,
-        process_name.as_str(),
-        &mut last_sent_manifest_revision,
-    )
-    .await?;
+    let mut handshake = HandshakeState::new(process_name, dashboard_token());
+    send_handshake_if_manifest_changed(&mut writer, &mut handshake).await?;
 
+    // r[impl wire.session-resume]
+    // Deltas wait for the server's SessionResume so a reconnect picks up where the
+    // previous session left off instead of replaying the whole change log. A server
+    // that predates resume never sends one, so past the timeout we start from zero.
+    let mut resumed = false;
+    let resume_timeout = tokio::time::sleep(Duration::from_millis(SESSION_RESUME_TIMEOUT_MS));
+    tokio::pin!(resume_timeout);
     let mut cursor = SeqNo::ZERO;
     let mut last_sent_backtrace_id = None;
-    let mut ticker = tokio::time::interval(Duration::from_millis(DASHBOARD_PUSH_INTERVAL_MS));
-    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut ticker_interval_ms = push_interval_ms();
+    let mut ticker = push_ticker(ticker_interval_ms);
 
     loop {
         tokio::select! {
-            _ = ticker.tick() => {
-                let requested_from = cursor;
-                let batch = pull_changes_since(cursor, DASHBOARD_PUSH_MAX_CHANGES);
-                let cursor_shifted = batch.from_seq_no > requested_from || batch.next_seq_no > requested_from;
-                if !batch.changes.is_empty() || batch.truncated || cursor_shifted {
-                    let next = batch.next_seq_no;
-                    flush_backtrace_records(
-                        &mut writer,
-                        process_name.as_str(),
-                        &mut last_sent_manifest_revision,
-                        &mut last_sent_backtrace_id,
-                    )
-                    .await?;
-                    write_client_message(&mut writer, &ClientMessage::DeltaBatch(batch)).await?;
-                    cursor = next.max(cursor);
-                } else {
-                    cursor = batch.next_seq_no.max(cursor);
-                }
+            () = &mut resume_timeout, if !resumed => {
+                resumed = true;
+            }
+            _ = ticker.tick(), if resumed => {
+                push_pending_changes(
+                    &mut writer,
+                    &mut handshake,
+                    &mut cursor,
+                    &mut last_sent_backtrace_id,
+                )
+                .await?;
             }
             inbound = read_server_message(&mut reader) => {
                 let Some(message) = inbound? else {
                     return Ok(());
                 };
                 match message {
+                    ServerMessage::SessionResume(resume) => {
+                        // Arriving after the timeout, it may be behind what this
+                        // session already sent; never step back.
+                        cursor = cursor.max(resume.next_seq_no);
+                        last_sent_backtrace_id =
+                            last_sent_backtrace_id.max(resume.last_backtrace_id);
+                        resumed = true;
+                    }
+                    ServerMessage::ControlRequest(request) => {
+                        let errors: Vec<String> = request
+                            .changes
+                            .iter()
+                            .filter_map(|change| apply_setting_change(change).err())
+                            .collect();
+                        if push_interval_ms() != ticker_interval_ms {
+                            ticker_interval_ms = push_interval_ms();
+                            ticker = push_ticker(ticker_interval_ms);
+                        }
+                        let ack = moire_wire::ControlAck {
+                            control_id: request.control_id,
+                            error: (!errors.is_empty()).then(|| errors.join("; ")),
+                            settings: current_settings(),
+                        };
+                        write_client_message(&mut writer, &ClientMessage::ControlAck(ack)).await?;
+                    }
                     ServerMessage::CutRequest(request) => {
                         flush_backtrace_records(
                             &mut writer,
-                            process_name.as_str(),
-                            &mut last_sent_manifest_revision,
+                            &mut handshake,
                             &mut last_sent_backtrace_id,
                         )
                         .await?;
@@ -122,16 +237,12 @@ async fn run_dashboard_session(addr: &str, process_name: String) -> Result<(), S
                     ServerMessage::SnapshotRequest(request) => {
                         flush_backtrace_records(
                             &mut writer,
-                            process_name.as_str(),
-                            &mut last_sent_manifest_revision,
+                            &mut handshake,
                             &mut last_sent_backtrace_id,
                         )
                         .await?;
                         let frame = super::db::encode_snapshot_reply_frame(request.snapshot_id)?;
-                        writer
-                            .write_all(&frame)
-                            .await
-                            .map_err(|e| format!("write frame: {e}"))?;
+                        write_frame(&mut writer, &frame).await?;
                     }
                 }
             }
@@ -139,15 +250,78 @@ async fn run_dashboard_session(addr: &str, process_name: String) -> Result<(), S
     }
 }
 
+/// Appends the change stream to a file in wire format: the protocol magic followed
+/// by framed `ClientMessage`s, exactly as a live session would send them.
+// r[impl config.record-file]
+async fn run_record_file_loop(path: &Path, process_name: &str) -> Result<(), String> {
+    let mut writer = tokio::fs::File::create(path)
+        .await
+        .map_err(|e| format!("create recording file: {e}"))?;
+    writer
+        .write_all(&moire_wire::encode_protocol_magic())
+        .await
+        .map_err(|e| format!("write protocol magic: {e}"))?;
+
+    // Tokens are never written to disk.
+    let mut handshake = HandshakeState::new(process_name, None);
+    send_handshake_if_manifest_changed(&mut writer, &mut handshake).await?;
+
+    let mut cursor = SeqNo::ZERO;
+    let mut last_sent_backtrace_id = None;
+    let mut ticker_interval_ms = push_interval_ms();
+    let mut ticker = push_ticker(ticker_interval_ms);
+    loop {
+        ticker.tick().await;
+        push_pending_changes(
+            &mut writer,
+            &mut handshake,
+            &mut cursor,
+            &mut last_sent_backtrace_id,
+        )
+        .await?;
+        if push_interval_ms() != ticker_interval_ms {
+            ticker_interval_ms = push_interval_ms();
+            ticker = push_ticker(ticker_interval_ms);
+        }
+    }
+}
+
+/// Sends the changes after `cursor` as one delta batch, preceded by any backtrace
+/// records (and manifest updates) the batch may refer to.
+async fn push_pending_changes(
+    writer: &mut (impl AsyncWrite + Unpin),
+    handshake: &mut HandshakeState<'_>,
+    cursor: &mut SeqNo,
+    last_sent_backtrace_id: &mut Option<moire_trace_types::BacktraceId>,
+) -> Result<(), String> {
+    let requested_from = *cursor;
+    let batch = pull_changes_since(requested_from, DASHBOARD_PUSH_MAX_CHANGES);
+    let cursor_shifted = batch.from_seq_no > requested_from || batch.next_seq_no > requested_from;
+    if batch.changes.is_empty() && !batch.truncated && !cursor_shifted {
+        *cursor = batch.next_seq_no.max(*cursor);
+        return Ok(());
+    }
+    let next = batch.next_seq_no;
+    flush_backtrace_records(writer, handshake, last_sent_backtrace_id).await?;
+    write_client_message(writer, &ClientMessage::DeltaBatch(batch)).await?;
+    *cursor = next.max(*cursor);
+    Ok(())
+}
+
+fn push_ticker(interval_ms: u64) -> tokio::time::Interval {
+    let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
 // r[impl wire.backtrace-record]
 async fn flush_backtrace_records(
-    writer: &mut tokio::net::tcp::OwnedWriteHalf,
-    process_name: &str,
-    last_sent_manifest_revision: &mut u64,
+    writer: &mut (impl AsyncWrite + Unpin),
+    handshake: &mut HandshakeState<'_>,
     last_sent_backtrace_id: &mut Option<moire_trace_types::BacktraceId>,
 ) -> Result<(), String> {
     let records = super::backtrace_records_after(*last_sent_backtrace_id);
-    send_handshake_if_manifest_changed(writer, process_name, last_sent_manifest_revision).await?;
+    send_handshake_if_manifest_changed(writer, handshake).await?;
     for record in records {
         let record_id = record.id;
         write_client_message(writer, &ClientMessage::BacktraceRecord(record)).await?;
@@ -157,44 +331,73 @@ async fn flush_backtrace_records(
 }
 
 async fn send_handshake_if_manifest_changed(
-    writer: &mut tokio::net::tcp::OwnedWriteHalf,
-    process_name: &str,
-    last_sent_manifest_revision: &mut u64,
+    writer: &mut (impl AsyncWrite + Unpin),
+    state: &mut HandshakeState<'_>,
 ) -> Result<(), String> {
     let (revision, module_manifest) = super::module_manifest_snapshot();
-    if revision == *last_sent_manifest_revision {
+    if revision == state.last_sent_manifest_revision {
         return Ok(());
     }
-    let handshake = ClientMessage::Handshake(moire_wire::Handshake {
+    let handshake = handshake_message(state.process_name, state.token.clone(), module_manifest);
+    write_client_message(writer, &handshake).await?;
+    state.last_sent_manifest_revision = revision;
+    Ok(())
+}
+
+/// Builds the handshake for this process, with `args` and `env` redacted.
+pub(super) fn handshake_message(
+    process_name: &str,
+    token: Option<String>,
+    module_manifest: Vec<moire_wire::ModuleManifestEntry>,
+) -> ClientMessage {
+    let mut args: Vec<String> = std::env::args().collect();
+    let mut env = handshake_env(std::env::vars());
+    let redaction = redact_handshake(&mut args, &mut env);
+    ClientMessage::Handshake(moire_wire::Handshake {
         process_id: super::runtime_process_id(),
         process_name: process_name.to_string(),
         pid: std::process::id(),
-        args: std::env::args().collect(),
-        env: std::env::vars()
-            .map(|(key, value)| format!("{key}={value}"))
-            .collect(),
+        args,
+        env,
         module_manifest,
-    });
-    write_client_message(writer, &handshake).await?;
-    *last_sent_manifest_revision = revision;
-    Ok(())
+        token,
+        redaction,
+        config_errors: config_errors(),
+    })
+}
+
+/// `KEY=VALUE` entries for the handshake, without the variables that never
+/// leave the process.
+fn handshake_env(vars: impl IntoIterator<Item = (String, String)>) -> Vec<String> {
+    vars.into_iter()
+        .filter(|(key, _)| !moire_wire::HANDSHAKE_EXCLUDED_ENV.contains(&key.as_str()))
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect()
 }
 
 async fn write_client_message(
-    writer: &mut tokio::net::tcp::OwnedWriteHalf,
+    writer: &mut (impl AsyncWrite + Unpin),
     message: &ClientMessage,
 ) -> Result<(), String> {
     let frame = encode_client_message_default(message)
         .map_err(|e| format!("encode client message: {e}"))?;
+    write_frame(writer, &frame).await
+}
+
+async fn write_frame(writer: &mut (impl AsyncWrite + Unpin), frame: &[u8]) -> Result<(), String> {
+    // Flushing matters over TLS, which may otherwise hold the last record back.
     writer
-        .write_all(&frame)
+        .write_all(frame)
         .await
         .map_err(|e| format!("write frame: {e}"))?;
-    Ok(())
+    writer
+        .flush()
+        .await
+        .map_err(|e| format!("flush frame: {e}"))
 }
 
 async fn read_server_message(
-    reader: &mut tokio::net::tcp::OwnedReadHalf,
+    reader: &mut (impl AsyncRead + Unpin),
 ) -> Result<Option<ServerMessage>, String> {
     let mut len_buf = [0u8; 4];
     if let Err(e) = reader.read_exact(&mut len_buf).await {
@@ -219,3 +422,71 @@ async fn read_server_message(
         .map_err(|e| format!("decode server message: {e}"))?;
     Ok(Some(message))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake_env_leaves_out_the_token() {
+        let vars = [
+            ("PATH", "/bin"),
+            ("MOIRE_TOKEN", "s3cret"),
+            ("HOME", "/root"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+        assert_eq!(handshake_env(vars), ["PATH=/bin", "HOME=/root"]);
+    }
+
+    #[test]
+    fn handshake_carries_each_config_error_once() {
+        crate::diagnostics::report_config_error("ignoring MOIRE_TEST_SETTING: bad");
+        crate::diagnostics::report_config_error("ignoring MOIRE_TEST_SETTING: bad");
+        let ClientMessage::Handshake(handshake) = handshake_message("test", None, Vec::new())
+        else {
+            panic!("handshake_message must build a handshake");
+        };
+        let reported = handshake
+            .config_errors
+            .iter()
+            .filter(|error| *error == "ignoring MOIRE_TEST_SETTING: bad")
+            .count();
+        assert_eq!(reported, 1);
+    }
+
+    #[tokio::test]
+    async fn session_pushes_from_zero_when_the_server_never_resumes() {
+        let _entity = crate::handles::EntityHandle::new(
+            "before-resume-timeout",
+            moire_types::NotifyEntity { waiter_count: 0 },
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move { run_dashboard_session(&addr, "no-resume").await });
+
+        // An old server: reads everything, answers nothing.
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut magic = [0u8; 4];
+        stream.read_exact(&mut magic).await.unwrap();
+        let first_batch = tokio::time::timeout(
+            Duration::from_millis(SESSION_RESUME_TIMEOUT_MS * 3),
+            async {
+                loop {
+                    let mut len_buf = [0u8; 4];
+                    stream.read_exact(&mut len_buf).await.unwrap();
+                    let mut frame = len_buf.to_vec();
+                    frame.resize(4 + u32::from_be_bytes(len_buf) as usize, 0);
+                    stream.read_exact(&mut frame[4..]).await.unwrap();
+                    if let ClientMessage::DeltaBatch(batch) =
+                        moire_wire::decode_client_message_default(&frame).unwrap()
+                    {
+                        return batch;
+                    }
+                }
+            },
+        )
+        .await
+        .expect("deltas start once the resume timeout passes");
+        assert_eq!(first_batch.from_seq_no, SeqNo::ZERO);
+    }
+}
@@ -3,12 +3,13 @@ use moire_trace_types::BacktraceId;
 use moire_types::{
     Change, Edge, EdgeKind, Entity, EntityBody, EntityId, Event, EventTarget, PTime,
     PullChangesResponse, Scope, ScopeBody, ScopeId, SeqNo, StampedChange, StreamCursor, StreamId,
-    TaskScopeBody,
+    TaskScopeBody, WaitStats, WaiterWaitStats,
 };
 use std::collections::{BTreeMap, VecDeque, hash_map::DefaultHasher};
 use std::hash::{Hash, Hasher};
 use std::sync::{Mutex as StdMutex, OnceLock};
 
+use super::redact::RedactedFields;
 use super::{
     COMPACT_TARGET_CHANGES, MAX_CHANGES_BEFORE_COMPACT, current_process_scope_id,
     current_tokio_task_key,
@@ -16,7 +17,12 @@ use super::{
 
 pub(crate) fn runtime_db() -> &'static StdMutex<RuntimeDb> {
     static DB: OnceLock<StdMutex<RuntimeDb>> = OnceLock::new();
-    DB.get_or_init(|| StdMutex::new(RuntimeDb::new(runtime_stream_id(), super::MAX_EVENTS)))
+    DB.get_or_init(|| {
+        StdMutex::new(RuntimeDb::new(
+            runtime_stream_id(),
+            super::DEFAULT_MAX_EVENTS,
+        ))
+    })
 }
 
 pub(crate) fn runtime_stream_id() -> StreamId {
@@ -63,6 +69,9 @@ impl RuntimeDb {
                 | EntityBody::Semaphore(_)
                 | EntityBody::Notify(_)
                 | EntityBody::OnceCell(_)
+                | EntityBody::Barrier(_)
+                | EntityBody::CancellationToken(_)
+                | EntityBody::Stream(_)
         )
     }
 
@@ -178,6 +187,7 @@ impl RuntimeDb {
         // checkpoint materialization and replay handoff.
     }
 
+    /// Stores `entity` as given: its body must already be redacted.
     pub(crate) fn upsert_entity(&mut self, entity: Entity) {
         let entity_id = EntityId::new(entity.id.as_str());
         let should_link_task_scope = Self::should_link_entity_to_creation_task_scope(&entity.body);
@@ -304,6 +314,40 @@ impl RuntimeDb {
         true
     }
 
+    /// Adds one completed wait to the entity's wait statistics, and to those
+    /// of `waiter` on it when the actor is known.
+    pub(crate) fn record_entity_wait(
+        &mut self,
+        id: &EntityId,
+        waiter: Option<&EntityId>,
+        wait_ns: u64,
+    ) -> bool {
+        let entity_json = {
+            let Some(entity) = self.entities.get_mut(id) else {
+                return false;
+            };
+            if entity.removed_at.is_some() {
+                return false;
+            }
+            entity
+                .wait_stats
+                .get_or_insert_with(WaitStats::default)
+                .record(wait_ns);
+            if let Some(waiter) = waiter {
+                WaiterWaitStats::record(&mut entity.waiter_wait_stats, waiter, wait_ns);
+            }
+            facet_json::to_vec(entity).expect("entity serialization must succeed")
+        };
+        self.push_change(InternalChange::UpsertEntity {
+            id: EntityId::new(id.as_str()),
+            entity_json,
+        });
+        true
+    }
+
+    /// Applies `mutate` to the body of `id` and publishes it as is: `mutate`
+    /// must not touch the fields redaction rewrites. Mutations that may go
+    /// through [`Self::mutate_entity_body_holding_redacted_fields`].
     pub(crate) fn mutate_entity_body_and_maybe_upsert(
         &mut self,
         id: &EntityId,
@@ -331,6 +375,35 @@ impl RuntimeDb {
         true
     }
 
+    /// Applies `mutate` to the body of `id`. Changes to fields that redaction
+    /// rewrites are held back: the body keeps its previous values and the new
+    /// ones are returned, to be redacted outside the db lock and stored with
+    /// [`Self::store_redacted_fields`]. Other changes are published now.
+    pub(crate) fn mutate_entity_body_holding_redacted_fields(
+        &mut self,
+        id: &EntityId,
+        mutate: impl FnOnce(&mut EntityBody),
+    ) -> (bool, Option<RedactedFields>) {
+        let mut held = None;
+        let changed = self.mutate_entity_body_and_maybe_upsert(id, |body| {
+            let redacted_before = RedactedFields::of(body);
+            mutate(body);
+            held = redacted_before.and_then(|redacted_before| {
+                let redacted_after = RedactedFields::of(body)
+                    .filter(|redacted_after| *redacted_after != redacted_before)?;
+                redacted_before.write_to(body);
+                Some(redacted_after)
+            });
+        });
+        (changed || held.is_some(), held)
+    }
+
+    /// Stores fields held back by [`Self::mutate_entity_body_holding_redacted_fields`]
+    /// once they are redacted.
+    pub(crate) fn store_redacted_fields(&mut self, id: &EntityId, fields: RedactedFields) -> bool {
+        self.mutate_entity_body_and_maybe_upsert(id, |body| fields.write_to(body))
+    }
+
     pub(crate) fn remove_entity(&mut self, id: &EntityId) {
         let Some(entity) = self.entities.get_mut(id) else {
             return;
@@ -514,6 +587,7 @@ impl RuntimeDb {
         }
     }
 
+    /// Records `event` as given: its kind must already be redacted.
     pub(crate) fn record_event(&mut self, event: Event) {
         // Increment ref count for entity-targeted events.
         if let EventTarget::Entity(ref id) = event.target {
@@ -524,7 +598,23 @@ impl RuntimeDb {
         }
         let event_json = facet_json::to_vec(&event).ok();
         self.events.push_back(event);
-        // Evict old events and decrement ref counts.
+        self.evict_events_over_capacity();
+        if let Some(event_json) = event_json {
+            self.push_change(InternalChange::AppendEvent { event_json });
+        }
+    }
+
+    pub(crate) fn max_events(&self) -> usize {
+        self.max_events
+    }
+
+    pub(crate) fn set_max_events(&mut self, max_events: usize) {
+        self.max_events = max_events;
+        self.evict_events_over_capacity();
+    }
+
+    /// Evict old events and decrement ref counts.
+    fn evict_events_over_capacity(&mut self) {
         while self.events.len() > self.max_events {
             if let Some(evicted) = self.events.pop_front()
                 && let EventTarget::Entity(ref id) = evicted.target
@@ -540,9 +630,6 @@ impl RuntimeDb {
                 }
             }
         }
-        if let Some(event_json) = event_json {
-            self.push_change(InternalChange::AppendEvent { event_json });
-        }
     }
 
     /// Remove a dead entity from the map and emit a `RemoveEntity` change.
@@ -697,6 +784,45 @@ enum SnapshotClientMessageRef<'a> {
     SnapshotReply(SnapshotReplyRef<'a>),
 }
 
+/// Borrowed mirror of the upsert half of `Change`, for dumping state without cloning.
+#[derive(Facet)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+#[allow(dead_code)]
+enum StateChangeRef<'a> {
+    UpsertEntity(&'a Entity),
+    UpsertScope(&'a Scope),
+    UpsertEntityScopeLink {
+        entity_id: &'a EntityId,
+        scope_id: &'a ScopeId,
+    },
+    UpsertEdge(&'a Edge),
+    AppendEvent(&'a Event),
+}
+
+#[derive(Facet)]
+struct StampedStateChangeRef<'a> {
+    seq_no: SeqNo,
+    change: StateChangeRef<'a>,
+}
+
+#[derive(Facet)]
+struct StateBatchRef<'a> {
+    stream_id: &'a StreamId,
+    from_seq_no: SeqNo,
+    next_seq_no: SeqNo,
+    changes: Vec<StampedStateChangeRef<'a>>,
+    truncated: bool,
+}
+
+#[derive(Facet)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+#[allow(dead_code)]
+enum StateClientMessageRef<'a> {
+    DeltaBatch(StateBatchRef<'a>),
+}
+
 impl InternalStampedChange {
     fn to_change(&self) -> Option<Change> {
         match &self.change {
@@ -749,6 +875,7 @@ pub(crate) fn encode_snapshot_reply_frame(snapshot_id: i64) -> Result<Vec<u8>, S
     // Capture process-relative now before locking the db, so the timestamp
     // represents the moment this snapshot was requested.
     let ptime_now_ms = PTime::now().as_millis();
+    super::deferred::flush_deferred_updates();
     let Ok(db) = runtime_db().lock() else {
         let payload =
             facet_json::to_vec(&SnapshotClientMessageRef::SnapshotReply(SnapshotReplyRef {
@@ -776,3 +903,57 @@ pub(crate) fn encode_snapshot_reply_frame(snapshot_id: i64) -> Result<Vec<u8>, S
     moire_wire::encode_frame_default(&payload)
         .map_err(|e| format!("encode snapshot reply frame: {e}"))
 }
+
+/// Encodes the whole materialized state as `delta_batch` frames of upserts, at most
+/// `max_changes` per frame. The batches form a stream of their own, numbered from
+/// zero, so a dump replays without the change log it was compacted from.
+pub(crate) fn encode_state_delta_frames(max_changes: usize) -> Result<Vec<Vec<u8>>, String> {
+    super::deferred::flush_deferred_updates();
+    let Ok(db) = runtime_db().lock() else {
+        return Err(String::from("runtime db mutex poisoned"));
+    };
+    let changes: Vec<StateChangeRef<'_>> = db
+        .scopes
+        .values()
+        .map(StateChangeRef::UpsertScope)
+        .chain(db.entities.values().map(StateChangeRef::UpsertEntity))
+        .chain(db.entity_scope_links.keys().map(|(entity_id, scope_id)| {
+            StateChangeRef::UpsertEntityScopeLink {
+                entity_id,
+                scope_id,
+            }
+        }))
+        .chain(db.edges.values().map(StateChangeRef::UpsertEdge))
+        .chain(db.events.iter().map(StateChangeRef::AppendEvent))
+        .collect();
+
+    let mut frames = Vec::new();
+    let mut seq_no = SeqNo::ZERO;
+    let mut changes = changes.into_iter().peekable();
+    while changes.peek().is_some() {
+        let from_seq_no = seq_no;
+        let batch: Vec<StampedStateChangeRef<'_>> = changes
+            .by_ref()
+            .take(max_changes.max(1))
+            .map(|change| {
+                let stamped = StampedStateChangeRef { seq_no, change };
+                seq_no = seq_no.next();
+                stamped
+            })
+            .collect();
+        let message = StateClientMessageRef::DeltaBatch(StateBatchRef {
+            stream_id: &db.stream_id,
+            from_seq_no,
+            next_seq_no: seq_no,
+            changes: batch,
+            truncated: changes.peek().is_some(),
+        });
+        let payload =
+            facet_json::to_vec(&message).map_err(|e| format!("encode state batch json: {e}"))?;
+        frames.push(
+            moire_wire::encode_frame_default(&payload)
+                .map_err(|e| format!("encode state batch frame: {e}"))?,
+        );
+    }
+    Ok(frames)
+}
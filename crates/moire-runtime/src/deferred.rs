@@ -0,0 +1,35 @@
+//! Entity updates that wrappers hold back to keep hot paths cheap, applied
+//! before anything reads the graph, so a held-back update never outlives the
+//! activity that would otherwise have published it.
+
+use std::sync::{Mutex as StdMutex, Weak};
+
+static PENDING: StdMutex<Vec<Weak<dyn DeferredUpdate>>> = StdMutex::new(Vec::new());
+
+/// An update a wrapper held back, see [`defer_update`].
+pub trait DeferredUpdate: Send + Sync {
+    /// Applies the held-back update. Runs without the runtime db locked.
+    fn flush(&self);
+}
+
+/// Queues `update` to be flushed before the next snapshot, change batch or
+/// dump is taken. Updates whose owner is gone by then are skipped.
+pub fn defer_update(update: Weak<dyn DeferredUpdate>) {
+    let Ok(mut pending) = PENDING.lock() else {
+        return;
+    };
+    pending.push(update);
+}
+
+/// Flushes every queued update. Callers must not hold the runtime db lock.
+pub(crate) fn flush_deferred_updates() {
+    let pending = match PENDING.lock() {
+        Ok(mut pending) => std::mem::take(&mut *pending),
+        Err(_) => return,
+    };
+    for update in pending {
+        if let Some(update) = update.upgrade() {
+            update.flush();
+        }
+    }
+}
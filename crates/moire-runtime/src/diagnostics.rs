@@ -0,0 +1,32 @@
+//! Messages about moire itself, as opposed to the program it instruments.
+
+use std::fmt::Display;
+use std::sync::Mutex as StdMutex;
+
+static CONFIG_ERRORS: StdMutex<Vec<String>> = StdMutex::new(Vec::new());
+
+/// Prints a message about moire to stderr.
+pub(crate) fn report(message: impl Display) {
+    eprintln!("moire: {message}");
+}
+
+/// Reports a setting that was ignored: once on stderr, and in every handshake
+/// so the dashboard shows it too.
+pub(crate) fn report_config_error(message: impl Display) {
+    let message = message.to_string();
+    let Ok(mut errors) = CONFIG_ERRORS.lock() else {
+        return;
+    };
+    if errors.contains(&message) {
+        return;
+    }
+    report(&message);
+    errors.push(message);
+}
+
+pub(crate) fn config_errors() -> Vec<String> {
+    CONFIG_ERRORS
+        .lock()
+        .map(|errors| errors.clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,226 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use moire_wire::{ClientMessage, encode_client_message_default};
+
+use super::DASHBOARD_PUSH_MAX_CHANGES;
+use moire_wire::non_empty_env;
+
+use super::dashboard::handshake_message;
+use super::diagnostics::{report, report_config_error};
+
+/// Writes the current state to a new file in `dir` and returns its path.
+///
+/// The file is a wire trace: the protocol magic, a handshake carrying the module
+/// manifest, every backtrace record, then the materialized entities, scopes,
+/// edges and events as delta batches. `moire-web open` and `moire-web replay`
+/// read it like any other recording.
+// r[impl config.dump-signal]
+pub(crate) fn write_snapshot_dump(dir: &Path, process_name: &str) -> Result<PathBuf, String> {
+    // Batches refer to backtraces and backtrace records refer to modules, so each
+    // is captured after whatever refers to it.
+    let state_frames = super::db::encode_state_delta_frames(DASHBOARD_PUSH_MAX_CHANGES as usize)?;
+    let backtrace_records = super::backtrace_records_after(None);
+    let (_, module_manifest) = super::module_manifest_snapshot();
+
+    let mut contents = moire_wire::encode_protocol_magic().to_vec();
+    // Tokens are never written to disk.
+    let handshake = handshake_message(process_name, None, module_manifest);
+    contents.extend(encode_message(&handshake)?);
+    for record in backtrace_records {
+        contents.extend(encode_message(&ClientMessage::BacktraceRecord(record))?);
+    }
+    for frame in state_frames {
+        contents.extend(frame);
+    }
+
+    let unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let path = dir.join(format!("moire-dump-{}-{unix_ms}.moire", std::process::id()));
+    // Write next to the final name and rename, so a reader never sees half a dump.
+    let partial = path.with_extension("moire.partial");
+    let mut file = std::fs::File::create(&partial)
+        .map_err(|e| format!("create {}: {e}", partial.display()))?;
+    file.write_all(&contents)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| format!("write {}: {e}", partial.display()))?;
+    std::fs::rename(&partial, &path)
+        .map_err(|e| format!("rename {} to {}: {e}", partial.display(), path.display()))?;
+    Ok(path)
+}
+
+fn encode_message(message: &ClientMessage) -> Result<Vec<u8>, String> {
+    encode_client_message_default(message).map_err(|e| format!("encode client message: {e}"))
+}
+
+pub(super) fn dump_dir_from_env() -> PathBuf {
+    non_empty_env("MOIRE_DUMP_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// Starts a thread that writes a snapshot dump each time `MOIRE_DUMP_SIGNAL`
+/// arrives. Does nothing when the variable is unset.
+// r[impl config.dump-signal]
+#[cfg(unix)]
+pub(super) fn init_dump_signal_handler(process_name: &str) {
+    use tokio::signal::unix::signal;
+
+    let Some(name) = non_empty_env("MOIRE_DUMP_SIGNAL") else {
+        return;
+    };
+    let kind = match parse_signal_kind(&name) {
+        Ok(kind) => kind,
+        Err(e) => {
+            report_config_error(format!("ignoring MOIRE_DUMP_SIGNAL: {e}"));
+            return;
+        }
+    };
+    let dir = dump_dir_from_env();
+    let process_name = process_name.to_string();
+    let spawned = std::thread::Builder::new()
+        .name("moire-dump".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    report(format!("failed to build dump signal runtime: {e}"));
+                    return;
+                }
+            };
+            runtime.block_on(async move {
+                let mut signals = match signal(kind) {
+                    Ok(signals) => signals,
+                    Err(e) => {
+                        report(format!("failed to listen for {name}: {e}"));
+                        return;
+                    }
+                };
+                while signals.recv().await.is_some() {
+                    match write_snapshot_dump(&dir, &process_name) {
+                        Ok(path) => report(format!("wrote snapshot dump to {}", path.display())),
+                        Err(e) => report(format!("snapshot dump failed: {e}")),
+                    }
+                }
+            });
+        });
+    if let Err(e) = spawned {
+        report(format!("failed to spawn dump signal thread: {e}"));
+    }
+}
+
+#[cfg(not(unix))]
+pub(super) fn init_dump_signal_handler(_process_name: &str) {
+    if non_empty_env("MOIRE_DUMP_SIGNAL").is_some() {
+        report_config_error("ignoring MOIRE_DUMP_SIGNAL: signals are only supported on unix");
+    }
+}
+
+/// Accepts `SIGUSR2`, `USR2`, `usr2` and so on, or a raw signal number.
+#[cfg(unix)]
+fn parse_signal_kind(name: &str) -> Result<tokio::signal::unix::SignalKind, String> {
+    use tokio::signal::unix::SignalKind;
+
+    if let Ok(number) = name.parse::<i32>() {
+        return Ok(SignalKind::from_raw(number));
+    }
+    let upper = name.to_ascii_uppercase();
+    match upper.strip_prefix("SIG").unwrap_or(&upper) {
+        "USR1" => Ok(SignalKind::user_defined1()),
+        "USR2" => Ok(SignalKind::user_defined2()),
+        "HUP" => Ok(SignalKind::hangup()),
+        "QUIT" => Ok(SignalKind::quit()),
+        _ => Err(format!(
+            "unsupported signal {name:?} (use USR1, USR2, HUP, QUIT or a number)"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::futures::instrument_future;
+    use moire_types::{Change, EntityId};
+    use moire_wire::decode_client_message_default;
+
+    /// Splits a wire trace back into the messages it was written from.
+    fn read_trace(bytes: &[u8]) -> Vec<ClientMessage> {
+        let (magic, mut rest) = bytes.split_at(4);
+        assert_eq!(magic, moire_wire::encode_protocol_magic());
+        let mut messages = Vec::new();
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (frame, tail) = rest.split_at(4 + len);
+            messages.push(decode_client_message_default(frame).expect("a whole client message"));
+            rest = tail;
+        }
+        messages
+    }
+
+    // r[verify config.dump-signal]
+    #[test]
+    fn dump_reads_back_as_a_wire_trace() {
+        let future = instrument_future("dump-round-trip", std::future::ready(()), None, None);
+        let entity_id = EntityId::new(future.future_handle.id().as_str());
+        let dir = std::env::temp_dir().join(format!("moire-dump-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = write_snapshot_dump(&dir, "dump-test").expect("dump is written");
+        let bytes = std::fs::read(&path).unwrap();
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|entry| *entry != path)
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(leftovers, Vec::<PathBuf>::new());
+        let messages = read_trace(&bytes);
+        let Some(ClientMessage::Handshake(handshake)) = messages.first() else {
+            panic!("a dump starts with the handshake");
+        };
+        assert_eq!(handshake.process_name, "dump-test");
+        assert_eq!(handshake.token, None);
+        assert!(
+            messages
+                .iter()
+                .any(|message| matches!(message, ClientMessage::BacktraceRecord(_)))
+        );
+        let dumped = messages.iter().any(|message| {
+            match message {
+            ClientMessage::DeltaBatch(batch) => batch.changes.iter().any(|stamped| {
+                matches!(&stamped.change, Change::UpsertEntity(entity) if entity.id == entity_id)
+            }),
+            _ => false,
+        }
+        });
+        assert!(dumped, "the live entity is in the dump");
+        drop(future);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_names_parse_with_or_without_the_prefix() {
+        use tokio::signal::unix::SignalKind;
+
+        assert_eq!(
+            parse_signal_kind("SIGUSR2"),
+            Ok(SignalKind::user_defined2())
+        );
+        assert_eq!(parse_signal_kind("usr1"), Ok(SignalKind::user_defined1()));
+        assert_eq!(parse_signal_kind("Hup"), Ok(SignalKind::hangup()));
+        assert_eq!(parse_signal_kind("sigquit"), Ok(SignalKind::quit()));
+        assert_eq!(parse_signal_kind("12"), Ok(SignalKind::from_raw(12)));
+
+        for invalid in ["", "SIG", "SIGTERM", "USR3", "12a"] {
+            let err = parse_signal_kind(invalid).expect_err(invalid);
+            assert!(err.contains("unsupported signal"), "{err}");
+        }
+    }
+}
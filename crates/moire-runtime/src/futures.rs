@@ -1,13 +1,20 @@
 use moire_trace_types::BacktraceId;
-use moire_types::{EdgeKind, EntityId, FutureEntity};
-use std::cell::RefCell;
+use moire_types::{
+    EdgeKind, EntityBody, EntityId, Event, EventKind, EventTarget, FutureEntity, FutureOutcome,
+    LongPollEvent,
+};
+use std::cell::{Cell, RefCell};
 use std::future::{Future, IntoFuture};
 use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use super::FUTURE_CAUSAL_STACK;
 use super::db::runtime_db;
 use super::handles::{EntityHandle, EntityRef, current_causal_target_from_stack};
+use super::heartbeat::PollStamp;
+use super::wake::{OperatingOn, WakeAttribution};
 
 pub struct OperationFuture<F> {
     inner: F,
@@ -15,6 +22,9 @@ pub struct OperationFuture<F> {
     resource_id: EntityId,
     current_edge: Option<EdgeKind>,
     backtrace: BacktraceId,
+    wake: WakeAttribution,
+    /// Set from the first `Pending` until the operation completes or is dropped.
+    waiting_since: Option<Instant>,
 }
 
 impl<F> OperationFuture<F> {
@@ -33,6 +43,20 @@ impl<F> OperationFuture<F> {
             resource_id,
             current_edge: None,
             backtrace: super::capture_backtrace_id(),
+            wake: WakeAttribution::default(),
+            waiting_since: None,
+        }
+    }
+
+    /// Adds the wait that just ended, if there was one, to the resource's stats.
+    // r[impl model.entity.wait-stats]
+    fn finish_wait(&mut self) {
+        let Some(since) = self.waiting_since.take() else {
+            return;
+        };
+        let wait_ns = duration_ns(since.elapsed());
+        if let Ok(mut db) = runtime_db().lock() {
+            db.record_entity_wait(&self.resource_id, self.actor_id.as_ref(), wait_ns);
         }
     }
 
@@ -68,12 +92,28 @@ where
             this.transition_edge(Some(EdgeKind::Polls));
         }
 
-        match unsafe { Pin::new_unchecked(&mut this.inner) }.poll(cx) {
+        let actor_id = this.actor_id.as_ref();
+        let waker = this.wake.waker(
+            cx.waker(),
+            || {
+                actor_id.map(|id| EntityId::new(id.as_str())).or_else(|| {
+                    current_causal_target_from_stack().map(|target| target.id().clone())
+                })
+            },
+            Some(&this.resource_id),
+        );
+        let poll = {
+            let _operating = OperatingOn::polling(&this.resource_id);
+            unsafe { Pin::new_unchecked(&mut this.inner) }.poll(&mut Context::from_waker(&waker))
+        };
+        match poll {
             Poll::Pending => {
+                this.waiting_since.get_or_insert_with(Instant::now);
                 this.transition_edge(Some(EdgeKind::WaitingOn));
                 Poll::Pending
             }
             Poll::Ready(output) => {
+                this.finish_wait();
                 this.transition_edge(None);
                 Poll::Ready(output)
             }
@@ -83,10 +123,38 @@ where
 
 impl<F> Drop for OperationFuture<F> {
     fn drop(&mut self) {
+        if self.current_edge == Some(EdgeKind::WaitingOn) {
+            record_operation_cancelled(&self.resource_id);
+        }
+        self.finish_wait();
         self.transition_edge(None);
     }
 }
 
+/// Records that an operation waiting on `resource_id` was dropped (by a
+/// `select!`, a timeout, or its task going away) and counts it on channel
+/// entities. The event's backtrace is the drop site.
+// r[impl model.event.operation-cancelled]
+fn record_operation_cancelled(resource_id: &EntityId) {
+    let event = Event::new(
+        EventTarget::Entity(EntityId::new(resource_id.as_str())),
+        EventKind::OperationCancelled,
+        super::capture_backtrace_id(),
+    );
+    let Ok(mut db) = runtime_db().lock() else {
+        return;
+    };
+    db.mutate_entity_body_and_maybe_upsert(resource_id, |body| match body {
+        EntityBody::MpscTx(tx) => tx.cancelled_sends = tx.cancelled_sends.saturating_add(1),
+        EntityBody::MpscRx(rx) => rx.cancelled_recvs = rx.cancelled_recvs.saturating_add(1),
+        EntityBody::BroadcastRx(rx) => rx.cancelled_recvs = rx.cancelled_recvs.saturating_add(1),
+        EntityBody::WatchRx(rx) => rx.cancelled_recvs = rx.cancelled_recvs.saturating_add(1),
+        EntityBody::OneshotRx(rx) => rx.cancelled_recvs = rx.cancelled_recvs.saturating_add(1),
+        _ => {}
+    });
+    db.record_event(event);
+}
+
 pub fn instrument_operation_on<F, S>(on: &EntityHandle<S>, fut: F) -> OperationFuture<F::IntoFuture>
 where
     F: IntoFuture,
@@ -109,12 +177,141 @@ where
     )
 }
 
+/// Shared between a task's handles and its future, so that dropping the future
+/// after an abort is reported as [`FutureOutcome::Aborted`] from where the abort
+/// was requested, rather than as a drop inside the executor.
+#[derive(Clone, Default)]
+pub struct AbortNote(Arc<OnceLock<BacktraceId>>);
+
+impl AbortNote {
+    /// Records the caller as the abort site. Only the first call counts.
+    pub fn record(&self) {
+        self.0.get_or_init(super::capture_backtrace_id);
+    }
+
+    fn backtrace(&self) -> Option<BacktraceId> {
+        self.0.get().copied()
+    }
+}
+
+/// Sets the terminal outcome of a future entity, unless one is already set.
+// r[impl model.future.outcome]
+pub(crate) fn record_future_outcome(
+    future_id: &EntityId,
+    outcome: FutureOutcome,
+    backtrace: Option<BacktraceId>,
+) {
+    // Not `EntityHandle::mutate`: this runs from drops, possibly while unwinding
+    // past a poisoned lock.
+    if let Ok(mut db) = runtime_db().lock() {
+        db.mutate_entity_body_and_maybe_upsert(future_id, |body| {
+            if let EntityBody::Future(future) = body
+                && future.outcome.is_none()
+            {
+                future.outcome = Some(outcome);
+                future.outcome_backtrace = backtrace;
+            }
+        });
+    }
+}
+
+/// Records an `Err` as [`FutureOutcome::Error`] on the innermost instrumented
+/// future being polled. `#[moire::instrument]` calls this for functions that
+/// return a `Result`.
+pub fn note_result<T, E>(result: &Result<T, E>) {
+    if result.is_ok() {
+        return;
+    }
+    if let Some(target) = current_causal_target_from_stack() {
+        record_future_outcome(target.id(), FutureOutcome::Error, None);
+    }
+}
+
 pub struct InstrumentedFuture<F> {
     inner: F,
     pub(super) future_handle: EntityHandle<FutureEntity>,
     backtrace: BacktraceId,
     awaited_by: Option<FutureEdgeRelation>,
     waits_on: Option<FutureEdgeRelation>,
+    abort_note: Option<AbortNote>,
+    finished: bool,
+    wake: WakeAttribution,
+    poll_stats: PollStats,
+    poll_stamp: Arc<PollStamp>,
+}
+
+/// Poll wall time, accumulated here and copied to the entity body only when it
+/// has moved enough to matter, so that busy futures don't re-upsert on every
+/// poll.
+#[derive(Default)]
+struct PollStats {
+    total: Duration,
+    max: Duration,
+    published_total: Duration,
+}
+
+/// Smallest growth of the total poll time that is worth an upsert on its own.
+const POLL_STATS_PUBLISH_STEP: Duration = Duration::from_millis(1);
+
+impl PollStats {
+    /// Adds one poll and returns whether the entity is now out of date.
+    fn add(&mut self, elapsed: Duration) -> bool {
+        self.total += elapsed;
+        let new_max = elapsed > self.max;
+        if new_max {
+            self.max = elapsed;
+        }
+        new_max || self.total - self.published_total >= POLL_STATS_PUBLISH_STEP
+    }
+
+    fn unpublished(&self) -> bool {
+        self.total != self.published_total
+    }
+
+    // r[impl model.future.poll-time]
+    fn publish(&mut self, future_id: &EntityId) {
+        self.published_total = self.total;
+        let total_ns = duration_ns(self.total);
+        let max_ns = duration_ns(self.max);
+        // Not `EntityHandle::mutate`: this also runs from drops.
+        if let Ok(mut db) = runtime_db().lock() {
+            db.mutate_entity_body_and_maybe_upsert(future_id, |body| {
+                if let EntityBody::Future(future) = body {
+                    future.poll_total_ns = Some(total_ns);
+                    future.poll_max_ns = Some(max_ns);
+                }
+            });
+        }
+    }
+}
+
+fn duration_ns(duration: Duration) -> u64 {
+    duration.as_nanos().min(u64::MAX as u128) as u64
+}
+
+thread_local! {
+    /// Wall time of the instrumented polls nested in the one running on this
+    /// thread, so that a slow poll is blamed on the innermost future only.
+    static NESTED_POLL_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Records a `long_poll` event on `future_id` if `own` (the poll's time minus
+/// that of the instrumented polls nested in it) is over the threshold. The
+/// backtrace is captured here, at the poll site.
+fn note_long_poll(future_id: &EntityId, own: Duration) {
+    if super::settings::long_poll_threshold().is_none_or(|threshold| own < threshold) {
+        return;
+    }
+    let event = Event::new(
+        EventTarget::Entity(EntityId::new(future_id.as_str())),
+        EventKind::LongPoll(LongPollEvent {
+            duration_ns: duration_ns(own),
+        }),
+        super::capture_backtrace_id(),
+    );
+    if let Ok(mut db) = runtime_db().lock() {
+        db.record_event(event);
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -153,15 +350,27 @@ impl<F> InstrumentedFuture<F> {
         });
         let waits_on = target
             .map(|target| FutureEdgeRelation::new(target, FutureEdgeDirection::ChildToTarget));
+        let poll_stamp = PollStamp::register(EntityId::new(future_handle.id().as_str()));
         Self {
             inner,
             future_handle,
             backtrace: super::capture_backtrace_id(),
             awaited_by,
             waits_on,
+            abort_note: None,
+            finished: false,
+            wake: WakeAttribution::default(),
+            poll_stats: PollStats::default(),
+            poll_stamp,
         }
     }
 
+    /// Reports a drop before completion as an abort once `note` is recorded.
+    pub fn abort_note(mut self, note: AbortNote) -> Self {
+        self.abort_note = Some(note);
+        self
+    }
+
     /// Sets how many entry frames to skip when displaying this future in the dashboard.
     pub fn skip_entry_frames(self, n: u8) -> Self {
         self.future_handle.mutate(|f| f.skip_entry_frames = Some(n));
@@ -212,6 +421,8 @@ fn transition_relation_edge(
 impl<F: Future> InstrumentedFuture<F> {
     fn poll_inner(&mut self, cx: &mut Context<'_>) -> Poll<F::Output> {
         let future_id = EntityId::new(self.future_handle.id().as_str());
+        super::note_application_runtime();
+        self.poll_stamp.note_poll();
         if let Ok(mut db) = runtime_db().lock() {
             let _ = db.link_entity_to_current_task_scope(&future_id);
         }
@@ -226,10 +437,26 @@ impl<F: Future> InstrumentedFuture<F> {
             transition_relation_edge(&future_id, self.backtrace, relation, Some(EdgeKind::Polls));
         }
 
-        let poll = unsafe { Pin::new_unchecked(&mut self.inner) }.poll(cx);
+        let waker = self
+            .wake
+            .waker(cx.waker(), || Some(EntityId::new(future_id.as_str())), None);
+        let outer_nested = NESTED_POLL_TIME
+            .try_with(|nested| nested.replace(Duration::ZERO))
+            .unwrap_or_default();
+        let started = Instant::now();
+        let poll =
+            unsafe { Pin::new_unchecked(&mut self.inner) }.poll(&mut Context::from_waker(&waker));
+        let elapsed = started.elapsed();
+        let nested = NESTED_POLL_TIME
+            .try_with(|nested| nested.replace(outer_nested + elapsed))
+            .unwrap_or_default();
         FUTURE_CAUSAL_STACK.with(|stack| {
             stack.borrow_mut().pop();
         });
+        if self.poll_stats.add(elapsed) || poll.is_ready() {
+            self.poll_stats.publish(&future_id);
+        }
+        note_long_poll(&future_id, elapsed.saturating_sub(nested));
 
         match poll {
             Poll::Pending => {
@@ -258,6 +485,8 @@ impl<F: Future> InstrumentedFuture<F> {
                 if let Some(relation) = self.waits_on.as_mut() {
                     transition_relation_edge(&future_id, self.backtrace, relation, None);
                 }
+                self.finished = true;
+                record_future_outcome(&future_id, FutureOutcome::Ok, None);
                 Poll::Ready(output)
             }
         }
@@ -290,6 +519,23 @@ impl<F> Drop for InstrumentedFuture<F> {
         if let Some(relation) = self.waits_on.as_mut() {
             transition_relation_edge(&future_id, self.backtrace, relation, None);
         }
+        if self.poll_stats.unpublished() {
+            self.poll_stats.publish(&future_id);
+        }
+        if !self.finished {
+            let (outcome, backtrace) = if std::thread::panicking() {
+                (FutureOutcome::Panicked, super::capture_backtrace_id())
+            } else if let Some(backtrace) = self.abort_note.as_ref().and_then(AbortNote::backtrace)
+            {
+                (FutureOutcome::Aborted, backtrace)
+            } else {
+                (
+                    FutureOutcome::CancelledByDrop,
+                    super::capture_backtrace_id(),
+                )
+            };
+            record_future_outcome(&future_id, outcome, Some(backtrace));
+        }
     }
 }
 
@@ -317,3 +563,138 @@ where
 {
     InstrumentedFuture::new(fut.into_future(), handle, on)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SettingsOverride;
+    use moire_types::{RuntimeSettingChange, WaiterWaitStats};
+    use std::task::Waker;
+
+    fn long_polls_on(id: &EntityId) -> Vec<u64> {
+        let db = runtime_db().lock().unwrap();
+        db.events
+            .iter()
+            .filter(|event| matches!(&event.target, EventTarget::Entity(target) if target == id))
+            .filter_map(|event| match &event.kind {
+                EventKind::LongPoll(long_poll) => Some(long_poll.duration_ns),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // r[verify model.future.poll-time]
+    #[test]
+    fn long_polls_are_reported_on_the_innermost_slow_future() {
+        let _settings = SettingsOverride::apply([
+            RuntimeSettingChange::BacktraceCapture(false),
+            RuntimeSettingChange::LongPollThresholdMs(10),
+        ]);
+        let inner = instrument_future(
+            "long-poll-inner",
+            async { std::thread::sleep(Duration::from_millis(30)) },
+            None,
+            None,
+        );
+        let inner_id = EntityId::new(inner.future_handle.id().as_str());
+        let outer = instrument_future("long-poll-outer", inner, None, None);
+        let outer_id = EntityId::new(outer.future_handle.id().as_str());
+
+        let poll = std::pin::pin!(outer).poll(&mut Context::from_waker(Waker::noop()));
+
+        assert!(poll.is_ready());
+        let inner_polls = long_polls_on(&inner_id);
+        assert_eq!(inner_polls.len(), 1);
+        assert!(inner_polls[0] >= 30_000_000);
+        assert_eq!(long_polls_on(&outer_id), []);
+    }
+
+    /// Runs one operation by `waiter` on `resource` that is pending for
+    /// `wait` before completing.
+    fn wait_once(resource: &EntityId, waiter: &EntityId, wait: Duration) {
+        let mut pending = true;
+        let inner = std::future::poll_fn(|_| {
+            if std::mem::take(&mut pending) {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        });
+        let mut operation = std::pin::pin!(OperationFuture::new_with_actor(
+            inner,
+            resource.clone(),
+            Some(waiter.clone())
+        ));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(operation.as_mut().poll(&mut cx).is_pending());
+        std::thread::sleep(wait);
+        assert!(operation.as_mut().poll(&mut cx).is_ready());
+    }
+
+    // r[verify model.entity.wait-stats]
+    #[test]
+    fn waits_are_counted_per_resource_and_per_waiter() {
+        let _settings = SettingsOverride::apply([RuntimeSettingChange::BacktraceCapture(false)]);
+        let resource = EntityHandle::new("wait-stats-resource", FutureEntity::default());
+        let busy = EntityHandle::new("wait-stats-busy", FutureEntity::default());
+        let idle = EntityHandle::new("wait-stats-idle", FutureEntity::default());
+
+        wait_once(resource.id(), idle.id(), Duration::from_millis(1));
+        wait_once(resource.id(), busy.id(), Duration::from_millis(5));
+        wait_once(resource.id(), busy.id(), Duration::from_millis(5));
+        // Completing without suspending is not a wait.
+        let ready = OperationFuture::new_with_actor(
+            std::future::ready(()),
+            resource.id().clone(),
+            Some(idle.id().clone()),
+        );
+        assert!(
+            std::pin::pin!(ready)
+                .poll(&mut Context::from_waker(Waker::noop()))
+                .is_ready()
+        );
+
+        let db = runtime_db().lock().unwrap();
+        let entity = &db.entities[resource.id()];
+        let stats = entity.wait_stats.as_ref().expect("resource has wait stats");
+        assert_eq!(stats.count, 3);
+        assert!(stats.max_ns >= 5_000_000);
+        assert_eq!(stats.buckets.iter().sum::<u64>(), 3);
+        let waiters: Vec<_> = entity
+            .waiter_wait_stats
+            .iter()
+            .map(|entry| (entry.waiter.as_str(), entry.stats.count))
+            .collect();
+        assert_eq!(waiters, [(busy.id().as_str(), 2), (idle.id().as_str(), 1)]);
+        let total: u64 = entity
+            .waiter_wait_stats
+            .iter()
+            .map(|entry| entry.stats.total_ns)
+            .sum();
+        assert_eq!(total, stats.total_ns);
+    }
+
+    #[test]
+    fn waiter_wait_stats_keep_the_busiest_waiters() {
+        let mut list = Vec::new();
+        for waiter in 0..WaiterWaitStats::MAX_WAITERS as u64 {
+            WaiterWaitStats::record(
+                &mut list,
+                &EntityId::new(format!("w{waiter}")),
+                100 + waiter,
+            );
+        }
+        // A newcomer that waited less than everyone kept is not recorded.
+        WaiterWaitStats::record(&mut list, &EntityId::new("brief"), 50);
+        assert_eq!(list.len(), WaiterWaitStats::MAX_WAITERS);
+        assert!(list.iter().all(|entry| entry.waiter.as_str() != "brief"));
+        assert_eq!(list.last().unwrap().waiter.as_str(), "w0");
+
+        // One that waited longer replaces the least-waiting actor.
+        WaiterWaitStats::record(&mut list, &EntityId::new("late"), 1_000);
+        assert_eq!(list.len(), WaiterWaitStats::MAX_WAITERS);
+        assert_eq!(list[0].waiter.as_str(), "late");
+        assert!(list.iter().all(|entry| entry.waiter.as_str() != "w0"));
+        assert_eq!(list.last().unwrap().waiter.as_str(), "w1");
+    }
+}
@@ -4,8 +4,10 @@ use moire_types::{
 };
 use std::marker::PhantomData;
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 
 use super::db::runtime_db;
+use super::redact::redact_entity_body;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct EntityRef {
@@ -125,9 +127,10 @@ impl<S> Clone for EntityHandle<S> {
 }
 
 impl<S> EntityHandle<S> {
-    fn from_entity(entity: Entity) -> Self {
+    fn from_entity(mut entity: Entity) -> Self {
         let kind_name = entity.body.kind_name();
         let id = EntityId::new(entity.id.as_str());
+        redact_entity_body(&mut entity.body);
 
         if let Ok(mut db) = runtime_db().lock() {
             db.upsert_entity(entity);
@@ -155,6 +158,7 @@ impl<S> EntityHandle<S> {
         &self.inner.id
     }
 
+    // r[impl api.rename]
     pub fn rename(&self, name: impl Into<String>) -> bool {
         let mut db = runtime_db()
             .lock()
@@ -166,6 +170,16 @@ impl<S> EntityHandle<S> {
         self.inner.kind_name
     }
 
+    /// Adds one completed wait on this entity by `waiter` to its wait
+    /// statistics, for waits that don't go through an operation future
+    /// (blocking locks).
+    pub fn record_wait(&self, waiter: Option<&EntityRef>, wait: Duration) -> bool {
+        let wait_ns = wait.as_nanos().min(u64::MAX as u128) as u64;
+        runtime_db().lock().is_ok_and(|mut db| {
+            db.record_entity_wait(self.id(), waiter.map(EntityRef::id), wait_ns)
+        })
+    }
+
     pub fn entity_ref(&self) -> EntityRef {
         EntityRef {
             id: EntityId::new(self.inner.id.as_str()),
@@ -197,10 +211,7 @@ where
             );
         }
 
-        let mut db = runtime_db()
-            .lock()
-            .expect("runtime db lock poisoned during entity mutate");
-        db.mutate_entity_body_and_maybe_upsert(self.id(), |body| {
+        mutate_entity_body(self.id(), |body| {
             let slot = S::project_mut(body).unwrap_or_else(|| {
                 panic!(
                     "entity body projection failed after kind check: kind={} entity_id={}",
@@ -213,6 +224,24 @@ where
     }
 }
 
+/// Applies `mutate` to the body of `id`. A change to redacted fields is
+/// redacted after the db lock is released, then stored under it again.
+fn mutate_entity_body(id: &EntityId, mutate: impl FnOnce(&mut EntityBody)) -> bool {
+    let (changed, held) = runtime_db()
+        .lock()
+        .expect("runtime db lock poisoned during entity mutate")
+        .mutate_entity_body_holding_redacted_fields(id, mutate);
+    let Some(mut fields) = held else {
+        return changed;
+    };
+    fields.redact();
+    runtime_db()
+        .lock()
+        .expect("runtime db lock poisoned during entity mutate")
+        .store_redacted_fields(id, fields)
+        || changed
+}
+
 impl<S> EntityHandle<S> {
     /// Emit a custom event on this entity.
     pub fn emit_event(
@@ -251,6 +280,16 @@ pub struct WeakEntityHandle<S> {
     _slot: PhantomData<S>,
 }
 
+impl<S> WeakEntityHandle<S> {
+    /// Returns a strong handle if the entity is still alive.
+    pub fn upgrade(&self) -> Option<EntityHandle<S>> {
+        self.inner.upgrade().map(|inner| EntityHandle {
+            inner,
+            _slot: PhantomData,
+        })
+    }
+}
+
 impl<S> Clone for WeakEntityHandle<S> {
     fn clone(&self) -> Self {
         Self {
@@ -278,10 +317,7 @@ where
         let Some(inner) = self.inner.upgrade() else {
             return false;
         };
-        let mut db = runtime_db()
-            .lock()
-            .expect("runtime db lock poisoned during weak entity mutate");
-        db.mutate_entity_body_and_maybe_upsert(&inner.id, |body| {
+        mutate_entity_body(&inner.id, |body| {
             let slot = S::project_mut(body).unwrap_or_else(|| {
                 panic!(
                     "entity body projection failed: kind={} entity_id={}",
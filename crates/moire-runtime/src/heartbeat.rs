@@ -0,0 +1,179 @@
+use moire_types::{EntityId, PTime};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+use moire_wire::{ClientMessage, Heartbeat, encode_client_message_default};
+
+use super::{DASHBOARD_RECONNECT_DELAY_MS, HEARTBEAT_INTERVAL_MS, LAST_ACTIVE_CAPACITY};
+
+static RUNTIME_TICKS: AtomicU64 = AtomicU64::new(0);
+static PROBE_IN_FLIGHT: AtomicBool = AtomicBool::new(false);
+static POLL_STAMPS: StdMutex<Vec<Weak<PollStamp>>> = StdMutex::new(Vec::new());
+
+/// When an instrumented future was last polled. Each future owns its stamp,
+/// so a poll only stores an atomic; the heartbeat thread ranks the stamps
+/// when it ticks.
+pub(crate) struct PollStamp {
+    entity_id: EntityId,
+    /// Nanoseconds since [`stamp_clock`] started, or zero before the first poll.
+    last_poll_ns: AtomicU64,
+}
+
+impl PollStamp {
+    /// Creates the stamp for `entity_id` and makes it visible to heartbeats.
+    pub(crate) fn register(entity_id: EntityId) -> Arc<Self> {
+        let stamp = Arc::new(Self {
+            entity_id,
+            last_poll_ns: AtomicU64::new(0),
+        });
+        if let Ok(mut stamps) = POLL_STAMPS.lock() {
+            // Heartbeats may never run, so drop dead stamps here too, once per
+            // doubling of the list.
+            if stamps.len() == stamps.capacity() {
+                stamps.retain(|stamp| stamp.strong_count() > 0);
+            }
+            stamps.push(Arc::downgrade(&stamp));
+        }
+        stamp
+    }
+
+    /// Records that the future is being polled, for the heartbeat's
+    /// last-active list.
+    pub(crate) fn note_poll(&self) {
+        let now_ns = u64::try_from(stamp_clock().elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.last_poll_ns.store(now_ns.max(1), Ordering::Relaxed);
+    }
+}
+
+fn stamp_clock() -> Instant {
+    static STARTED: OnceLock<Instant> = OnceLock::new();
+    *STARTED.get_or_init(Instant::now)
+}
+
+/// The live futures polled most recently, newest first, at most `limit`.
+/// Stamps of dropped futures are removed along the way.
+fn most_recently_polled(stamps: &mut Vec<Weak<PollStamp>>, limit: usize) -> Vec<EntityId> {
+    let mut polled = Vec::new();
+    stamps.retain(|stamp| {
+        let Some(stamp) = stamp.upgrade() else {
+            return false;
+        };
+        let last_poll_ns = stamp.last_poll_ns.load(Ordering::Relaxed);
+        if last_poll_ns > 0 {
+            polled.push((last_poll_ns, stamp.entity_id.clone()));
+        }
+        true
+    });
+    polled.sort_unstable_by_key(|(last_poll_ns, _)| std::cmp::Reverse(*last_poll_ns));
+    polled.truncate(limit);
+    polled.into_iter().map(|(_, entity_id)| entity_id).collect()
+}
+
+/// Spawns a no-op probe onto the application runtime. The tick counter only
+/// advances when that runtime actually gets around to running it.
+fn probe_runtime() -> Option<u64> {
+    let handle = super::application_runtime()?;
+    if !PROBE_IN_FLIGHT.swap(true, Ordering::AcqRel) {
+        handle.spawn(async {
+            RUNTIME_TICKS.fetch_add(1, Ordering::Relaxed);
+            PROBE_IN_FLIGHT.store(false, Ordering::Release);
+        });
+    }
+    Some(RUNTIME_TICKS.load(Ordering::Relaxed))
+}
+
+// r[impl wire.heartbeat]
+pub(super) fn init_heartbeat_thread(addr: String) {
+    let _ = std::thread::Builder::new()
+        .name(String::from("moire-heartbeat"))
+        .spawn(move || {
+            loop {
+                let _ = run_heartbeat_session(&addr);
+                std::thread::sleep(Duration::from_millis(DASHBOARD_RECONNECT_DELAY_MS));
+            }
+        });
+}
+
+fn run_heartbeat_session(addr: &str) -> Result<(), String> {
+    let mut stream = super::transport::connect_blocking(addr)?;
+    stream
+        .write_all(&moire_wire::encode_protocol_magic())
+        .map_err(|e| format!("write protocol magic: {e}"))?;
+
+    loop {
+        let runtime_ticks = probe_runtime();
+        let last_active = match POLL_STAMPS.lock() {
+            Ok(mut stamps) => most_recently_polled(&mut stamps, LAST_ACTIVE_CAPACITY),
+            Err(_) => Vec::new(),
+        };
+        let heartbeat = ClientMessage::Heartbeat(Heartbeat {
+            process_id: super::runtime_process_id(),
+            ptime_now_ms: PTime::now().as_millis(),
+            runtime_ticks,
+            last_active,
+            token: super::transport::dashboard_token(),
+        });
+        let frame = encode_client_message_default(&heartbeat)
+            .map_err(|e| format!("encode heartbeat: {e}"))?;
+        stream
+            .write_all(&frame)
+            .and_then(|()| stream.flush())
+            .map_err(|e| format!("write heartbeat: {e}"))?;
+        std::thread::sleep(Duration::from_millis(HEARTBEAT_INTERVAL_MS));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_active_lists_live_polled_futures_newest_first() {
+        let stamp = |name| {
+            Arc::new(PollStamp {
+                entity_id: EntityId::new(name),
+                last_poll_ns: AtomicU64::new(0),
+            })
+        };
+        let (first, never, dropped, last) = (
+            stamp("first"),
+            stamp("never"),
+            stamp("dropped"),
+            stamp("last"),
+        );
+        let mut stamps: Vec<_> = [&first, &never, &dropped, &last]
+            .into_iter()
+            .map(Arc::downgrade)
+            .collect();
+
+        first.note_poll();
+        std::thread::sleep(Duration::from_millis(1));
+        dropped.note_poll();
+        std::thread::sleep(Duration::from_millis(1));
+        last.note_poll();
+        drop(dropped);
+
+        let ids = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| EntityId::new(*name))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            most_recently_polled(&mut stamps, 16),
+            ids(&["last", "first"])
+        );
+        assert_eq!(stamps.len(), 3);
+        assert_eq!(most_recently_polled(&mut stamps, 1), ids(&["last"]));
+
+        // Polling again moves a future back to the front.
+        std::thread::sleep(Duration::from_millis(1));
+        first.note_poll();
+        assert_eq!(
+            most_recently_polled(&mut stamps, 16),
+            ids(&["first", "last"])
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use moire_types::EntityId;
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+
+use super::HELD_MUTEX_STACK;
+
+/// A lock id on the acquiring thread's [`HELD_MUTEX_STACK`], removed again
+/// when this is dropped.
+///
+/// The entry keeps a handle to that thread's stack, so an owned guard dropped
+/// on another thread removes it all the same, even once the acquiring thread
+/// has exited.
+pub struct HeldMutexEntry {
+    id: EntityId,
+    stack: Arc<StdMutex<Vec<EntityId>>>,
+}
+
+impl HeldMutexEntry {
+    pub fn push(id: EntityId) -> Self {
+        let stack = HELD_MUTEX_STACK.with(Arc::clone);
+        lock(&stack).push(id.clone());
+        Self { id, stack }
+    }
+}
+
+impl Drop for HeldMutexEntry {
+    fn drop(&mut self) {
+        let mut stack = lock(&self.stack);
+        if let Some(pos) = stack
+            .iter()
+            .rposition(|held| held.as_str() == self.id.as_str())
+        {
+            stack.remove(pos);
+        }
+    }
+}
+
+fn lock(stack: &StdMutex<Vec<EntityId>>) -> StdMutexGuard<'_, Vec<EntityId>> {
+    stack.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack() -> Vec<String> {
+        HELD_MUTEX_STACK.with(|stack| {
+            lock(stack)
+                .iter()
+                .map(|id| id.as_str().to_owned())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn entries_released_on_another_thread_leave_the_owning_stack() {
+        let moved = HeldMutexEntry::push(EntityId::new("moved"));
+        let kept = HeldMutexEntry::push(EntityId::new("kept"));
+        std::thread::spawn(move || drop(moved)).join().unwrap();
+        assert_eq!(stack(), ["kept"]);
+
+        drop(kept);
+        assert!(stack().is_empty());
+    }
+
+    #[test]
+    fn entries_outliving_their_thread_release_its_stack() {
+        let entry = std::thread::spawn(|| HeldMutexEntry::push(EntityId::new("orphan")))
+            .join()
+            .unwrap();
+        // The exited thread's stack is kept alive by the entry alone.
+        assert_eq!(Arc::strong_count(&entry.stack), 1);
+        assert_eq!(lock(&entry.stack).len(), 1);
+
+        let stack = Arc::downgrade(&entry.stack);
+        drop(entry);
+        assert!(stack.upgrade().is_none());
+        assert!(self::stack().is_empty());
+    }
+}
@@ -8,39 +8,68 @@ use moire_types::{
     ProcessScopeBody, ScopeBody, ScopeId, TaskScopeBody, next_process_id,
 };
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Bound;
-use std::sync::{Mutex as StdMutex, OnceLock};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
 
-pub(crate) const MAX_EVENTS: usize = 16_384;
+// Defaults for settings the dashboard can change at runtime, see `settings`.
+pub(crate) const DEFAULT_MAX_EVENTS: usize = 16_384;
 pub(crate) const MAX_CHANGES_BEFORE_COMPACT: usize = 65_536;
 pub(crate) const COMPACT_TARGET_CHANGES: usize = 8_192;
 pub(crate) const DASHBOARD_PUSH_MAX_CHANGES: u32 = 2048;
-pub(crate) const DASHBOARD_PUSH_INTERVAL_MS: u64 = 100;
+pub(crate) const DEFAULT_DASHBOARD_PUSH_INTERVAL_MS: u64 = 100;
+pub(crate) const DEFAULT_LONG_POLL_THRESHOLD_MS: u64 = 10;
 pub(crate) const DASHBOARD_RECONNECT_DELAY_MS: u64 = 500;
+pub(crate) const SESSION_RESUME_TIMEOUT_MS: u64 = 5000;
+pub(crate) const HEARTBEAT_INTERVAL_MS: u64 = 1000;
+pub(crate) const LAST_ACTIVE_CAPACITY: usize = 16;
+pub(crate) const MAX_CALLSITES: usize = 4096;
 
 tokio::task_local! {
     pub static FUTURE_CAUSAL_STACK: RefCell<Vec<EntityId>>;
 }
 thread_local! {
-    pub static HELD_MUTEX_STACK: RefCell<Vec<EntityId>> = const { RefCell::new(Vec::new()) };
+    /// Lock ids held on this thread, innermost last. Shared so that an entry
+    /// released on another thread can still remove itself (see
+    /// [`HeldMutexEntry`]).
+    pub static HELD_MUTEX_STACK: Arc<StdMutex<Vec<EntityId>>> = Arc::default();
 }
 
 pub(crate) mod api;
 pub(crate) mod dashboard;
 pub(crate) mod db;
+pub(crate) mod deferred;
+pub(crate) mod diagnostics;
+pub(crate) mod dump;
 pub(crate) mod futures;
 pub(crate) mod handles;
+pub(crate) mod heartbeat;
+pub(crate) mod held_mutex;
+pub(crate) mod panic_hook;
+pub(crate) mod redact;
+pub(crate) mod settings;
+pub(crate) mod transport;
+pub(crate) mod wake;
 
 pub use self::api::*;
+pub use self::deferred::{DeferredUpdate, defer_update};
 pub use self::futures::*;
 pub use self::handles::*;
+pub use self::held_mutex::HeldMutexEntry;
+pub use self::panic_hook::PanicHook;
+pub use self::redact::{REDACTED, Redaction};
+pub use self::settings::{
+    KindDetail, SettingsOverride, apply_setting_change, kind_detail_enabled,
+    send_provenance_enabled,
+};
+pub use self::wake::OperatingOn;
 
 static PROCESS_SCOPE: OnceLock<ScopeHandle> = OnceLock::new();
 static PROCESS_ID: OnceLock<ProcessId> = OnceLock::new();
 static BACKTRACE_RECORDS: OnceLock<StdMutex<BTreeMap<BacktraceId, moire_wire::BacktraceRecord>>> =
     OnceLock::new();
 static MODULE_STATE: OnceLock<StdMutex<ModuleState>> = OnceLock::new();
+static APPLICATION_RUNTIME: OnceLock<tokio::runtime::Handle> = OnceLock::new();
 
 #[derive(Default)]
 struct ModuleState {
@@ -52,12 +81,20 @@ struct ModuleState {
 // r[impl process.auto-init]
 #[ctor]
 fn init_diagnostics_runtime() {
-    validate_frame_pointers_or_panic();
+    if !frame_pointer_check_skipped() {
+        validate_frame_pointers_or_panic();
+    }
     init_runtime_from_macro();
 }
 
+/// `MOIRE_SKIP_FRAME_POINTER_CHECK=1` opts out of the startup check, for
+/// environments where the sanity walk fails although nothing relies on it.
+fn frame_pointer_check_skipped() -> bool {
+    moire_wire::non_empty_env("MOIRE_SKIP_FRAME_POINTER_CHECK").as_deref() == Some("1")
+}
+
 pub fn init_runtime_from_macro() {
-    let process_name = std::env::current_exe().unwrap().display().to_string();
+    let process_name = process_name();
     PROCESS_SCOPE.get_or_init(|| {
         ScopeHandle::new(
             process_name.clone(),
@@ -67,6 +104,34 @@ pub fn init_runtime_from_macro() {
         )
     });
     dashboard::init_dashboard_push_loop(&process_name);
+    dump::init_dump_signal_handler(&process_name);
+    settings::init_long_poll_threshold_from_env();
+    match PanicHook::from_env() {
+        Ok(Some(hook)) => hook.install(),
+        Ok(None) => {}
+        Err(e) => diagnostics::report_config_error(format!("ignoring MOIRE_PANIC_HOOK: {e}")),
+    }
+}
+
+pub(crate) fn process_name() -> String {
+    std::env::current_exe().unwrap().display().to_string()
+}
+
+/// Remembers the first tokio runtime that polls an instrumented future.
+pub(crate) fn note_application_runtime() {
+    if APPLICATION_RUNTIME.get().is_some() {
+        return;
+    }
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    if APPLICATION_RUNTIME.set(handle.clone()).is_ok() {
+        dashboard::start_deferred_push_loop(&handle);
+    }
+}
+
+pub(crate) fn application_runtime() -> Option<&'static tokio::runtime::Handle> {
+    APPLICATION_RUNTIME.get()
 }
 
 pub(crate) fn runtime_process_id() -> ProcessId {
@@ -74,6 +139,44 @@ pub(crate) fn runtime_process_id() -> ProcessId {
 }
 
 pub(crate) fn capture_backtrace_id() -> BacktraceId {
+    if !settings::backtrace_capture_enabled() {
+        return capture_disabled_backtrace_id();
+    }
+    capture_fresh_backtrace_id()
+}
+
+/// Backtrace shared by everything created while capture is off: a single
+/// frame in a placeholder module, so the dashboard can tell it apart from a
+/// real call site.
+fn capture_disabled_backtrace_id() -> BacktraceId {
+    static CAPTURE_DISABLED_BACKTRACE: OnceLock<BacktraceId> = OnceLock::new();
+    *CAPTURE_DISABLED_BACKTRACE.get_or_init(|| {
+        let backtrace_id = BacktraceId::next()
+            .expect("backtrace id invariant violated: generated id must be valid and JS-safe");
+        let Ok(mut modules) = module_state().lock() else {
+            panic!("module state mutex poisoned; cannot continue");
+        };
+        let runtime_base =
+            RuntimeBase::new(1).expect("invariant violated: placeholder runtime base is valid");
+        let module_id = global_module_id(
+            &mut modules,
+            moire_wire::CAPTURE_DISABLED_MODULE_PATH,
+            runtime_base,
+        );
+        drop(modules);
+        let frame = FrameKey {
+            module_id,
+            rel_pc: RelPc::new(0).expect("invariant violated: zero rel_pc is JS-safe"),
+        };
+        remember_backtrace_record(
+            moire_wire::BacktraceRecord::new(backtrace_id, vec![frame])
+                .expect("invariant violated: placeholder backtrace has a frame"),
+        );
+        backtrace_id
+    })
+}
+
+fn capture_fresh_backtrace_id() -> BacktraceId {
     let backtrace_id = BacktraceId::next()
         .expect("backtrace id invariant violated: generated id must be valid and JS-safe");
 
@@ -96,6 +199,30 @@ fn module_identity_for(path: &str, runtime_base: RuntimeBase) -> moire_wire::Mod
     moire_wire::ModuleIdentity::DebugId(format!("runtime:{:x}:{path}", runtime_base.get()))
 }
 
+/// Returns the process-wide id of a module, adding it to the manifest the
+/// first time it is seen.
+fn global_module_id(modules: &mut ModuleState, path: &str, runtime_base: RuntimeBase) -> ModuleId {
+    let key = (runtime_base, path.to_string());
+    if let Some(existing) = modules.by_key.get(&key).copied() {
+        return existing;
+    }
+    let global = ModuleId::next()
+        .expect("invariant violated: generated module id must be valid and JS-safe");
+    modules.by_id.insert(
+        global,
+        moire_wire::ModuleManifestEntry {
+            module_id: global,
+            module_path: key.1.clone(),
+            runtime_base: key.0,
+            identity: module_identity_for(&key.1, key.0),
+            arch: std::env::consts::ARCH.to_string(),
+        },
+    );
+    modules.by_key.insert(key, global);
+    modules.revision = modules.revision.saturating_add(1);
+    global
+}
+
 fn remap_and_register_backtrace(captured: CapturedBacktrace) -> moire_wire::BacktraceRecord {
     let Ok(mut modules) = module_state().lock() else {
         panic!("module state mutex poisoned; cannot continue");
@@ -103,26 +230,7 @@ fn remap_and_register_backtrace(captured: CapturedBacktrace) -> moire_wire::Back
 
     let mut local_to_global: BTreeMap<ModuleId, ModuleId> = BTreeMap::new();
     for module in &captured.modules {
-        let key = (module.runtime_base, module.path.as_str().to_string());
-        let global = if let Some(existing) = modules.by_key.get(&key).copied() {
-            existing
-        } else {
-            let global = ModuleId::next()
-                .expect("invariant violated: generated module id must be valid and JS-safe");
-            modules.by_key.insert(key.clone(), global);
-            modules.by_id.insert(
-                global,
-                moire_wire::ModuleManifestEntry {
-                    module_id: global,
-                    module_path: key.1.clone(),
-                    runtime_base: key.0,
-                    identity: module_identity_for(&key.1, key.0),
-                    arch: std::env::consts::ARCH.to_string(),
-                },
-            );
-            modules.revision = modules.revision.saturating_add(1);
-            global
-        };
+        let global = global_module_id(&mut modules, module.path.as_str(), module.runtime_base);
         local_to_global.insert(module.id, global);
     }
 
@@ -264,7 +372,56 @@ pub fn new_event(target: EventTarget, kind: EventKind) -> Event {
     Event::new(target, kind, capture_backtrace_id())
 }
 
-pub fn record_event(event: Event) {
+/// Backtrace IDs by call path, for [`capture_callsite_backtrace`]. Holds at
+/// most [`MAX_CALLSITES`] paths; once full it starts over, so a path seen
+/// again after that gets one new ID.
+#[derive(Default)]
+struct CallsiteIds {
+    ids: HashMap<Vec<FrameKey>, BacktraceId>,
+}
+
+impl CallsiteIds {
+    /// Returns the ID already given to `frames`, or `None` after remembering
+    /// `id` for them.
+    fn intern(&mut self, frames: &[FrameKey], id: BacktraceId) -> Option<BacktraceId> {
+        if let Some(existing) = self.ids.get(frames) {
+            return Some(*existing);
+        }
+        if self.ids.len() >= MAX_CALLSITES {
+            self.ids.clear();
+        }
+        self.ids.insert(frames.to_vec(), id);
+        None
+    }
+}
+
+/// Captures the current backtrace for instrumentation that groups things by
+/// call site. Unlike entity and event backtraces, a capture identical to an
+/// earlier one returns the earlier ID, so captures from the same call path
+/// compare equal.
+pub fn capture_callsite_backtrace() -> BacktraceId {
+    static CALLSITES: OnceLock<StdMutex<CallsiteIds>> = OnceLock::new();
+    if !settings::backtrace_capture_enabled() {
+        return capture_backtrace_id();
+    }
+    let backtrace_id = BacktraceId::next()
+        .expect("backtrace id invariant violated: generated id must be valid and JS-safe");
+    let captured = capture_current(backtrace_id, CaptureOptions::default()).unwrap_or_else(|err| {
+        panic!("failed to capture backtrace for enabled API boundary: {err}")
+    });
+    let remapped = remap_and_register_backtrace(captured);
+    let Ok(mut callsites) = CALLSITES.get_or_init(Default::default).lock() else {
+        panic!("callsite backtrace mutex poisoned; cannot continue");
+    };
+    if let Some(existing) = callsites.intern(&remapped.frames, backtrace_id) {
+        return existing;
+    }
+    remember_backtrace_record(remapped);
+    backtrace_id
+}
+
+pub fn record_event(mut event: Event) {
+    redact::redact_event_kind(&mut event.kind);
     if let Ok(mut db) = db::runtime_db().lock() {
         db.record_event(event);
     }
@@ -288,6 +445,7 @@ pub fn record_custom_event(
 }
 
 pub fn record_event_with_entity_source(mut event: Event, entity_id: &EntityId) {
+    redact::redact_event_kind(&mut event.kind);
     if let Ok(mut db) = db::runtime_db().lock() {
         if let Some(entity) = db.entities.get(entity_id) {
             event.backtrace = entity.backtrace;
@@ -315,4 +473,56 @@ mod tests {
                 && format!("{second}").starts_with("BACKTRACE#")
         );
     }
+
+    #[test]
+    fn capture_disabled_backtrace_is_a_placeholder_frame() {
+        let id = capture_disabled_backtrace_id();
+        assert_eq!(capture_disabled_backtrace_id(), id);
+
+        let record = backtrace_records().lock().unwrap()[&id].clone();
+        let [frame] = record.frames.as_slice() else {
+            panic!("placeholder backtrace must have exactly one frame");
+        };
+        let (_, manifest) = module_manifest_snapshot();
+        let module = manifest
+            .iter()
+            .find(|module| module.module_id == frame.module_id)
+            .expect("placeholder module is in the manifest");
+        assert_eq!(module.module_path, moire_wire::CAPTURE_DISABLED_MODULE_PATH);
+    }
+
+    // r[verify model.channel.queued-by]
+    #[test]
+    fn callsite_ids_are_shared_per_path_and_bounded() {
+        let module_id = ModuleId::next().unwrap();
+        let frames = |rel_pc| {
+            vec![FrameKey {
+                module_id,
+                rel_pc: RelPc::new(rel_pc).unwrap(),
+            }]
+        };
+        let mut callsites = CallsiteIds::default();
+        let first = BacktraceId::next().unwrap();
+        assert_eq!(callsites.intern(&frames(0), first), None);
+        assert_eq!(
+            callsites.intern(&frames(0), BacktraceId::next().unwrap()),
+            Some(first)
+        );
+
+        for rel_pc in 1..MAX_CALLSITES as u64 {
+            assert_eq!(
+                callsites.intern(&frames(rel_pc), BacktraceId::next().unwrap()),
+                None
+            );
+        }
+        assert_eq!(callsites.ids.len(), MAX_CALLSITES);
+        // Full: a new path starts the map over.
+        let last = BacktraceId::next().unwrap();
+        assert_eq!(callsites.intern(&frames(MAX_CALLSITES as u64), last), None);
+        assert_eq!(callsites.ids.len(), 1);
+        assert_eq!(
+            callsites.intern(&frames(MAX_CALLSITES as u64), first),
+            Some(last)
+        );
+    }
 }
@@ -0,0 +1,265 @@
+use std::any::Any;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, RwLock, TryLockError};
+use std::time::Duration;
+
+use moire_trace_types::BacktraceId;
+use moire_types::{EntityBody, EntityId, Event, EventKind, EventTarget, FutureOutcome, PanicEvent};
+
+use super::FUTURE_CAUSAL_STACK;
+use super::db::{RuntimeDb, runtime_db};
+use super::diagnostics::report;
+use super::redact::redact_panic;
+use moire_wire::non_empty_env;
+
+/// How long the hook waits for the runtime db before giving up. The panicking
+/// thread may be the one holding it.
+const DB_LOCK_ATTEMPTS: u32 = 50;
+const DB_LOCK_RETRY: Duration = Duration::from_millis(1);
+
+/// Opt-in panic hook that records panics in the runtime graph.
+///
+/// When a task polled through moire futures panics, the hook marks the task's
+/// future entity as [`FutureOutcome::Panicked`] and records a `panicked` event on
+/// it with the message, location and backtrace. With
+/// [`PanicHook::write_snapshot_to`], it also writes a snapshot dump of the whole
+/// graph as it was at the panic. The previously installed hook still runs
+/// afterwards.
+#[derive(Clone, Default)]
+pub struct PanicHook {
+    dump_dir: Option<PathBuf>,
+}
+
+static INSTALLED: RwLock<Option<PanicHook>> = RwLock::new(None);
+
+impl PanicHook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads `MOIRE_PANIC_HOOK`: `record` (or `1`, `on`, `true`) records panics,
+    /// `dump` also writes a snapshot to `MOIRE_DUMP_DIR`. Unset means no hook.
+    // r[impl config.panic-hook]
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(value) = non_empty_env("MOIRE_PANIC_HOOK") else {
+            return Ok(None);
+        };
+        match value.to_ascii_lowercase().as_str() {
+            "0" | "off" | "false" => Ok(None),
+            "1" | "on" | "true" | "record" => Ok(Some(Self::new())),
+            "dump" => Ok(Some(
+                Self::new().write_snapshot_to(super::dump::dump_dir_from_env()),
+            )),
+            _ => Err(format!(
+                "unsupported MOIRE_PANIC_HOOK value {value:?} (use record or dump)"
+            )),
+        }
+    }
+
+    /// Also writes a snapshot dump into `dir` on every panic.
+    pub fn write_snapshot_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dump_dir = Some(dir.into());
+        self
+    }
+
+    /// Installs the hook in front of the current one. Installing again only
+    /// replaces the options.
+    pub fn install(self) {
+        let first = {
+            let Ok(mut installed) = INSTALLED.write() else {
+                return;
+            };
+            installed.replace(self).is_none()
+        };
+        if first {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                record_panic(info);
+                previous(info);
+            }));
+        }
+    }
+}
+
+fn record_panic(info: &PanicHookInfo<'_>) {
+    let Some(hook) = INSTALLED
+        .read()
+        .ok()
+        .and_then(|installed| installed.clone())
+    else {
+        return;
+    };
+    // Redacted before taking the db lock.
+    let panicked = panicking_task_future().map(|task_id| {
+        let mut panic = panic_event(info);
+        redact_panic(&mut panic);
+        (task_id, panic)
+    });
+    let Some(mut db) = lock_for_panic(runtime_db()) else {
+        report("runtime db busy during panic, not recording it");
+        return;
+    };
+    if let Some((task_id, panic)) = panicked {
+        let backtrace = super::capture_backtrace_id();
+        record_panic_on(&mut db, &task_id, panic, backtrace);
+    }
+    // The dump locks the db itself.
+    drop(db);
+    if let Some(dir) = hook.dump_dir.as_deref() {
+        match super::dump::write_snapshot_dump(dir, &super::process_name()) {
+            Ok(path) => report(format!("wrote panic snapshot to {}", path.display())),
+            Err(e) => report(format!("panic snapshot failed: {e}")),
+        }
+    }
+}
+
+fn panic_event(info: &PanicHookInfo<'_>) -> PanicEvent {
+    PanicEvent {
+        message: panic_message(info.payload()),
+        location: info.location().map(|location| {
+            format!(
+                "{}:{}:{}",
+                location.file(),
+                location.line(),
+                location.column()
+            )
+        }),
+    }
+}
+
+fn record_panic_on(
+    db: &mut RuntimeDb,
+    task_id: &EntityId,
+    panic: PanicEvent,
+    backtrace: BacktraceId,
+) {
+    let event = Event::new(
+        EventTarget::Entity(EntityId::new(task_id.as_str())),
+        EventKind::Panicked(panic),
+        backtrace,
+    );
+    db.mutate_entity_body_and_maybe_upsert(task_id, |body| {
+        if let EntityBody::Future(future) = body
+            && future.outcome.is_none()
+        {
+            future.outcome = Some(FutureOutcome::Panicked);
+            future.outcome_backtrace = Some(backtrace);
+        }
+    });
+    db.record_event(event);
+}
+
+/// The outermost instrumented future on this thread's causal stack: for spawned
+/// tasks, the task itself.
+fn panicking_task_future() -> Option<EntityId> {
+    FUTURE_CAUSAL_STACK
+        .try_with(|stack| {
+            let stack = stack.try_borrow().ok()?;
+            stack.first().map(|id| EntityId::new(id.as_str()))
+        })
+        .ok()
+        .flatten()
+}
+
+fn lock_for_panic<T>(mutex: &Mutex<T>) -> Option<MutexGuard<'_, T>> {
+    for _ in 0..DB_LOCK_ATTEMPTS {
+        match mutex.try_lock() {
+            Ok(db) => return Some(db),
+            Err(TryLockError::WouldBlock) => std::thread::sleep(DB_LOCK_RETRY),
+            Err(TryLockError::Poisoned(_)) => return None,
+        }
+    }
+    None
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        String::from(*message)
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Box<dyn Any>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moire_types::{Entity, FutureEntity, StreamId};
+
+    fn panic_at(message: &str) -> PanicEvent {
+        PanicEvent {
+            message: String::from(message),
+            location: Some(String::from("src/lib.rs:1:1")),
+        }
+    }
+
+    #[tokio::test]
+    async fn panic_is_recorded_on_the_outermost_future() {
+        let mut db = RuntimeDb::new(StreamId(String::from("panic-hook-test")), 16);
+        let backtrace = BacktraceId::next().expect("backtrace id");
+        let task = Entity::new(
+            backtrace,
+            "task",
+            EntityBody::Future(FutureEntity::default()),
+        );
+        let task_id = EntityId::new(task.id.as_str());
+        db.upsert_entity(task);
+        let inner_id = EntityId::new("inner-future");
+
+        let stack = vec![EntityId::new(task_id.as_str()), inner_id];
+        let panicking = FUTURE_CAUSAL_STACK
+            .scope(stack.into(), async { panicking_task_future() })
+            .await
+            .expect("a future is on the causal stack");
+        assert_eq!(panicking, task_id);
+        assert_eq!(panicking_task_future(), None);
+
+        record_panic_on(&mut db, &panicking, panic_at("boom"), backtrace);
+        // A second panic on the same task keeps the first outcome.
+        record_panic_on(&mut db, &panicking, panic_at("again"), backtrace);
+
+        let EntityBody::Future(future) = &db.entities[&task_id].body else {
+            panic!("task must still be a future");
+        };
+        assert!(matches!(future.outcome, Some(FutureOutcome::Panicked)));
+        assert_eq!(future.outcome_backtrace, Some(backtrace));
+        let messages: Vec<_> = db
+            .events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::Panicked(panic) => Some(panic.message.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(messages, ["boom", "again"]);
+    }
+
+    #[test]
+    fn lock_for_panic_gives_up_on_a_held_lock() {
+        let mutex = Mutex::new(());
+        let held = mutex.lock().unwrap();
+        assert!(lock_for_panic(&mutex).is_none());
+        drop(held);
+        assert!(lock_for_panic(&mutex).is_some());
+
+        let poisoned = Mutex::new(());
+        let _ = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let _guard = poisoned.lock().unwrap();
+                    panic!("poison the lock");
+                })
+                .join()
+        });
+        assert!(lock_for_panic(&poisoned).is_none());
+    }
+
+    #[test]
+    fn panic_messages_cover_str_and_string_payloads() {
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&String::from("owned")), "owned");
+        assert_eq!(panic_message(&42u32), "Box<dyn Any>");
+    }
+}
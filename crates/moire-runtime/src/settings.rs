@@ -0,0 +1,240 @@
+use moire_types::{KindDetailSetting, RuntimeSettingChange, RuntimeSettings};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex as StdMutex, MutexGuard};
+use std::time::Duration;
+
+use super::db::runtime_db;
+use super::{DEFAULT_DASHBOARD_PUSH_INTERVAL_MS, DEFAULT_LONG_POLL_THRESHOLD_MS};
+use moire_wire::non_empty_env;
+
+const PUSH_INTERVAL_MS_RANGE: std::ops::RangeInclusive<u64> = 10..=60_000;
+const MAX_EVENTS_RANGE: std::ops::RangeInclusive<u32> = 1..=1_048_576;
+const LONG_POLL_THRESHOLD_MS_RANGE: std::ops::RangeInclusive<u64> = 0..=60_000;
+
+static PUSH_INTERVAL_MS: AtomicU64 = AtomicU64::new(DEFAULT_DASHBOARD_PUSH_INTERVAL_MS);
+static LONG_POLL_THRESHOLD_MS: AtomicU64 = AtomicU64::new(DEFAULT_LONG_POLL_THRESHOLD_MS);
+static BACKTRACE_CAPTURE: AtomicBool = AtomicBool::new(true);
+static SEND_PROVENANCE: AtomicBool = AtomicBool::new(false);
+static DETAILED_KINDS: StdMutex<BTreeSet<String>> = StdMutex::new(BTreeSet::new());
+/// Bumped whenever `DETAILED_KINDS` changes, so [`KindDetail`] caches notice.
+static DETAILED_KINDS_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) fn push_interval_ms() -> u64 {
+    PUSH_INTERVAL_MS.load(Ordering::Relaxed)
+}
+
+pub(crate) fn backtrace_capture_enabled() -> bool {
+    BACKTRACE_CAPTURE.load(Ordering::Relaxed)
+}
+
+/// Whether stamped channel sends also record who sent them and from where.
+pub fn send_provenance_enabled() -> bool {
+    SEND_PROVENANCE.load(Ordering::Relaxed)
+}
+
+/// Polls at least this slow record a `long_poll` event. `None` when turned off.
+pub(crate) fn long_poll_threshold() -> Option<Duration> {
+    match LONG_POLL_THRESHOLD_MS.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    }
+}
+
+/// Reads the starting long-poll threshold from `MOIRE_LONG_POLL_MS`.
+// r[impl config.long-poll]
+pub(crate) fn init_long_poll_threshold_from_env() {
+    let Some(value) = non_empty_env("MOIRE_LONG_POLL_MS") else {
+        return;
+    };
+    let result = value
+        .parse::<u64>()
+        .map_err(|e| format!("{value:?} is not a number of milliseconds: {e}"))
+        .and_then(|ms| apply_setting_change(&RuntimeSettingChange::LongPollThresholdMs(ms)));
+    if let Err(e) = result {
+        super::diagnostics::report_config_error(format!("ignoring MOIRE_LONG_POLL_MS: {e}"));
+    }
+}
+
+/// Whether the dashboard asked for extra detail on entities of `kind`.
+pub fn kind_detail_enabled(kind: &str) -> bool {
+    DETAILED_KINDS
+        .lock()
+        .map(|kinds| kinds.contains(kind))
+        .unwrap_or(false)
+}
+
+/// [`kind_detail_enabled`] for one kind, cached so that checking it on every
+/// send or receive costs two atomic loads. Only the first check after the
+/// detail settings change takes the settings lock.
+pub struct KindDetail {
+    kind: &'static str,
+    /// `generation << 1 | enabled`, or 0 before the first check.
+    cached: AtomicU64,
+}
+
+impl KindDetail {
+    pub const fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            cached: AtomicU64::new(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        let generation = DETAILED_KINDS_GENERATION.load(Ordering::Acquire);
+        let cached = self.cached.load(Ordering::Relaxed);
+        if cached >> 1 == generation {
+            return cached & 1 == 1;
+        }
+        let enabled = kind_detail_enabled(self.kind);
+        self.cached
+            .store(generation << 1 | u64::from(enabled), Ordering::Relaxed);
+        enabled
+    }
+}
+
+pub(crate) fn current_settings() -> RuntimeSettings {
+    let max_events = runtime_db()
+        .lock()
+        .map(|db| db.max_events())
+        .unwrap_or(super::DEFAULT_MAX_EVENTS);
+    RuntimeSettings {
+        push_interval_ms: push_interval_ms(),
+        backtrace_capture: backtrace_capture_enabled(),
+        max_events: u32::try_from(max_events).unwrap_or(u32::MAX),
+        detailed_kinds: DETAILED_KINDS
+            .lock()
+            .map(|kinds| kinds.iter().cloned().collect())
+            .unwrap_or_default(),
+        long_poll_threshold_ms: LONG_POLL_THRESHOLD_MS.load(Ordering::Relaxed),
+        send_provenance: send_provenance_enabled(),
+    }
+}
+
+/// Applies one setting change, as if the dashboard had sent it.
+// r[impl wire.control]
+pub fn apply_setting_change(change: &RuntimeSettingChange) -> Result<(), String> {
+    match change {
+        RuntimeSettingChange::PushIntervalMs(interval_ms) => {
+            if !PUSH_INTERVAL_MS_RANGE.contains(interval_ms) {
+                return Err(format!(
+                    "push_interval_ms must be in {}..={}, got {interval_ms}",
+                    PUSH_INTERVAL_MS_RANGE.start(),
+                    PUSH_INTERVAL_MS_RANGE.end()
+                ));
+            }
+            PUSH_INTERVAL_MS.store(*interval_ms, Ordering::Relaxed);
+        }
+        RuntimeSettingChange::BacktraceCapture(enabled) => {
+            BACKTRACE_CAPTURE.store(*enabled, Ordering::Relaxed);
+        }
+        RuntimeSettingChange::MaxEvents(max_events) => {
+            if !MAX_EVENTS_RANGE.contains(max_events) {
+                return Err(format!(
+                    "max_events must be in {}..={}, got {max_events}",
+                    MAX_EVENTS_RANGE.start(),
+                    MAX_EVENTS_RANGE.end()
+                ));
+            }
+            let mut db = runtime_db()
+                .lock()
+                .map_err(|_| String::from("runtime db mutex poisoned"))?;
+            db.set_max_events(*max_events as usize);
+        }
+        RuntimeSettingChange::KindDetail(setting) => {
+            if setting.kind.trim().is_empty() {
+                return Err(String::from("kind_detail.kind must be non-empty"));
+            }
+            let mut kinds = DETAILED_KINDS
+                .lock()
+                .map_err(|_| String::from("detailed kinds mutex poisoned"))?;
+            if setting.enabled {
+                kinds.insert(setting.kind.clone());
+            } else {
+                kinds.remove(&setting.kind);
+            }
+            DETAILED_KINDS_GENERATION.fetch_add(1, Ordering::Release);
+        }
+        RuntimeSettingChange::LongPollThresholdMs(threshold_ms) => {
+            if !LONG_POLL_THRESHOLD_MS_RANGE.contains(threshold_ms) {
+                return Err(format!(
+                    "long_poll_threshold_ms must be in {}..={}, got {threshold_ms}",
+                    LONG_POLL_THRESHOLD_MS_RANGE.start(),
+                    LONG_POLL_THRESHOLD_MS_RANGE.end()
+                ));
+            }
+            LONG_POLL_THRESHOLD_MS.store(*threshold_ms, Ordering::Relaxed);
+        }
+        RuntimeSettingChange::SendProvenance(enabled) => {
+            SEND_PROVENANCE.store(*enabled, Ordering::Relaxed);
+        }
+    }
+    Ok(())
+}
+
+/// Applies setting changes until dropped, then puts back the values they
+/// replaced. Meant for tests: overrides are held one at a time, so tests that
+/// change settings in parallel cannot restore each other's values.
+#[doc(hidden)]
+#[must_use]
+pub struct SettingsOverride {
+    restore: Vec<RuntimeSettingChange>,
+    _exclusive: MutexGuard<'static, ()>,
+}
+
+static OVERRIDE_LOCK: StdMutex<()> = StdMutex::new(());
+
+impl SettingsOverride {
+    /// Panics if a change is rejected.
+    pub fn apply(changes: impl IntoIterator<Item = RuntimeSettingChange>) -> Self {
+        let exclusive = OVERRIDE_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let before = current_settings();
+        let mut restore = Vec::new();
+        for change in changes {
+            restore.push(previous_value(&before, &change));
+            if let Err(e) = apply_setting_change(&change) {
+                panic!("setting override rejected: {e}");
+            }
+        }
+        Self {
+            restore,
+            _exclusive: exclusive,
+        }
+    }
+}
+
+impl Drop for SettingsOverride {
+    fn drop(&mut self) {
+        for change in self.restore.iter().rev() {
+            let _ = apply_setting_change(change);
+        }
+    }
+}
+
+/// The change that sets what `change` touches back to its value in `before`.
+fn previous_value(before: &RuntimeSettings, change: &RuntimeSettingChange) -> RuntimeSettingChange {
+    match change {
+        RuntimeSettingChange::PushIntervalMs(_) => {
+            RuntimeSettingChange::PushIntervalMs(before.push_interval_ms)
+        }
+        RuntimeSettingChange::BacktraceCapture(_) => {
+            RuntimeSettingChange::BacktraceCapture(before.backtrace_capture)
+        }
+        RuntimeSettingChange::MaxEvents(_) => RuntimeSettingChange::MaxEvents(before.max_events),
+        RuntimeSettingChange::KindDetail(setting) => {
+            RuntimeSettingChange::KindDetail(KindDetailSetting {
+                kind: setting.kind.clone(),
+                enabled: before.detailed_kinds.contains(&setting.kind),
+            })
+        }
+        RuntimeSettingChange::LongPollThresholdMs(_) => {
+            RuntimeSettingChange::LongPollThresholdMs(before.long_poll_threshold_ms)
+        }
+        RuntimeSettingChange::SendProvenance(_) => {
+            RuntimeSettingChange::SendProvenance(before.send_provenance)
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::io::{Read, Write};
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use moire_wire::non_empty_env;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+#[cfg(feature = "tls")]
+use tokio_rustls::TlsConnector;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::pki_types::ServerName;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::{ClientConfig, ClientConnection, StreamOwned};
+
+pub(crate) trait DashboardIo: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> DashboardIo for T {}
+
+/// The push connection, either plain TCP or TLS.
+pub(crate) type DashboardStream = Box<dyn DashboardIo>;
+
+pub(crate) trait BlockingIo: Read + Write + Send {}
+impl<T: Read + Write + Send> BlockingIo for T {}
+
+// r[impl config.dashboard-token]
+pub(crate) fn dashboard_token() -> Option<String> {
+    static TOKEN: OnceLock<Option<String>> = OnceLock::new();
+    TOKEN.get_or_init(|| non_empty_env("MOIRE_TOKEN")).clone()
+}
+
+// r[impl config.dashboard-tls]
+#[cfg(feature = "tls")]
+fn tls_client_config() -> Result<Option<Arc<ClientConfig>>, String> {
+    static CONFIG: OnceLock<Result<Option<Arc<ClientConfig>>, String>> = OnceLock::new();
+    CONFIG
+        .get_or_init(moire_wire::tls_client_config_from_env)
+        .clone()
+}
+
+/// Without the `tls` feature a configured CA still rules out plain TCP.
+#[cfg(not(feature = "tls"))]
+fn refuse_tls_without_feature() -> Result<(), String> {
+    match non_empty_env("MOIRE_TLS_CA") {
+        Some(_) => Err(String::from(
+            "MOIRE_TLS_CA is set, but moire was built without the `tls` feature",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The certificate is checked against the host part of `MOIRE_DASHBOARD`.
+#[cfg(feature = "tls")]
+fn server_name(addr: &str) -> Result<ServerName<'static>, String> {
+    let host = addr.rsplit_once(':').map_or(addr, |(host, _port)| host);
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    ServerName::try_from(host.to_string())
+        .map_err(|e| format!("invalid tls server name {host:?}: {e}"))
+}
+
+#[cfg(feature = "tls")]
+pub(crate) async fn connect(addr: &str) -> Result<DashboardStream, String> {
+    let stream = TcpStream::connect(addr)
+        .await
+        .map_err(|e| format!("dashboard connect: {e}"))?;
+    let Some(config) = tls_client_config()? else {
+        return Ok(Box::new(stream));
+    };
+    let stream = TlsConnector::from(config)
+        .connect(server_name(addr)?, stream)
+        .await
+        .map_err(|e| format!("dashboard tls handshake: {e}"))?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(not(feature = "tls"))]
+pub(crate) async fn connect(addr: &str) -> Result<DashboardStream, String> {
+    refuse_tls_without_feature()?;
+    let stream = TcpStream::connect(addr)
+        .await
+        .map_err(|e| format!("dashboard connect: {e}"))?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(feature = "tls")]
+pub(crate) fn connect_blocking(addr: &str) -> Result<Box<dyn BlockingIo>, String> {
+    let stream =
+        std::net::TcpStream::connect(addr).map_err(|e| format!("heartbeat connect: {e}"))?;
+    let Some(config) = tls_client_config()? else {
+        return Ok(Box::new(stream));
+    };
+    let conn = ClientConnection::new(config, server_name(addr)?)
+        .map_err(|e| format!("heartbeat tls setup: {e}"))?;
+    Ok(Box::new(StreamOwned::new(conn, stream)))
+}
+
+#[cfg(not(feature = "tls"))]
+pub(crate) fn connect_blocking(addr: &str) -> Result<Box<dyn BlockingIo>, String> {
+    refuse_tls_without_feature()?;
+    let stream =
+        std::net::TcpStream::connect(addr).map_err(|e| format!("heartbeat connect: {e}"))?;
+    Ok(Box::new(stream))
+}
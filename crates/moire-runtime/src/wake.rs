@@ -0,0 +1,201 @@
+use std::cell::{Cell, RefCell};
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+
+use moire_types::{EntityId, Event, EventKind, EventTarget, WakeEvent};
+
+use super::db::runtime_db;
+use super::handles::current_causal_target_from_stack;
+
+thread_local! {
+    /// Resources being operated on on this thread, innermost last: operation
+    /// futures being polled, and wrapper calls that wake other tasks.
+    static OPERATING_ON: RefCell<Vec<Operating>> = const { RefCell::new(Vec::new()) };
+    /// Set while a wake is being recorded, so that the wrappers further out in
+    /// the same waker chain don't record it again.
+    static RECORDING_WAKE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks `resource` as being operated on until dropped, so that wakes fired
+/// meanwhile (a `send` waking a receiver, a guard drop waking the next
+/// waiter) are attributed to it.
+///
+/// Operation futures enter it around every poll; wrappers enter it around
+/// the synchronous calls that wake other tasks.
+#[must_use]
+pub struct OperatingOn(bool);
+
+struct Operating {
+    resource: EntityId,
+    /// Entered around the poll of an operation future rather than a call
+    /// that wakes other tasks.
+    polling: bool,
+}
+
+impl OperatingOn {
+    /// Marks a call on `resource` that may wake tasks waiting on it or on
+    /// its peers (a send, a notify, a guard release).
+    pub fn enter(resource: &EntityId) -> Self {
+        Self::push(resource, false)
+    }
+
+    /// Marks the poll of an operation future on `resource`.
+    pub(crate) fn polling(resource: &EntityId) -> Self {
+        Self::push(resource, true)
+    }
+
+    fn push(resource: &EntityId, polling: bool) -> Self {
+        // Polls from thread-local destructors run after the stack is gone;
+        // their wakes just go unattributed.
+        let pushed = OPERATING_ON
+            .try_with(|stack| {
+                stack.borrow_mut().push(Operating {
+                    resource: EntityId::new(resource.as_str()),
+                    polling,
+                })
+            })
+            .is_ok();
+        Self(pushed)
+    }
+}
+
+impl Drop for OperatingOn {
+    fn drop(&mut self) {
+        if self.0 {
+            let _ = OPERATING_ON.try_with(|stack| stack.borrow_mut().pop());
+        }
+    }
+}
+
+/// Wraps the waker a future is polled with, so that waking it records a
+/// `woken` event naming the entity that did the waking.
+///
+/// The wrapper is rebuilt only when the executor hands over a waker that would
+/// wake a different task.
+#[derive(Default)]
+pub(crate) struct WakeAttribution {
+    cached: Option<(Waker, Waker)>,
+}
+
+impl WakeAttribution {
+    /// Returns the waker to poll the inner future with. `woken` is the entity
+    /// that resumes when it fires; `via` is the resource it was waiting on.
+    pub(crate) fn waker(
+        &mut self,
+        outer: &Waker,
+        woken: impl FnOnce() -> Option<EntityId>,
+        via: Option<&EntityId>,
+    ) -> Waker {
+        if let Some((cached_outer, wrapped)) = self.cached.as_ref()
+            && cached_outer.will_wake(outer)
+        {
+            return wrapped.clone();
+        }
+        let Some(woken) = woken() else {
+            return outer.clone();
+        };
+        let wrapped = Waker::from(Arc::new(AttributingWaker {
+            inner: outer.clone(),
+            woken,
+            via: via.map(|id| EntityId::new(id.as_str())),
+        }));
+        self.cached = Some((outer.clone(), wrapped.clone()));
+        wrapped
+    }
+}
+
+struct AttributingWaker {
+    inner: Waker,
+    woken: EntityId,
+    via: Option<EntityId>,
+}
+
+impl Wake for AttributingWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // The first wrapper in the chain belongs to the innermost future, which
+        // knows best what was waited on.
+        let first = RECORDING_WAKE
+            .try_with(|recording| !recording.replace(true))
+            .unwrap_or(false);
+        if first {
+            record_wake(&self.woken, self.via.as_ref());
+        }
+        self.inner.wake_by_ref();
+        if first {
+            let _ = RECORDING_WAKE.try_with(|recording| recording.set(false));
+        }
+    }
+}
+
+// r[impl model.event.woken]
+fn record_wake(woken: &EntityId, via: Option<&EntityId>) {
+    let by = OPERATING_ON
+        .try_with(|stack| {
+            stack
+                .borrow()
+                .last()
+                .map(|op| (EntityId::new(op.resource.as_str()), op.polling))
+        })
+        .ok()
+        .flatten()
+        .or_else(|| current_causal_target_from_stack().map(|target| (target.id().clone(), true)));
+    // Wakes from timers, I/O drivers and plain threads have no waking entity.
+    let Some((by, polling)) = by else {
+        return;
+    };
+    // A future waking itself (`yield_now`, or tokio's coop budget running out
+    // inside its own operation) says nothing about who unblocked it. A
+    // notify or a guard release waking a waiter on the same resource does.
+    if by.as_str() == woken.as_str()
+        || (polling && via.is_some_and(|via| via.as_str() == by.as_str()))
+    {
+        return;
+    }
+    let event = Event::new(
+        EventTarget::Entity(EntityId::new(woken.as_str())),
+        EventKind::Woken(WakeEvent {
+            by,
+            via: via.map(|id| EntityId::new(id.as_str())),
+        }),
+        // Wakes are frequent and come from a handful of places, so they share
+        // one backtrace record per wake site.
+        super::capture_callsite_backtrace(),
+    );
+    if let Ok(mut db) = runtime_db().lock() {
+        db.record_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wakes_from_thread_local_destructors_do_not_panic() {
+        struct WakeOnExit(Waker);
+        impl Drop for WakeOnExit {
+            fn drop(&mut self) {
+                let _operating = OperatingOn::enter(&EntityId::new("wake-test-exiting"));
+                self.0.wake_by_ref();
+            }
+        }
+        thread_local! {
+            static WAKE_ON_EXIT: RefCell<Option<WakeOnExit>> = const { RefCell::new(None) };
+        }
+
+        let woken = EntityId::new("wake-test-after-exit");
+        std::thread::spawn(move || {
+            let mut attribution = WakeAttribution::default();
+            let waker = attribution.waker(Waker::noop(), || Some(woken), None);
+            // Registered first, so destroyed after the runtime's thread-locals.
+            WAKE_ON_EXIT.with(|slot| *slot.borrow_mut() = Some(WakeOnExit(waker.clone())));
+            drop(OperatingOn::enter(&EntityId::new("wake-test-running")));
+        })
+        .join()
+        .expect("thread exits cleanly");
+    }
+}
@@ -1,8 +1,11 @@
 pub mod custom;
 pub mod fs;
 pub mod net;
+pub mod panic_hook;
 pub mod process;
+pub mod redact;
 pub mod rpc;
+pub mod stream;
 pub mod sync;
 pub mod task;
 pub mod time;
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+/// No-op panic hook when diagnostics are disabled: nothing is recorded.
+#[derive(Clone, Default)]
+pub struct PanicHook;
+
+impl PanicHook {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn from_env() -> Result<Option<Self>, String> {
+        Ok(None)
+    }
+
+    pub fn write_snapshot_to(self, _dir: impl Into<PathBuf>) -> Self {
+        self
+    }
+
+    pub fn install(self) {}
+}
@@ -0,0 +1,47 @@
+pub use moire_types::RedactionSummary;
+
+pub const REDACTED: &str = "[redacted]";
+
+/// No-op redaction rules when diagnostics are disabled: nothing is reported.
+#[derive(Clone, Default)]
+pub struct Redaction;
+
+impl Redaction {
+    pub fn none() -> Self {
+        Self
+    }
+
+    pub fn defaults() -> Self {
+        Self
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    pub fn allow_env(self, _pattern: impl Into<String>) -> Self {
+        self
+    }
+
+    pub fn deny_key(self, _pattern: impl Into<String>) -> Self {
+        self
+    }
+
+    pub fn scrub_literal(self, _needle: impl Into<String>) -> Self {
+        self
+    }
+
+    pub fn scrub_with(
+        self,
+        _name: impl Into<String>,
+        _scrub: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self
+    }
+
+    pub fn install(self) {}
+
+    pub fn summary(&self) -> RedactionSummary {
+        RedactionSummary::default()
+    }
+}
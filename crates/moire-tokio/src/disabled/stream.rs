@@ -0,0 +1,101 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub use futures_core::Stream;
+
+use crate::sync::mpsc::{Receiver, UnboundedReceiver};
+
+/// No-op extension trait matching the enabled `StreamExt`.
+pub trait StreamExt: Stream + Sized {
+    fn named(self, _name: impl Into<String>) -> Self {
+        self
+    }
+}
+
+impl<S: Stream> StreamExt for S {}
+
+/// Pass-through [`Stream`] over an mpsc [`Receiver`].
+pub struct ReceiverStream<T>(Receiver<T>);
+
+impl<T> ReceiverStream<T> {
+    pub fn new(recv: Receiver<T>) -> Self {
+        Self(recv)
+    }
+
+    pub fn into_inner(self) -> Receiver<T> {
+        self.0
+    }
+
+    pub fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+impl<T> Stream for ReceiverStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+impl<T> From<Receiver<T>> for ReceiverStream<T> {
+    fn from(recv: Receiver<T>) -> Self {
+        Self(recv)
+    }
+}
+
+impl<T> AsRef<Receiver<T>> for ReceiverStream<T> {
+    fn as_ref(&self) -> &Receiver<T> {
+        &self.0
+    }
+}
+
+impl<T> AsMut<Receiver<T>> for ReceiverStream<T> {
+    fn as_mut(&mut self) -> &mut Receiver<T> {
+        &mut self.0
+    }
+}
+
+/// Pass-through [`Stream`] over an [`UnboundedReceiver`].
+pub struct UnboundedReceiverStream<T>(UnboundedReceiver<T>);
+
+impl<T> UnboundedReceiverStream<T> {
+    pub fn new(recv: UnboundedReceiver<T>) -> Self {
+        Self(recv)
+    }
+
+    pub fn into_inner(self) -> UnboundedReceiver<T> {
+        self.0
+    }
+
+    pub fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+impl<T> Stream for UnboundedReceiverStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+impl<T> From<UnboundedReceiver<T>> for UnboundedReceiverStream<T> {
+    fn from(recv: UnboundedReceiver<T>) -> Self {
+        Self(recv)
+    }
+}
+
+impl<T> AsRef<UnboundedReceiver<T>> for UnboundedReceiverStream<T> {
+    fn as_ref(&self) -> &UnboundedReceiver<T> {
+        &self.0
+    }
+}
+
+impl<T> AsMut<UnboundedReceiver<T>> for UnboundedReceiverStream<T> {
+    fn as_mut(&mut self) -> &mut UnboundedReceiver<T> {
+        &mut self.0
+    }
+}
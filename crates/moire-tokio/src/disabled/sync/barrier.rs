@@ -0,0 +1,22 @@
+use std::fmt;
+
+/// Pass-through `tokio::sync::Barrier` wrapper, accepting a name parameter for API parity.
+pub struct Barrier(tokio::sync::Barrier);
+
+impl Barrier {
+    pub fn new(_name: impl Into<String>, n: usize) -> Self {
+        Self(tokio::sync::Barrier::new(n))
+    }
+
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub async fn wait(&self) -> tokio::sync::BarrierWaitResult {
+        self.0.wait().await
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -1,5 +1,59 @@
-pub use tokio::sync::broadcast::{Receiver, Sender, error};
+use std::fmt;
+
+pub use tokio::sync::broadcast::error;
+
+/// Pass-through `tokio::sync::broadcast::Sender` wrapper, accepting names for API parity.
+pub struct Sender<T>(tokio::sync::broadcast::Sender<T>);
+
+/// Pass-through `tokio::sync::broadcast::Receiver` wrapper, accepting names for API parity.
+pub struct Receiver<T>(tokio::sync::broadcast::Receiver<T>);
 
 pub fn channel<T: Clone>(_name: impl Into<String>, capacity: usize) -> (Sender<T>, Receiver<T>) {
-    tokio::sync::broadcast::channel(capacity)
+    let (tx, rx) = tokio::sync::broadcast::channel(capacity);
+    (Sender(tx), Receiver(rx))
+}
+
+impl<T> Sender<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub fn subscribe(&self) -> Receiver<T> {
+        Receiver(self.0.subscribe())
+    }
+
+    pub fn send(&self, value: T) -> Result<usize, error::SendError<T>> {
+        self.0.send(value)
+    }
+}
+
+impl<T: Clone> Receiver<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub async fn recv(&mut self) -> Result<T, error::RecvError> {
+        self.0.recv().await
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+// Like the instrumented receiver, a clone starts at the current tail.
+impl<T: Clone> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.resubscribe())
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
 }
@@ -0,0 +1,63 @@
+use std::fmt;
+use std::future::Future;
+
+/// Pass-through `tokio_util::sync::CancellationToken` wrapper, accepting name parameters for API parity.
+#[derive(Clone)]
+pub struct CancellationToken(tokio_util::sync::CancellationToken);
+
+/// Pass-through `tokio_util::sync::DropGuard` wrapper.
+pub struct DropGuard(tokio_util::sync::DropGuard);
+
+impl CancellationToken {
+    pub fn new(_name: impl Into<String>) -> Self {
+        Self(tokio_util::sync::CancellationToken::new())
+    }
+
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub fn child_token(&self, _name: impl Into<String>) -> Self {
+        Self(self.0.child_token())
+    }
+
+    pub fn cancel(&self) {
+        self.0.cancel()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    pub async fn cancelled(&self) {
+        self.0.cancelled().await
+    }
+
+    pub async fn cancelled_owned(self) {
+        self.0.cancelled_owned().await
+    }
+
+    pub async fn run_until_cancelled<F: Future>(&self, fut: F) -> Option<F::Output> {
+        self.0.run_until_cancelled(fut).await
+    }
+
+    pub fn drop_guard(self) -> DropGuard {
+        DropGuard(self.0.drop_guard())
+    }
+}
+
+impl DropGuard {
+    pub fn disarm(self) -> CancellationToken {
+        CancellationToken(self.0.disarm())
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Debug for DropGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -3,6 +3,14 @@ pub mod mpsc;
 pub mod oneshot;
 pub mod watch;
 
+mod barrier;
+pub use barrier::*;
+
+#[cfg(feature = "tokio-util")]
+mod cancellation_token;
+#[cfg(feature = "tokio-util")]
+pub use cancellation_token::*;
+
 mod mutex;
 pub use mutex::*;
 
@@ -1,13 +1,284 @@
-pub use tokio::sync::mpsc::{
-    OwnedPermit, Receiver, Sender, UnboundedReceiver, UnboundedSender, error,
-};
+use std::fmt;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+pub use tokio::sync::mpsc::{Permit, error};
+
+/// Pass-through `tokio::sync::mpsc::Sender` wrapper, accepting names for API parity.
+pub struct Sender<T>(tokio::sync::mpsc::Sender<T>);
+
+/// Pass-through `tokio::sync::mpsc::Receiver` wrapper, accepting names for API parity.
+pub struct Receiver<T>(tokio::sync::mpsc::Receiver<T>);
+
+/// Pass-through `tokio::sync::mpsc::UnboundedSender` wrapper, accepting names for API parity.
+pub struct UnboundedSender<T>(tokio::sync::mpsc::UnboundedSender<T>);
+
+/// Pass-through `tokio::sync::mpsc::UnboundedReceiver` wrapper, accepting names for API parity.
+pub struct UnboundedReceiver<T>(tokio::sync::mpsc::UnboundedReceiver<T>);
+
+/// Pass-through `tokio::sync::mpsc::OwnedPermit` wrapper.
+pub struct OwnedPermit<T>(tokio::sync::mpsc::OwnedPermit<T>);
+
+/// Pass-through `tokio::sync::mpsc::WeakSender` wrapper.
+pub struct WeakSender<T>(tokio::sync::mpsc::WeakSender<T>);
+
+/// Pass-through `tokio::sync::mpsc::WeakUnboundedSender` wrapper.
+pub struct WeakUnboundedSender<T>(tokio::sync::mpsc::WeakUnboundedSender<T>);
 
 pub fn channel<T>(_name: impl Into<String>, capacity: usize) -> (Sender<T>, Receiver<T>) {
-    tokio::sync::mpsc::channel(capacity)
+    let (tx, rx) = tokio::sync::mpsc::channel(capacity);
+    (Sender(tx), Receiver(rx))
 }
 
 pub fn unbounded_channel<T>(
     _name: impl Into<String>,
 ) -> (UnboundedSender<T>, UnboundedReceiver<T>) {
-    tokio::sync::mpsc::unbounded_channel()
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    (UnboundedSender(tx), UnboundedReceiver(rx))
+}
+
+impl<T> Sender<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub async fn send(&self, value: T) -> Result<(), error::SendError<T>> {
+        self.0.send(value).await
+    }
+
+    pub fn try_send(&self, value: T) -> Result<(), error::TrySendError<T>> {
+        self.0.try_send(value)
+    }
+
+    pub async fn send_timeout(
+        &self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), error::SendTimeoutError<T>> {
+        self.0.send_timeout(value, timeout).await
+    }
+
+    pub fn blocking_send(&self, value: T) -> Result<(), error::SendError<T>> {
+        self.0.blocking_send(value)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    pub async fn closed(&self) {
+        self.0.closed().await
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    pub fn max_capacity(&self) -> usize {
+        self.0.max_capacity()
+    }
+
+    pub fn downgrade(&self) -> WeakSender<T> {
+        WeakSender(self.0.downgrade())
+    }
+
+    pub fn same_channel(&self, other: &Self) -> bool {
+        self.0.same_channel(&other.0)
+    }
+
+    pub async fn reserve(&self) -> Result<Permit<'_, T>, error::SendError<()>> {
+        self.0.reserve().await
+    }
+
+    pub fn try_reserve(&self) -> Result<Permit<'_, T>, error::TrySendError<()>> {
+        self.0.try_reserve()
+    }
+
+    pub async fn reserve_owned(self) -> Result<OwnedPermit<T>, error::SendError<()>> {
+        self.0.reserve_owned().await.map(OwnedPermit)
+    }
+
+    pub fn try_reserve_owned(self) -> Result<OwnedPermit<T>, error::TrySendError<Self>> {
+        self.0
+            .try_reserve_owned()
+            .map(OwnedPermit)
+            .map_err(|e| match e {
+                error::TrySendError::Full(tx) => error::TrySendError::Full(Self(tx)),
+                error::TrySendError::Closed(tx) => error::TrySendError::Closed(Self(tx)),
+            })
+    }
+}
+
+impl<T> OwnedPermit<T> {
+    pub fn send(self, value: T) -> Sender<T> {
+        Sender(self.0.send(value))
+    }
+
+    pub fn release(self) -> Sender<T> {
+        Sender(self.0.release())
+    }
+
+    pub fn same_channel(&self, other: &Self) -> bool {
+        self.0.same_channel(&other.0)
+    }
+
+    pub fn same_channel_as_sender(&self, sender: &Sender<T>) -> bool {
+        self.0.same_channel_as_sender(&sender.0)
+    }
+}
+
+impl<T> WeakSender<T> {
+    pub fn upgrade(&self) -> Option<Sender<T>> {
+        self.0.upgrade().map(Sender)
+    }
+}
+
+impl<T> WeakUnboundedSender<T> {
+    pub fn upgrade(&self) -> Option<UnboundedSender<T>> {
+        self.0.upgrade().map(UnboundedSender)
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub async fn recv(&mut self) -> Option<T> {
+        self.0.recv().await
+    }
+
+    pub async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        self.0.recv_many(buffer, limit).await
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, error::TryRecvError> {
+        self.0.try_recv()
+    }
+
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll_recv(cx)
+    }
+
+    pub fn blocking_recv(&mut self) -> Option<T> {
+        self.0.blocking_recv()
+    }
+
+    pub fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+impl<T> UnboundedSender<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub fn send(&self, value: T) -> Result<(), error::SendError<T>> {
+        self.0.send(value)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    pub async fn closed(&self) {
+        self.0.closed().await
+    }
+
+    pub fn downgrade(&self) -> WeakUnboundedSender<T> {
+        WeakUnboundedSender(self.0.downgrade())
+    }
+
+    pub fn same_channel(&self, other: &Self) -> bool {
+        self.0.same_channel(&other.0)
+    }
+}
+
+impl<T> UnboundedReceiver<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub async fn recv(&mut self) -> Option<T> {
+        self.0.recv().await
+    }
+
+    pub async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        self.0.recv_many(buffer, limit).await
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, error::TryRecvError> {
+        self.0.try_recv()
+    }
+
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll_recv(cx)
+    }
+
+    pub fn blocking_recv(&mut self) -> Option<T> {
+        self.0.blocking_recv()
+    }
+
+    pub fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Clone for WeakSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Clone for WeakUnboundedSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for UnboundedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for UnboundedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for OwnedPermit<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for WeakSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for WeakUnboundedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
 }
@@ -10,6 +10,8 @@ impl Notify {
         Self(Arc::new(tokio::sync::Notify::new()))
     }
 
+    pub fn rename(&self, _name: impl Into<String>) {}
+
     pub async fn notified(&self) {
         self.0.notified().await
     }
@@ -9,6 +9,15 @@ impl<T> OnceCell<T> {
         Self(tokio::sync::OnceCell::new())
     }
 
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub fn new_debug(name: impl Into<String>) -> Self
+    where
+        T: fmt::Debug,
+    {
+        Self::new(name)
+    }
+
     pub fn get(&self) -> Option<&T> {
         self.0.get()
     }
@@ -1,7 +1,49 @@
-pub use tokio::sync::oneshot::{Sender, error};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
-pub use tokio::sync::oneshot::Receiver;
+pub use tokio::sync::oneshot::error;
+
+/// Pass-through `tokio::sync::oneshot::Sender` wrapper, accepting names for API parity.
+pub struct Sender<T>(tokio::sync::oneshot::Sender<T>);
+
+/// Pass-through `tokio::sync::oneshot::Receiver` wrapper, accepting names for API parity.
+pub struct Receiver<T>(tokio::sync::oneshot::Receiver<T>);
 
 pub fn channel<T>(_name: impl Into<String>) -> (Sender<T>, Receiver<T>) {
-    tokio::sync::oneshot::channel()
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    (Sender(tx), Receiver(rx))
+}
+
+impl<T> Sender<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub fn send(self, value: T) -> Result<(), T> {
+        self.0.send(value)
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, error::RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
 }
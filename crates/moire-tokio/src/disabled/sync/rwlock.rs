@@ -1,9 +1,14 @@
 use std::fmt;
+use std::sync::Arc;
 
 /// Pass-through `tokio::sync::RwLock` wrapper, accepting a name parameter for API parity.
-pub struct RwLock<T>(tokio::sync::RwLock<T>);
+pub struct RwLock<T>(Arc<tokio::sync::RwLock<T>>);
 
-pub use tokio::sync::{RwLockReadGuard, RwLockWriteGuard, TryLockError as AsyncRwLockTryLockError};
+pub use tokio::sync::{
+    OwnedRwLockMappedWriteGuard, OwnedRwLockReadGuard, OwnedRwLockWriteGuard,
+    RwLockMappedWriteGuard, RwLockReadGuard, RwLockWriteGuard,
+    TryLockError as AsyncRwLockTryLockError,
+};
 
 /// Pass-through `parking_lot::RwLock` wrapper, accepting a name parameter for API parity.
 pub struct SyncRwLock<T>(parking_lot::RwLock<T>);
@@ -13,10 +18,12 @@ pub use parking_lot::{
 };
 
 impl<T> RwLock<T> {
-    pub fn new(_name: &'static str, value: T) -> Self {
-        Self(tokio::sync::RwLock::new(value))
+    pub fn new(_name: impl Into<String>, value: T) -> Self {
+        Self(Arc::new(tokio::sync::RwLock::new(value)))
     }
 
+    pub fn rename(&self, _name: impl Into<String>) {}
+
     pub async fn read(&self) -> RwLockReadGuard<'_, T> {
         self.0.read().await
     }
@@ -32,13 +39,35 @@ impl<T> RwLock<T> {
     pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, T>, AsyncRwLockTryLockError> {
         self.0.try_write()
     }
+
+    pub async fn read_owned(self: &Arc<Self>) -> OwnedRwLockReadGuard<T> {
+        Arc::clone(&self.0).read_owned().await
+    }
+
+    pub async fn write_owned(self: &Arc<Self>) -> OwnedRwLockWriteGuard<T> {
+        Arc::clone(&self.0).write_owned().await
+    }
+
+    pub fn try_read_owned(
+        self: &Arc<Self>,
+    ) -> Result<OwnedRwLockReadGuard<T>, AsyncRwLockTryLockError> {
+        Arc::clone(&self.0).try_read_owned()
+    }
+
+    pub fn try_write_owned(
+        self: &Arc<Self>,
+    ) -> Result<OwnedRwLockWriteGuard<T>, AsyncRwLockTryLockError> {
+        Arc::clone(&self.0).try_write_owned()
+    }
 }
 
 impl<T> SyncRwLock<T> {
-    pub fn new(_name: &'static str, value: T) -> Self {
+    pub fn new(_name: impl Into<String>, value: T) -> Self {
         Self(parking_lot::RwLock::new(value))
     }
 
+    pub fn rename(&self, _name: impl Into<String>) {}
+
     pub fn read(&self) -> SyncRwLockReadGuard<'_, T> {
         self.0.read()
     }
@@ -12,6 +12,8 @@ impl Semaphore {
         Self(Arc::new(tokio::sync::Semaphore::new(permits)))
     }
 
+    pub fn rename(&self, _name: impl Into<String>) {}
+
     pub fn available_permits(&self) -> usize {
         self.0.available_permits()
     }
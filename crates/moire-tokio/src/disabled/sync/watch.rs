@@ -1,5 +1,81 @@
-pub use tokio::sync::watch::{Receiver, Ref, Sender, error};
+use std::fmt;
+
+pub use tokio::sync::watch::{Ref, error};
+
+/// Pass-through `tokio::sync::watch::Sender` wrapper, accepting names for API parity.
+pub struct Sender<T>(tokio::sync::watch::Sender<T>);
+
+/// Pass-through `tokio::sync::watch::Receiver` wrapper, accepting names for API parity.
+pub struct Receiver<T>(tokio::sync::watch::Receiver<T>);
 
 pub fn channel<T: Clone>(_name: impl Into<String>, initial: T) -> (Sender<T>, Receiver<T>) {
-    tokio::sync::watch::channel(initial)
+    let (tx, rx) = tokio::sync::watch::channel(initial);
+    (Sender(tx), Receiver(rx))
+}
+
+pub fn channel_debug<T: Clone + fmt::Debug>(
+    name: impl Into<String>,
+    initial: T,
+) -> (Sender<T>, Receiver<T>) {
+    channel(name, initial)
+}
+
+impl<T> Sender<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub fn send(&self, value: T) -> Result<(), error::SendError<T>> {
+        self.0.send(value)
+    }
+
+    pub fn send_replace(&self, value: T) -> T {
+        self.0.send_replace(value)
+    }
+
+    pub fn subscribe(&self) -> Receiver<T> {
+        Receiver(self.0.subscribe())
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn rename(&self, _name: impl Into<String>) {}
+
+    pub async fn changed(&mut self) -> Result<(), error::RecvError> {
+        self.0.changed().await
+    }
+
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.0.borrow_and_update()
+    }
+
+    pub fn has_changed(&self) -> Result<bool, error::RecvError> {
+        self.0.has_changed()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
 }
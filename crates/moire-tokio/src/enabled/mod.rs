@@ -1,8 +1,11 @@
 pub mod custom;
 pub mod fs;
 pub mod net;
+pub mod panic_hook;
 pub mod process;
+pub mod redact;
 pub mod rpc;
+pub mod stream;
 pub mod sync;
 pub mod task;
 pub mod time;
@@ -11,5 +14,5 @@ pub use task::{spawn, spawn_blocking};
 
 #[doc(hidden)]
 pub mod __internal {
-    pub use moire_runtime::{InstrumentedFuture, instrument_future};
+    pub use moire_runtime::{InstrumentedFuture, instrument_future, note_result};
 }
@@ -0,0 +1,5 @@
+//! Opt-in panic hook that records task panics in the runtime graph.
+//!
+//! Enable it with `MOIRE_PANIC_HOOK` (see [`PanicHook::from_env`]) or install
+//! it programmatically with [`PanicHook::install`].
+pub use moire_runtime::PanicHook;
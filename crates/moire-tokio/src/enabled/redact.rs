@@ -0,0 +1,6 @@
+//! Client-side redaction of what the process reports to moire-web.
+//!
+//! Rules come from `MOIRE_REDACT` (see [`Redaction::from_env`]) and can be
+//! replaced programmatically with [`Redaction::install`].
+pub use moire_runtime::{REDACTED, Redaction};
+pub use moire_types::RedactionSummary;
@@ -0,0 +1,344 @@
+// r[impl api.stream]
+//! Instrumented streams, for pipelines built from [`Stream`] combinators.
+//!
+//! | Item | Equivalent |
+//! |---|---|
+//! | [`StreamExt`] | *(moire extension)* |
+//! | [`ReceiverStream`] | `tokio_stream::wrappers::ReceiverStream` |
+//! | [`UnboundedReceiverStream`] | `tokio_stream::wrappers::UnboundedReceiverStream` |
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+pub use futures_core::Stream;
+use moire_runtime::{
+    AsEntityRef, EdgeHandle, EntityHandle, EntityRef, FUTURE_CAUSAL_STACK,
+    current_causal_target_from_stack,
+};
+use moire_types::{EdgeKind, PTime, StreamEntity};
+
+use crate::sync::mpsc::{Receiver, UnboundedReceiver};
+
+/// Shortest time between two updates of a stream entity's item counts.
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Extension trait for attaching a diagnostic name to any [`Stream`].
+///
+/// ```rust,no_run
+/// use moire_tokio::stream::{ReceiverStream, StreamExt as _};
+///
+/// let (_tx, rx) = moire_tokio::sync::mpsc::channel::<u32>("events", 16);
+/// let events = ReceiverStream::new(rx).named("events");
+/// ```
+pub trait StreamExt: Stream + Sized {
+    /// Wraps this stream in an entity visible in the Moiré dashboard.
+    fn named(self, name: impl Into<String>) -> InstrumentedStream<Self> {
+        InstrumentedStream::new(name, self)
+    }
+}
+
+impl<S: Stream> StreamExt for S {}
+
+/// Stream returned by [`StreamExt::named`].
+///
+/// Whoever polls it has a `waiting_on` edge to it while `poll_next` is
+/// pending, and resources the inner stream waits on are linked from it.
+pub struct InstrumentedStream<S> {
+    inner: S,
+    handle: EntityHandle<moire_types::Stream>,
+    waiting: PendingEdge,
+    stats: ItemStats,
+}
+
+impl<S> InstrumentedStream<S> {
+    fn new(name: impl Into<String>, inner: S) -> Self {
+        let handle = EntityHandle::new(
+            name,
+            StreamEntity {
+                items: 0,
+                last_item_at: None,
+                item_gap_total_ns: 0,
+                item_gap_max_ns: 0,
+                finished: false,
+            },
+        );
+        Self {
+            inner,
+            handle,
+            waiting: PendingEdge::default(),
+            stats: ItemStats::default(),
+        }
+    }
+
+    fn publish(&mut self) {
+        if !self.stats.unpublished {
+            return;
+        }
+        self.stats.unpublished = false;
+        self.stats.published_at = Some(Instant::now());
+        let stats = &self.stats;
+        let _ = self.handle.mutate(|body| {
+            body.items = stats.items;
+            body.last_item_at = stats.last_item_at;
+            body.item_gap_total_ns = duration_ns(stats.gap_total);
+            body.item_gap_max_ns = duration_ns(stats.gap_max);
+            body.finished = stats.finished;
+        });
+    }
+}
+
+impl<S: Stream> Stream for InstrumentedStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: `inner` is never moved out of a pinned `InstrumentedStream`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let poller = current_causal_target_from_stack();
+        this.stats.polled();
+
+        let stream_id = this.handle.id().clone();
+        let on_stack = FUTURE_CAUSAL_STACK
+            .try_with(|stack| stack.borrow_mut().push(stream_id))
+            .is_ok();
+        let poll = unsafe { Pin::new_unchecked(&mut this.inner) }.poll_next(cx);
+        if on_stack {
+            let _ = FUTURE_CAUSAL_STACK.try_with(|stack| stack.borrow_mut().pop());
+        }
+
+        match &poll {
+            Poll::Pending => {
+                this.waiting.pending(poller, &this.handle);
+                // A stall after a burst is what the entity is there to show,
+                // so don't leave the burst's counts unpublished through it.
+                this.publish();
+            }
+            Poll::Ready(item) => {
+                this.waiting.ready();
+                if this.stats.ready(item.is_some()) {
+                    this.publish();
+                }
+            }
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Drop for InstrumentedStream<S> {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            self.publish();
+        }
+    }
+}
+
+impl<S> AsEntityRef for InstrumentedStream<S> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
+/// Item counts kept on the stream itself, and copied to the entity only
+/// every [`PUBLISH_INTERVAL`], on a new longest gap, when the stream goes
+/// pending and when it ends.
+#[derive(Default)]
+struct ItemStats {
+    items: u64,
+    last_item_at: Option<PTime>,
+    gap_total: Duration,
+    gap_max: Duration,
+    finished: bool,
+    /// Start of the current gap: the first poll, then each item.
+    gap_start: Option<Instant>,
+    published_at: Option<Instant>,
+    unpublished: bool,
+}
+
+impl ItemStats {
+    fn polled(&mut self) {
+        self.gap_start.get_or_insert_with(Instant::now);
+    }
+
+    /// Records a `Ready` and returns whether the entity is now out of date.
+    fn ready(&mut self, item: bool) -> bool {
+        self.unpublished = true;
+        if !item {
+            self.finished = true;
+            return true;
+        }
+        let now = Instant::now();
+        let gap = self
+            .gap_start
+            .replace(now)
+            .map_or(Duration::ZERO, |start| now - start);
+        self.items += 1;
+        self.last_item_at = Some(PTime::now());
+        self.gap_total += gap;
+        let new_max = gap > self.gap_max;
+        if new_max {
+            self.gap_max = gap;
+        }
+        new_max
+            || self
+                .published_at
+                .is_none_or(|at| now.duration_since(at) >= PUBLISH_INTERVAL)
+    }
+}
+
+fn duration_ns(duration: Duration) -> u64 {
+    duration.as_nanos().min(u64::MAX as u128) as u64
+}
+
+/// The `waiting_on` edge from the poller of a stream to what it is pending
+/// on, kept from a `Pending` until the next `Ready` or until it is dropped.
+#[derive(Default)]
+struct PendingEdge {
+    edge: Option<(EntityRef, EdgeHandle)>,
+}
+
+impl PendingEdge {
+    fn pending(&mut self, poller: Option<EntityRef>, on: &impl AsEntityRef) {
+        if self.edge.as_ref().map(|(current, _)| current) == poller.as_ref() {
+            return;
+        }
+        self.edge = None;
+        self.edge = poller.map(|poller| {
+            let edge = poller.link_to_owned(on, EdgeKind::WaitingOn);
+            (poller, edge)
+        });
+    }
+
+    fn ready(&mut self) {
+        self.edge = None;
+    }
+}
+
+/// A [`Stream`] of the messages on a moire [`Receiver`].
+///
+/// The poller has a `waiting_on` edge to the receiver while the channel is
+/// empty.
+pub struct ReceiverStream<T> {
+    inner: Receiver<T>,
+    waiting: PendingEdge,
+}
+
+impl<T> ReceiverStream<T> {
+    /// Wraps a receiver.
+    pub fn new(recv: Receiver<T>) -> Self {
+        Self {
+            inner: recv,
+            waiting: PendingEdge::default(),
+        }
+    }
+
+    /// Returns the wrapped receiver.
+    pub fn into_inner(self) -> Receiver<T> {
+        self.inner
+    }
+
+    /// Closes the receiving half without dropping it, see [`Receiver::close`].
+    pub fn close(&mut self) {
+        self.inner.close();
+    }
+}
+
+impl<T> Stream for ReceiverStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = &mut *self;
+        let poll = this.inner.poll_recv(cx);
+        match &poll {
+            Poll::Pending => this
+                .waiting
+                .pending(current_causal_target_from_stack(), &this.inner),
+            Poll::Ready(_) => this.waiting.ready(),
+        }
+        poll
+    }
+}
+
+impl<T> From<Receiver<T>> for ReceiverStream<T> {
+    fn from(recv: Receiver<T>) -> Self {
+        Self::new(recv)
+    }
+}
+
+impl<T> AsRef<Receiver<T>> for ReceiverStream<T> {
+    fn as_ref(&self) -> &Receiver<T> {
+        &self.inner
+    }
+}
+
+impl<T> AsMut<Receiver<T>> for ReceiverStream<T> {
+    fn as_mut(&mut self) -> &mut Receiver<T> {
+        &mut self.inner
+    }
+}
+
+/// A [`Stream`] of the messages on a moire [`UnboundedReceiver`].
+///
+/// The poller has a `waiting_on` edge to the receiver while the channel is
+/// empty.
+pub struct UnboundedReceiverStream<T> {
+    inner: UnboundedReceiver<T>,
+    waiting: PendingEdge,
+}
+
+impl<T> UnboundedReceiverStream<T> {
+    /// Wraps a receiver.
+    pub fn new(recv: UnboundedReceiver<T>) -> Self {
+        Self {
+            inner: recv,
+            waiting: PendingEdge::default(),
+        }
+    }
+
+    /// Returns the wrapped receiver.
+    pub fn into_inner(self) -> UnboundedReceiver<T> {
+        self.inner
+    }
+
+    /// Closes the receiving half without dropping it, see [`UnboundedReceiver::close`].
+    pub fn close(&mut self) {
+        self.inner.close();
+    }
+}
+
+impl<T> Stream for UnboundedReceiverStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = &mut *self;
+        let poll = this.inner.poll_recv(cx);
+        match &poll {
+            Poll::Pending => this
+                .waiting
+                .pending(current_causal_target_from_stack(), &this.inner),
+            Poll::Ready(_) => this.waiting.ready(),
+        }
+        poll
+    }
+}
+
+impl<T> From<UnboundedReceiver<T>> for UnboundedReceiverStream<T> {
+    fn from(recv: UnboundedReceiver<T>) -> Self {
+        Self::new(recv)
+    }
+}
+
+impl<T> AsRef<UnboundedReceiver<T>> for UnboundedReceiverStream<T> {
+    fn as_ref(&self) -> &UnboundedReceiver<T> {
+        &self.inner
+    }
+}
+
+impl<T> AsMut<UnboundedReceiver<T>> for UnboundedReceiverStream<T> {
+    fn as_mut(&mut self) -> &mut UnboundedReceiver<T> {
+        &mut self.inner
+    }
+}
@@ -0,0 +1,67 @@
+// r[impl api.barrier]
+use moire_types::BarrierEntity;
+use std::fmt;
+
+use moire_runtime::{AsEntityRef, EntityHandle, EntityRef, instrument_operation_on};
+
+/// Instrumented version of [`tokio::sync::Barrier`].
+pub struct Barrier {
+    inner: tokio::sync::Barrier,
+    handle: EntityHandle<moire_types::Barrier>,
+}
+
+impl Barrier {
+    /// Creates a new instrumented barrier, matching [`tokio::sync::Barrier::new`].
+    pub fn new(name: impl Into<String>, n: usize) -> Self {
+        let handle = EntityHandle::new(
+            name,
+            BarrierEntity {
+                target: n.max(1).min(u32::MAX as usize) as u32,
+                waiting: 0,
+                generations: 0,
+            },
+        );
+        Self {
+            inner: tokio::sync::Barrier::new(n),
+            handle,
+        }
+    }
+
+    /// Renames the barrier entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
+    /// Waits for all tasks to reach the barrier, matching [`tokio::sync::Barrier::wait`].
+    pub async fn wait(&self) -> tokio::sync::BarrierWaitResult {
+        let _ = self
+            .handle
+            .mutate(|body| body.waiting = body.waiting.saturating_add(1));
+
+        let result = instrument_operation_on(&self.handle, self.inner.wait()).await;
+
+        // The leader is the last task of the round to arrive, and it returns
+        // first, while the others are still being woken. Every task of the
+        // round has counted itself in by then, so the leader retires all of
+        // them at once and the others leave `waiting` alone.
+        if result.is_leader() {
+            let _ = self.handle.mutate(|body| {
+                body.waiting = body.waiting.saturating_sub(body.target);
+                body.generations = body.generations.saturating_add(1);
+            });
+        }
+        result
+    }
+}
+
+impl AsEntityRef for Barrier {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -1,18 +1,26 @@
 // r[impl api.broadcast]
 
 use moire_runtime::{
-    AsEntityRef, EntityHandle, EntityRef, WeakEntityHandle, new_event, record_event,
+    AsEntityRef, EntityHandle, EntityRef, KindDetail, OperatingOn, WeakEntityHandle,
+    instrument_operation_on, new_event, record_event,
 };
 use moire_types::{BroadcastRxEntity, BroadcastTxEntity, EdgeKind, EventKind, EventTarget};
 use std::fmt;
+use std::sync::Arc;
 use tokio::sync::broadcast;
 
+use super::queue_stamps::{QueueStamps, QueueUpdate};
+
+/// Detail setting that turns on queue latency tracking for broadcast channels.
+static LATENCY_DETAIL: KindDetail = KindDetail::new("broadcast_tx");
+
 /// Instrumented version of [`tokio::sync::broadcast::Sender`].
 ///
 /// This wraps the Tokio broadcast sender and records send/subscribe lifecycle.
 pub struct Sender<T> {
     inner: tokio::sync::broadcast::Sender<T>,
     handle: EntityHandle<moire_types::BroadcastTx>,
+    stamps: Arc<QueueStamps>,
 }
 
 /// Instrumented version of [`tokio::sync::broadcast::Receiver`].
@@ -22,6 +30,8 @@ pub struct Receiver<T> {
     inner: tokio::sync::broadcast::Receiver<T>,
     handle: EntityHandle<moire_types::BroadcastRx>,
     tx_handle: WeakEntityHandle<moire_types::BroadcastTx>,
+    stamps: Arc<QueueStamps>,
+    stamp_receiver: u64,
 }
 
 impl<T> Clone for Sender<T> {
@@ -29,16 +39,20 @@ impl<T> Clone for Sender<T> {
         Self {
             inner: self.inner.clone(),
             handle: self.handle.clone(),
+            stamps: Arc::clone(&self.stamps),
         }
     }
 }
 
 impl<T: Clone> Clone for Receiver<T> {
     fn clone(&self) -> Self {
+        let (stamp_receiver, inner) = self.stamps.add_receiver(|| self.inner.resubscribe());
         Self {
-            inner: self.inner.resubscribe(),
+            inner,
             handle: self.handle.clone(),
             tx_handle: self.tx_handle.clone(),
+            stamps: Arc::clone(&self.stamps),
+            stamp_receiver,
         }
     }
 }
@@ -48,19 +62,42 @@ impl<T: Clone> Sender<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::BroadcastTx> {
         &self.handle
     }
+
+    /// Renames the sender entity, which every clone of this sender shares.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Subscribes a receiver, equivalent to [`tokio::sync::broadcast::Sender::subscribe`].
     pub fn subscribe(&self) -> Receiver<T> {
-        let handle = EntityHandle::new("broadcast:rx.subscribe", BroadcastRxEntity { lag: 0 });
+        let handle = EntityHandle::new(
+            "broadcast:rx.subscribe",
+            BroadcastRxEntity {
+                lag: 0,
+                cancelled_recvs: 0,
+            },
+        );
         self.handle.link_to_handle(&handle, EdgeKind::PairedWith);
+        let (stamp_receiver, inner) = self.stamps.add_receiver(|| self.inner.subscribe());
         Receiver {
-            inner: self.inner.subscribe(),
+            inner,
             handle,
             tx_handle: self.handle.downgrade(),
+            stamps: Arc::clone(&self.stamps),
+            stamp_receiver,
         }
     }
     /// Sends a value through the channel, mirroring [`tokio::sync::broadcast::Sender::send`].
     pub fn send(&self, value: T) -> Result<usize, broadcast::error::SendError<T>> {
-        let result = self.inner.send(value);
+        let (result, update) = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.stamps.send(|| self.inner.send(value))
+        };
+        if let Some(update) = update {
+            let _ = self
+                .handle
+                .mutate(|body| update.apply(&mut body.latency, &mut body.queued_by));
+        }
         let event = new_event(
             EventTarget::Entity(self.handle.id().clone()),
             EventKind::ChannelSent,
@@ -75,12 +112,26 @@ impl<T: Clone> Receiver<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::BroadcastRx> {
         &self.handle
     }
+
+    /// Renames the receiver entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Receives the next broadcast value, equivalent to [`tokio::sync::broadcast::Receiver::recv`].
     pub async fn recv(&mut self) -> Result<T, broadcast::error::RecvError> {
-        match self.inner.recv().await {
+        match instrument_operation_on(&self.handle, self.inner.recv()).await {
             Ok(value) => {
                 let lag = self.inner.len().min(u32::MAX as usize) as u32;
                 let _ = self.handle.mutate(|body| body.lag = lag);
+                let received = self
+                    .stamps
+                    .received(self.stamp_receiver, 1, || self.inner.len());
+                if let Some(received) = received.filter(QueueUpdate::is_tracked) {
+                    let _ = self
+                        .tx_handle
+                        .mutate(|body| received.apply(&mut body.latency, &mut body.queued_by));
+                }
                 let event = new_event(
                     EventTarget::Entity(self.handle.id().clone()),
                     EventKind::ChannelReceived,
@@ -114,10 +165,24 @@ pub fn channel<T: Clone>(name: impl Into<String>, capacity: usize) -> (Sender<T>
         format!("{name}:tx"),
         BroadcastTxEntity {
             capacity: capacity_u32,
+            latency: None,
+            queued_by: Vec::new(),
         },
     );
+    // Tokio rounds the ring buffer up to a power of two.
+    let stamps = Arc::new(QueueStamps::new(
+        &LATENCY_DETAIL,
+        Some(capacity.next_power_of_two()),
+    ));
+    let (stamp_receiver, ()) = stamps.add_receiver(|| ());
 
-    let rx_handle = EntityHandle::new(format!("{name}:rx"), BroadcastRxEntity { lag: 0 });
+    let rx_handle = EntityHandle::new(
+        format!("{name}:rx"),
+        BroadcastRxEntity {
+            lag: 0,
+            cancelled_recvs: 0,
+        },
+    );
 
     tx_handle.link_to_handle(&rx_handle, EdgeKind::PairedWith);
 
@@ -125,21 +190,36 @@ pub fn channel<T: Clone>(name: impl Into<String>, capacity: usize) -> (Sender<T>
         Sender {
             inner: tx,
             handle: tx_handle.clone(),
+            stamps: Arc::clone(&stamps),
         },
         Receiver {
             inner: rx,
             handle: rx_handle,
             tx_handle: tx_handle.downgrade(),
+            stamps,
+            stamp_receiver,
         },
     )
 }
 
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.stamps.remove_receiver(self.stamp_receiver);
+    }
+}
+
 impl<T: Clone> AsEntityRef for Sender<T> {
     fn as_entity_ref(&self) -> EntityRef {
         self.handle.entity_ref()
     }
 }
 
+impl<T: Clone> AsEntityRef for Receiver<T> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
 impl<T> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
@@ -0,0 +1,250 @@
+// r[impl api.cancellation-token]
+use moire_types::{CancellationTokenEntity, EdgeKind, PTime};
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+
+use moire_runtime::{
+    AsEntityRef, DeferredUpdate, EntityHandle, EntityRef, OperatingOn, WeakEntityHandle,
+    defer_update, instrument_operation_on,
+};
+
+/// Instrumented version of [`tokio_util::sync::CancellationToken`].
+///
+/// Tokens made with [`child_token`](Self::child_token) are linked to their
+/// parent with `cancels` edges, and cancelling a token marks every
+/// descendant as cancelled too.
+#[derive(Clone)]
+pub struct CancellationToken {
+    inner: tokio_util::sync::CancellationToken,
+    handle: EntityHandle<moire_types::CancellationToken>,
+    node: Arc<TokenNode>,
+}
+
+/// Guard returned by [`CancellationToken::drop_guard`], equivalent to
+/// [`tokio_util::sync::DropGuard`].
+pub struct DropGuard {
+    token: Option<CancellationToken>,
+}
+
+/// A token's place in the tree. Like tokio-util's own tree, a node keeps its
+/// parent alive, so cancellation still reaches a grandchild after the token
+/// in between was dropped. Only the token itself keeps its entity alive.
+struct TokenNode {
+    handle: WeakEntityHandle<moire_types::CancellationToken>,
+    _parent: Option<Arc<TokenNode>>,
+    children: parking_lot::Mutex<Vec<Weak<TokenNode>>>,
+    /// Times cancellation was observed. `is_cancelled()` can sit in a polling
+    /// loop, so the count reaches the entity at the runtime's next read
+    /// rather than on every call.
+    observed: AtomicU64,
+    /// Queued with the runtime to publish `observed`.
+    observed_deferred: AtomicBool,
+}
+
+impl TokenNode {
+    fn new(
+        name: impl Into<String>,
+        parent: Option<Arc<TokenNode>>,
+    ) -> (EntityHandle<moire_types::CancellationToken>, Arc<Self>) {
+        let handle = EntityHandle::new(
+            name,
+            CancellationTokenEntity {
+                cancelled_at: None,
+                waiter_count: 0,
+                observed: 0,
+            },
+        );
+        let node = Arc::new(Self {
+            handle: handle.downgrade(),
+            _parent: parent,
+            children: parking_lot::Mutex::new(Vec::new()),
+            observed: AtomicU64::new(0),
+            observed_deferred: AtomicBool::new(false),
+        });
+        (handle, node)
+    }
+
+    /// Records cancellation on this token and, the way tokio-util propagates
+    /// it, on every live descendant.
+    fn mark_cancelled(&self, at: PTime) {
+        let _ = self.handle.mutate(|body| {
+            if body.cancelled_at.is_none() {
+                body.cancelled_at = Some(at);
+            }
+        });
+        for child in self.children.lock().iter() {
+            if let Some(child) = child.upgrade() {
+                child.mark_cancelled(at);
+            }
+        }
+    }
+
+    fn observed(self: &Arc<Self>) {
+        self.observed.fetch_add(1, Ordering::SeqCst);
+        if !self.observed_deferred.swap(true, Ordering::SeqCst) {
+            let node: Weak<dyn DeferredUpdate> = Arc::downgrade(self) as _;
+            defer_update(node);
+        }
+    }
+}
+
+impl DeferredUpdate for TokenNode {
+    fn flush(&self) {
+        // Cleared before reading, so a call that lands after the read queues
+        // the node again.
+        self.observed_deferred.store(false, Ordering::SeqCst);
+        let observed = self.observed.load(Ordering::SeqCst);
+        let _ = self.handle.mutate(|body| body.observed = observed);
+    }
+}
+
+/// A pending `cancelled()` call. Dropping it before cancellation (the other
+/// branch of a `select!` won) takes it back off the waiter count.
+struct Waiter<'a> {
+    node: &'a TokenNode,
+}
+
+impl<'a> Waiter<'a> {
+    fn new(node: &'a TokenNode) -> Self {
+        let _ = node
+            .handle
+            .mutate(|body| body.waiter_count = body.waiter_count.saturating_add(1));
+        Self { node }
+    }
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        let _ = self
+            .node
+            .handle
+            .mutate(|body| body.waiter_count = body.waiter_count.saturating_sub(1));
+    }
+}
+
+impl CancellationToken {
+    /// Creates a new instrumented token, matching [`tokio_util::sync::CancellationToken::new`].
+    pub fn new(name: impl Into<String>) -> Self {
+        let (handle, node) = TokenNode::new(name, None);
+        Self {
+            inner: tokio_util::sync::CancellationToken::new(),
+            handle,
+            node,
+        }
+    }
+
+    /// Renames the token entity. Clones share it, so they all see the new
+    /// name; child tokens keep theirs.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
+    /// Creates a child token, matching [`tokio_util::sync::CancellationToken::child_token`].
+    ///
+    /// The parent is linked to the child with a `cancels` edge.
+    pub fn child_token(&self, name: impl Into<String>) -> Self {
+        let (handle, node) = TokenNode::new(name, Some(Arc::clone(&self.node)));
+        let child = Self {
+            inner: self.inner.child_token(),
+            handle,
+            node,
+        };
+        self.handle.link_to_handle(&child.handle, EdgeKind::Cancels);
+        {
+            let mut children = self.node.children.lock();
+            children.retain(|child| child.strong_count() > 0);
+            children.push(Arc::downgrade(&child.node));
+        }
+        if child.inner.is_cancelled() {
+            child.node.mark_cancelled(PTime::now());
+        }
+        child
+    }
+
+    /// Cancels this token and its children, matching [`tokio_util::sync::CancellationToken::cancel`].
+    pub fn cancel(&self) {
+        {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.inner.cancel();
+        }
+        self.node.mark_cancelled(PTime::now());
+    }
+
+    /// Returns whether the token was cancelled, matching [`tokio_util::sync::CancellationToken::is_cancelled`].
+    pub fn is_cancelled(&self) -> bool {
+        let cancelled = self.inner.is_cancelled();
+        if cancelled {
+            self.node.observed();
+        }
+        cancelled
+    }
+
+    /// Waits until the token is cancelled, matching [`tokio_util::sync::CancellationToken::cancelled`].
+    pub async fn cancelled(&self) {
+        let waiter = Waiter::new(&self.node);
+        instrument_operation_on(&self.handle, self.inner.cancelled()).await;
+        drop(waiter);
+        self.node.observed();
+    }
+
+    /// Owned variant of [`cancelled`](Self::cancelled), matching
+    /// [`tokio_util::sync::CancellationToken::cancelled_owned`].
+    pub async fn cancelled_owned(self) {
+        self.cancelled().await
+    }
+
+    /// Runs `fut` until it completes or the token is cancelled, matching
+    /// [`tokio_util::sync::CancellationToken::run_until_cancelled`].
+    pub async fn run_until_cancelled<F: Future>(&self, fut: F) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => None,
+            output = fut => Some(output),
+        }
+    }
+
+    /// Returns a guard that cancels this token when dropped, matching
+    /// [`tokio_util::sync::CancellationToken::drop_guard`].
+    pub fn drop_guard(self) -> DropGuard {
+        DropGuard { token: Some(self) }
+    }
+}
+
+impl DropGuard {
+    /// Returns the token without cancelling it, matching [`tokio_util::sync::DropGuard::disarm`].
+    pub fn disarm(mut self) -> CancellationToken {
+        self.token
+            .take()
+            .expect("drop guard token is only taken by disarm or drop")
+    }
+}
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        if let Some(token) = self.token.take() {
+            token.cancel();
+        }
+    }
+}
+
+impl AsEntityRef for CancellationToken {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl fmt::Debug for DropGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropGuard")
+            .field("token", &self.token)
+            .finish()
+    }
+}
@@ -0,0 +1,304 @@
+//! Bookkeeping for the holder, waiter and contention fields of lock entities,
+//! shared by the mutex and read-write lock wrappers.
+
+use moire_runtime::{
+    EdgeHandle, EntityHandle, EntityRef, HeldMutexEntry, OperatingOn,
+    current_causal_target_with_task_fallback,
+};
+use moire_types::{EdgeKind, LockEntity};
+use std::thread::{self, ThreadId};
+use std::time::Instant;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Access {
+    /// A read guard.
+    Shared,
+    /// A mutex guard or a write guard.
+    Exclusive,
+}
+
+/// An acquisition that found the lock taken and is waiting for it. Dropping
+/// it without acquiring (a cancelled `lock()` future) takes it back off the
+/// waiter count.
+pub(crate) struct LockWaiter {
+    handle: EntityHandle<moire_types::Lock>,
+    access: Access,
+    waiting: bool,
+}
+
+impl LockWaiter {
+    pub(crate) fn new(handle: &EntityHandle<moire_types::Lock>, access: Access) -> Self {
+        let _ = handle.mutate(|body| {
+            let waiting = waiting_count(body, access);
+            *waiting = waiting.saturating_add(1);
+        });
+        Self {
+            handle: handle.clone(),
+            access,
+            waiting: true,
+        }
+    }
+
+    pub(crate) fn acquired(mut self) -> LockHold {
+        self.waiting = false;
+        LockHold::start(&self.handle, self.access, true)
+    }
+}
+
+impl Drop for LockWaiter {
+    fn drop(&mut self) {
+        if self.waiting {
+            let _ = self.handle.mutate(|body| {
+                let waiting = waiting_count(body, self.access);
+                *waiting = waiting.saturating_sub(1);
+            });
+        }
+    }
+}
+
+/// A held guard's share of the lock state. Dropping it releases the hold and
+/// updates the longest hold time.
+pub(crate) struct LockHold {
+    handle: EntityHandle<moire_types::Lock>,
+    access: Access,
+    since: Instant,
+}
+
+impl LockHold {
+    /// Records an acquisition that didn't have to wait.
+    pub(crate) fn uncontended(handle: &EntityHandle<moire_types::Lock>, access: Access) -> Self {
+        Self::start(handle, access, false)
+    }
+
+    fn start(handle: &EntityHandle<moire_types::Lock>, access: Access, contended: bool) -> Self {
+        let _ = handle.mutate(|body| {
+            if contended {
+                let waiting = waiting_count(body, access);
+                *waiting = waiting.saturating_sub(1);
+                body.contended_acquisitions = body.contended_acquisitions.saturating_add(1);
+            }
+            body.acquisitions = body.acquisitions.saturating_add(1);
+            match access {
+                Access::Shared => body.readers = body.readers.saturating_add(1),
+                Access::Exclusive => body.writer_held = true,
+            }
+        });
+        Self {
+            handle: handle.clone(),
+            access,
+            since: Instant::now(),
+        }
+    }
+}
+
+impl LockHold {
+    /// Turns an exclusive hold into a shared one, for write guard downgrades.
+    pub(crate) fn downgrade(mut self) -> Self {
+        if let Access::Exclusive = self.access {
+            let _ = self.handle.mutate(|body| {
+                body.writer_held = false;
+                body.readers = body.readers.saturating_add(1);
+            });
+            self.access = Access::Shared;
+        }
+        self
+    }
+}
+
+impl Drop for LockHold {
+    fn drop(&mut self) {
+        let held_ns = u64::try_from(self.since.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        let _ = self.handle.mutate(|body| {
+            match self.access {
+                Access::Shared => body.readers = body.readers.saturating_sub(1),
+                Access::Exclusive => body.writer_held = false,
+            }
+            body.max_hold_ns = body.max_hold_ns.max(held_ns);
+        });
+    }
+}
+
+/// A Tokio guard together with its [`Holder`] and [`LockHold`], released
+/// together when dropped. Lock guards keep one of these rather than the
+/// pieces, so that `map`, `try_map` and `downgrade` can move them into the
+/// new guard while the old one still has a `Drop` impl.
+pub(crate) struct Held<G> {
+    parts: Option<HeldParts<G>>,
+}
+
+struct HeldParts<G> {
+    guard: G,
+    holder: Holder,
+    hold: LockHold,
+}
+
+impl<G> Held<G> {
+    pub(crate) fn new(guard: G, holder: Holder, hold: LockHold) -> Self {
+        Self {
+            parts: Some(HeldParts {
+                guard,
+                holder,
+                hold,
+            }),
+        }
+    }
+
+    fn parts(&self) -> &HeldParts<G> {
+        self.parts
+            .as_ref()
+            .expect("a guard is only taken apart when it is consumed")
+    }
+
+    fn take(&mut self) -> HeldParts<G> {
+        self.parts.take().expect("a guard is only taken apart once")
+    }
+
+    /// Moves the parts into a new `Held`, for guards whose own `Drop` impl
+    /// keeps this one from being moved out.
+    pub(crate) fn take_over(&mut self) -> Self {
+        Self {
+            parts: Some(self.take()),
+        }
+    }
+
+    /// Whether the parts are still here, i.e. not moved to a mapped guard.
+    pub(crate) fn is_held(&self) -> bool {
+        self.parts.is_some()
+    }
+
+    pub(crate) fn guard(&self) -> &G {
+        &self.parts().guard
+    }
+
+    pub(crate) fn guard_mut(&mut self) -> &mut G {
+        &mut self
+            .parts
+            .as_mut()
+            .expect("a guard is only taken apart when it is consumed")
+            .guard
+    }
+
+    pub(crate) fn holder(&self) -> &Holder {
+        &self.parts().holder
+    }
+
+    /// Moves the hold over to a guard derived from this one.
+    pub(crate) fn map<H>(mut self, f: impl FnOnce(G) -> H) -> Held<H> {
+        let HeldParts {
+            guard,
+            holder,
+            hold,
+        } = self.take();
+        Held::new(f(guard), holder, hold)
+    }
+
+    /// Like [`map`](Self::map), keeping the hold here when `f` gives the
+    /// guard back.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn try_map<H>(mut self, f: impl FnOnce(G) -> Result<H, G>) -> Result<Held<H>, Self> {
+        let HeldParts {
+            guard,
+            holder,
+            hold,
+        } = self.take();
+        match f(guard) {
+            Ok(guard) => Ok(Held::new(guard, holder, hold)),
+            Err(guard) => Err(Held::new(guard, holder, hold)),
+        }
+    }
+
+    /// Moves the hold over to the read guard a write guard downgrades to.
+    pub(crate) fn downgrade<H>(mut self, f: impl FnOnce(G) -> H) -> Held<H> {
+        let HeldParts {
+            guard,
+            holder,
+            hold,
+        } = self.take();
+        Held::new(f(guard), holder, hold.downgrade())
+    }
+}
+
+impl<G> Drop for Held<G> {
+    fn drop(&mut self) {
+        // Release the Tokio guard as the lock, so the waiter it wakes is
+        // recorded as woken by the lock rather than by the releasing task.
+        if let Some(HeldParts {
+            guard,
+            holder,
+            hold,
+        }) = self.parts.take()
+        {
+            let _operating = OperatingOn::enter(holder.handle.id());
+            drop(guard);
+            drop(holder);
+            drop(hold);
+        }
+    }
+}
+
+/// Who holds a guard: the `held_by` edge from the lock to the holding task
+/// and, for mutexes, the lock's entry on the holding thread's
+/// `HELD_MUTEX_STACK`. Both go away when this is dropped.
+pub(crate) struct Holder {
+    handle: EntityHandle<moire_types::Lock>,
+    state: parking_lot::Mutex<HolderState>,
+}
+
+struct HolderState {
+    task: Option<tokio::task::Id>,
+    thread: ThreadId,
+    edge: Option<EdgeHandle>,
+    stack_entry: Option<HeldMutexEntry>,
+}
+
+impl Holder {
+    pub(crate) fn new(
+        handle: &EntityHandle<moire_types::Lock>,
+        owner: Option<&EntityRef>,
+        on_stack: bool,
+    ) -> Self {
+        let edge = owner.map(|owner| handle.link_to_owned(owner, EdgeKind::HeldBy));
+        let stack_entry = on_stack.then(|| HeldMutexEntry::push(handle.id().clone()));
+        Self {
+            handle: handle.clone(),
+            state: parking_lot::Mutex::new(HolderState {
+                task: tokio::task::try_id(),
+                thread: thread::current().id(),
+                edge,
+                stack_entry,
+            }),
+        }
+    }
+
+    /// Re-points the holder at the current task and thread when an owned
+    /// guard is used somewhere other than where it was last seen, e.g. after
+    /// being moved into a spawned task.
+    pub(crate) fn follow(&self) {
+        let task = tokio::task::try_id();
+        let thread = thread::current().id();
+        let mut state = self.state.lock();
+        if state.task == task && state.thread == thread {
+            return;
+        }
+        if state.task != task {
+            state.task = task;
+            // Remove the old edge first: the new owner may be the same entity.
+            state.edge = None;
+            state.edge = current_causal_target_with_task_fallback()
+                .map(|owner| self.handle.link_to_owned(&owner, EdgeKind::HeldBy));
+        }
+        if state.thread != thread {
+            state.thread = thread;
+            if state.stack_entry.take().is_some() {
+                state.stack_entry = Some(HeldMutexEntry::push(self.handle.id().clone()));
+            }
+        }
+    }
+}
+
+fn waiting_count(body: &mut LockEntity, access: Access) -> &mut u32 {
+    match access {
+        Access::Shared => &mut body.readers_waiting,
+        Access::Exclusive => &mut body.writers_waiting,
+    }
+}
@@ -21,12 +21,27 @@
 //! | [`Semaphore`] | [`tokio::sync::Semaphore`] |
 //! | [`Notify`] | [`tokio::sync::Notify`] |
 //! | [`OnceCell`] | [`tokio::sync::OnceCell`] |
+//! | [`Barrier`] | [`tokio::sync::Barrier`] |
+//! | `CancellationToken`, `DropGuard` (feature `tokio-util`) | `tokio_util::sync::CancellationToken`, `tokio_util::sync::DropGuard` |
 
 pub mod broadcast;
 pub mod mpsc;
 pub mod oneshot;
 pub mod watch;
 
+mod blocking;
+mod lock_state;
+mod queue_stamps;
+mod value_inspect;
+
+mod barrier;
+pub use barrier::*;
+
+#[cfg(feature = "tokio-util")]
+mod cancellation_token;
+#[cfg(feature = "tokio-util")]
+pub use cancellation_token::*;
+
 mod mutex;
 pub use mutex::*;
 
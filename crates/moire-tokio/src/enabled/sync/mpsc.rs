@@ -1,20 +1,31 @@
 // r[impl api.mpsc]
 
 use moire_runtime::{
-    AsEntityRef, EntityHandle, EntityRef, WeakEntityHandle, instrument_operation_on, new_event,
-    record_event,
+    AsEntityRef, EntityHandle, EntityRef, KindDetail, OperatingOn, WeakEntityHandle,
+    instrument_operation_on, new_event, record_event,
 };
 use moire_types::{EdgeKind, EventKind, EventTarget, MpscRxEntity, MpscTxEntity};
+use std::convert::Infallible;
 use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::sync::mpsc;
 pub use tokio::sync::mpsc::error;
 
+use super::blocking::block_on;
+use super::queue_stamps::{QueueStamps, QueueUpdate};
+
+/// Detail setting that turns on queue latency tracking for mpsc channels.
+static LATENCY_DETAIL: KindDetail = KindDetail::new("mpsc_tx");
+
 /// Instrumented version of [`tokio::sync::mpsc::Sender`].
 ///
 /// Tracks queue length and send activity for diagnostics.
 pub struct Sender<T> {
     inner: tokio::sync::mpsc::Sender<T>,
     handle: EntityHandle<moire_types::MpscTx>,
+    stamps: Arc<QueueStamps>,
 }
 
 /// Instrumented version of [`tokio::sync::mpsc::Receiver`].
@@ -24,6 +35,8 @@ pub struct Receiver<T> {
     inner: tokio::sync::mpsc::Receiver<T>,
     handle: EntityHandle<moire_types::MpscRx>,
     tx_handle: WeakEntityHandle<moire_types::MpscTx>,
+    stamps: Arc<QueueStamps>,
+    stamp_receiver: u64,
 }
 
 /// Instrumented version of [`tokio::sync::mpsc::UnboundedSender`].
@@ -31,6 +44,7 @@ pub struct Receiver<T> {
 pub struct UnboundedSender<T> {
     inner: tokio::sync::mpsc::UnboundedSender<T>,
     handle: EntityHandle<moire_types::MpscTx>,
+    stamps: Arc<QueueStamps>,
 }
 
 /// Instrumented version of [`tokio::sync::mpsc::UnboundedReceiver`].
@@ -39,6 +53,8 @@ pub struct UnboundedReceiver<T> {
     inner: tokio::sync::mpsc::UnboundedReceiver<T>,
     handle: EntityHandle<moire_types::MpscRx>,
     tx_handle: WeakEntityHandle<moire_types::MpscTx>,
+    stamps: Arc<QueueStamps>,
+    stamp_receiver: u64,
 }
 
 /// Instrumented version of [`tokio::sync::mpsc::OwnedPermit`].
@@ -47,6 +63,33 @@ pub struct UnboundedReceiver<T> {
 pub struct OwnedPermit<T> {
     inner: tokio::sync::mpsc::OwnedPermit<T>,
     handle: EntityHandle<moire_types::MpscTx>,
+    stamps: Arc<QueueStamps>,
+}
+
+/// Instrumented version of [`tokio::sync::mpsc::Permit`].
+///
+/// Tracks send activity emitted through reserved capacity.
+pub struct Permit<'a, T> {
+    inner: tokio::sync::mpsc::Permit<'a, T>,
+    sender: &'a Sender<T>,
+}
+
+/// Instrumented version of [`tokio::sync::mpsc::WeakSender`].
+///
+/// Upgrades back to a [`Sender`] on the same sender entity.
+pub struct WeakSender<T> {
+    inner: tokio::sync::mpsc::WeakSender<T>,
+    handle: WeakEntityHandle<moire_types::MpscTx>,
+    stamps: Arc<QueueStamps>,
+}
+
+/// Instrumented version of [`tokio::sync::mpsc::WeakUnboundedSender`].
+///
+/// Upgrades back to an [`UnboundedSender`] on the same sender entity.
+pub struct WeakUnboundedSender<T> {
+    inner: tokio::sync::mpsc::WeakUnboundedSender<T>,
+    handle: WeakEntityHandle<moire_types::MpscTx>,
+    stamps: Arc<QueueStamps>,
 }
 
 impl<T> Clone for Sender<T> {
@@ -54,6 +97,7 @@ impl<T> Clone for Sender<T> {
         Self {
             inner: self.inner.clone(),
             handle: self.handle.clone(),
+            stamps: Arc::clone(&self.stamps),
         }
     }
 }
@@ -63,6 +107,27 @@ impl<T> Clone for UnboundedSender<T> {
         Self {
             inner: self.inner.clone(),
             handle: self.handle.clone(),
+            stamps: Arc::clone(&self.stamps),
+        }
+    }
+}
+
+impl<T> Clone for WeakSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            handle: self.handle.clone(),
+            stamps: Arc::clone(&self.stamps),
+        }
+    }
+}
+
+impl<T> Clone for WeakUnboundedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            handle: self.handle.clone(),
+            stamps: Arc::clone(&self.stamps),
         }
     }
 }
@@ -73,17 +138,40 @@ impl<T> Sender<T> {
         &self.handle
     }
 
+    /// Renames the sender entity, which every clone of this sender shares.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Attempts to enqueue a value without waiting, equivalent to [`tokio::sync::mpsc::Sender::try_send`].
     pub fn try_send(&self, value: T) -> Result<(), mpsc::error::TrySendError<T>> {
-        match self.inner.try_send(value) {
-            Ok(()) => {
-                let _ = self
-                    .handle
-                    .mutate(|body| body.queue_len = body.queue_len.saturating_add(1));
-                Ok(())
+        let permit = match self.inner.try_reserve() {
+            Ok(permit) => permit,
+            Err(mpsc::error::TrySendError::Full(())) => {
+                return Err(mpsc::error::TrySendError::Full(value));
             }
-            Err(err) => Err(err),
-        }
+            Err(mpsc::error::TrySendError::Closed(())) => {
+                return Err(mpsc::error::TrySendError::Closed(value));
+            }
+        };
+        let (_, update) = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.stamps.send(|| {
+                permit.send(value);
+                Ok::<(), Infallible>(())
+            })
+        };
+        self.note_sent(update);
+        Ok(())
+    }
+
+    fn note_sent(&self, update: Option<QueueUpdate>) {
+        let _ = self.handle.mutate(|body| {
+            body.queue_len = body.queue_len.saturating_add(1);
+            if let Some(update) = update {
+                update.apply(&mut body.latency, &mut body.queued_by);
+            }
+        });
     }
 
     /// Returns true if the sender is closed.
@@ -91,45 +179,157 @@ impl<T> Sender<T> {
         self.inner.is_closed()
     }
 
-    /// Sends a value and awaits slot availability, matching [`tokio::sync::mpsc::Sender::send`].
-    pub async fn send(&self, value: T) -> Result<(), mpsc::error::SendError<T>> {
-        let result = instrument_operation_on(&self.handle, self.inner.send(value)).await;
-        if result.is_ok() {
-            let _ = self
-                .handle
-                .mutate(|body| body.queue_len = body.queue_len.saturating_add(1));
+    /// Waits until the receiver is dropped or closed, matching [`tokio::sync::mpsc::Sender::closed`].
+    pub async fn closed(&self) {
+        self.inner.closed().await
+    }
+
+    /// Returns the current free capacity, matching [`tokio::sync::mpsc::Sender::capacity`].
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns the capacity the channel was created with, matching [`tokio::sync::mpsc::Sender::max_capacity`].
+    pub fn max_capacity(&self) -> usize {
+        self.inner.max_capacity()
+    }
+
+    /// Creates a sender that doesn't keep the channel open, matching [`tokio::sync::mpsc::Sender::downgrade`].
+    pub fn downgrade(&self) -> WeakSender<T> {
+        WeakSender {
+            inner: self.inner.downgrade(),
+            handle: self.handle.downgrade(),
+            stamps: Arc::clone(&self.stamps),
         }
+    }
+
+    /// Returns whether both senders belong to the same channel.
+    pub fn same_channel(&self, other: &Self) -> bool {
+        self.inner.same_channel(&other.inner)
+    }
+
+    /// Reserves capacity for one message, matching [`tokio::sync::mpsc::Sender::reserve`].
+    pub async fn reserve(&self) -> Result<Permit<'_, T>, mpsc::error::SendError<()>> {
+        let inner = instrument_operation_on(&self.handle, self.inner.reserve()).await?;
+        Ok(Permit {
+            inner,
+            sender: self,
+        })
+    }
+
+    /// Reserves capacity without waiting, matching [`tokio::sync::mpsc::Sender::try_reserve`].
+    pub fn try_reserve(&self) -> Result<Permit<'_, T>, mpsc::error::TrySendError<()>> {
+        let inner = self.inner.try_reserve()?;
+        Ok(Permit {
+            inner,
+            sender: self,
+        })
+    }
+
+    /// Sends a value, giving up after `timeout`, matching [`tokio::sync::mpsc::Sender::send_timeout`].
+    ///
+    /// A send that times out is recorded as a cancelled send.
+    pub async fn send_timeout(
+        &self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), mpsc::error::SendTimeoutError<T>> {
+        let reserve = instrument_operation_on(&self.handle, self.inner.reserve());
+        let result = match tokio::time::timeout(timeout, reserve).await {
+            Ok(Ok(permit)) => {
+                Permit {
+                    inner: permit,
+                    sender: self,
+                }
+                .send_quiet(value);
+                Ok(())
+            }
+            Ok(Err(mpsc::error::SendError(()))) => {
+                Err(mpsc::error::SendTimeoutError::Closed(value))
+            }
+            Err(_elapsed) => Err(mpsc::error::SendTimeoutError::Timeout(value)),
+        };
+        self.record_sent_event();
+        result
+    }
+
+    /// Sends a value from synchronous code, matching [`tokio::sync::mpsc::Sender::blocking_send`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an asynchronous execution context.
+    pub fn blocking_send(&self, value: T) -> Result<(), mpsc::error::SendError<T>> {
+        block_on(self.send(value))
+    }
+
+    fn record_sent_event(&self) {
         let event = new_event(
             EventTarget::Entity(self.handle.id().clone()),
             EventKind::ChannelSent,
         );
         record_event(event);
+    }
+
+    /// Sends a value and awaits slot availability, matching [`tokio::sync::mpsc::Sender::send`].
+    pub async fn send(&self, value: T) -> Result<(), mpsc::error::SendError<T>> {
+        // Reserving first, then sending under the stamp lock, keeps the send
+        // times in the same order as the messages.
+        let result = match instrument_operation_on(&self.handle, self.inner.reserve()).await {
+            Ok(permit) => {
+                Permit {
+                    inner: permit,
+                    sender: self,
+                }
+                .send_quiet(value);
+                Ok(())
+            }
+            Err(mpsc::error::SendError(())) => Err(mpsc::error::SendError(value)),
+        };
+        self.record_sent_event();
         result
     }
 
     /// Reserves capacity and returns an owned permit, matching [`tokio::sync::mpsc::Sender::reserve_owned`].
     pub async fn reserve_owned(self) -> Result<OwnedPermit<T>, mpsc::error::SendError<()>> {
-        let Self { inner, handle } = self;
+        let Self {
+            inner,
+            handle,
+            stamps,
+        } = self;
         let permit = instrument_operation_on(&handle, inner.reserve_owned()).await?;
         Ok(OwnedPermit {
             inner: permit,
             handle,
+            stamps,
         })
     }
 
     /// Reserves capacity without waiting, matching [`tokio::sync::mpsc::Sender::try_reserve_owned`].
     pub fn try_reserve_owned(self) -> Result<OwnedPermit<T>, mpsc::error::TrySendError<Self>> {
-        let Self { inner, handle } = self;
+        let Self {
+            inner,
+            handle,
+            stamps,
+        } = self;
         match inner.try_reserve_owned() {
             Ok(permit) => Ok(OwnedPermit {
                 inner: permit,
                 handle,
+                stamps,
             }),
             Err(mpsc::error::TrySendError::Full(inner)) => {
-                Err(mpsc::error::TrySendError::Full(Self { inner, handle }))
+                Err(mpsc::error::TrySendError::Full(Self {
+                    inner,
+                    handle,
+                    stamps,
+                }))
             }
             Err(mpsc::error::TrySendError::Closed(inner)) => {
-                Err(mpsc::error::TrySendError::Closed(Self { inner, handle }))
+                Err(mpsc::error::TrySendError::Closed(Self {
+                    inner,
+                    handle,
+                    stamps,
+                }))
             }
         }
     }
@@ -138,20 +338,27 @@ impl<T> Sender<T> {
 impl<T> OwnedPermit<T> {
     /// Sends a value using reserved capacity, matching [`tokio::sync::mpsc::OwnedPermit::send`].
     pub fn send(self, value: T) -> Sender<T> {
-        let sender = self.inner.send(value);
-        let _ = self
-            .handle
-            .mutate(|body| body.queue_len = body.queue_len.saturating_add(1));
+        let Self {
+            inner,
+            handle,
+            stamps,
+        } = self;
+        let (Ok(inner), update) = {
+            let _operating = OperatingOn::enter(handle.id());
+            stamps.send(|| Ok::<_, Infallible>(inner.send(value)))
+        };
+        let sender = Sender {
+            inner,
+            handle,
+            stamps,
+        };
+        sender.note_sent(update);
         let event = new_event(
-            EventTarget::Entity(self.handle.id().clone()),
+            EventTarget::Entity(sender.handle.id().clone()),
             EventKind::ChannelSent,
         );
         record_event(event);
-
-        Sender {
-            inner: sender,
-            handle: self.handle,
-        }
+        sender
     }
 
     /// Releases reserved capacity without sending, matching [`tokio::sync::mpsc::OwnedPermit::release`].
@@ -160,6 +367,7 @@ impl<T> OwnedPermit<T> {
         Sender {
             inner: sender,
             handle: self.handle,
+            stamps: self.stamps,
         }
     }
 
@@ -174,24 +382,69 @@ impl<T> OwnedPermit<T> {
     }
 }
 
+impl<T> Permit<'_, T> {
+    /// Sends a value using reserved capacity, matching [`tokio::sync::mpsc::Permit::send`].
+    pub fn send(self, value: T) {
+        let sender = self.sender;
+        self.send_quiet(value);
+        sender.record_sent_event();
+    }
+
+    /// Sends without recording a `channel_sent` event, for callers that
+    /// record their own.
+    fn send_quiet(self, value: T) {
+        let Self { inner, sender } = self;
+        let (_, update) = {
+            let _operating = OperatingOn::enter(sender.handle.id());
+            sender.stamps.send(|| {
+                inner.send(value);
+                Ok::<(), Infallible>(())
+            })
+        };
+        sender.note_sent(update);
+    }
+}
+
+impl<T> WeakSender<T> {
+    /// Returns a sender if the channel is still open, matching [`tokio::sync::mpsc::WeakSender::upgrade`].
+    pub fn upgrade(&self) -> Option<Sender<T>> {
+        Some(Sender {
+            inner: self.inner.upgrade()?,
+            handle: self.handle.upgrade()?,
+            stamps: Arc::clone(&self.stamps),
+        })
+    }
+}
+
+impl<T> WeakUnboundedSender<T> {
+    /// Returns a sender if the channel is still open, matching [`tokio::sync::mpsc::WeakUnboundedSender::upgrade`].
+    pub fn upgrade(&self) -> Option<UnboundedSender<T>> {
+        Some(UnboundedSender {
+            inner: self.inner.upgrade()?,
+            handle: self.handle.upgrade()?,
+            stamps: Arc::clone(&self.stamps),
+        })
+    }
+}
+
 impl<T> Receiver<T> {
     #[doc(hidden)]
     pub fn handle(&self) -> &EntityHandle<moire_types::MpscRx> {
         &self.handle
     }
+
+    /// Renames the receiver entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Receives the next message, matching [`tokio::sync::mpsc::Receiver::recv`].
     pub async fn recv(&mut self) -> Option<T> {
         let result = instrument_operation_on(&self.handle, self.inner.recv()).await;
         if result.is_some() {
-            let _ = self
-                .tx_handle
-                .mutate(|body| body.queue_len = body.queue_len.saturating_sub(1));
+            self.note_received(1);
         }
-        let event = new_event(
-            EventTarget::Entity(self.handle.id().clone()),
-            EventKind::ChannelReceived,
-        );
-        record_event(event);
+        self.record_received_event();
         result
     }
 
@@ -199,6 +452,69 @@ impl<T> Receiver<T> {
     pub fn close(&mut self) {
         self.inner.close();
     }
+
+    /// Receives up to `limit` messages into `buffer`, matching [`tokio::sync::mpsc::Receiver::recv_many`].
+    pub async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        let received =
+            instrument_operation_on(&self.handle, self.inner.recv_many(buffer, limit)).await;
+        self.note_received(received);
+        self.record_received_event();
+        received
+    }
+
+    /// Receives a message without waiting, matching [`tokio::sync::mpsc::Receiver::try_recv`].
+    pub fn try_recv(&mut self) -> Result<T, mpsc::error::TryRecvError> {
+        let result = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.inner.try_recv()
+        };
+        if result.is_ok() {
+            self.note_received(1);
+            self.record_received_event();
+        }
+        result
+    }
+
+    /// Polls for the next message, matching [`tokio::sync::mpsc::Receiver::poll_recv`].
+    ///
+    /// Polling directly records no `waiting_on` edge; prefer [`recv`](Self::recv).
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let poll = self.inner.poll_recv(cx);
+        if let Poll::Ready(message) = &poll {
+            if message.is_some() {
+                self.note_received(1);
+            }
+            self.record_received_event();
+        }
+        poll
+    }
+
+    /// Receives a message from synchronous code, matching [`tokio::sync::mpsc::Receiver::blocking_recv`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an asynchronous execution context.
+    pub fn blocking_recv(&mut self) -> Option<T> {
+        block_on(self.recv())
+    }
+
+    fn note_received(&self, count: usize) {
+        note_received(
+            &self.tx_handle,
+            &self.stamps,
+            self.stamp_receiver,
+            count,
+            || self.inner.len(),
+        );
+    }
+
+    fn record_received_event(&self) {
+        let event = new_event(
+            EventTarget::Entity(self.handle.id().clone()),
+            EventKind::ChannelReceived,
+        );
+        record_event(event);
+    }
 }
 
 impl<T> UnboundedSender<T> {
@@ -206,13 +522,26 @@ impl<T> UnboundedSender<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::MpscTx> {
         &self.handle
     }
+
+    /// Renames the sender entity, which every clone of this sender shares.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Sends a value on an unbounded channel, matching [`tokio::sync::mpsc::UnboundedSender::send`].
     pub fn send(&self, value: T) -> Result<(), mpsc::error::SendError<T>> {
-        match self.inner.send(value) {
-            Ok(()) => {
-                let _ = self
-                    .handle
-                    .mutate(|body| body.queue_len = body.queue_len.saturating_add(1));
+        let sent = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.stamps.send(|| self.inner.send(value))
+        };
+        match sent {
+            (Ok(()), update) => {
+                let _ = self.handle.mutate(|body| {
+                    body.queue_len = body.queue_len.saturating_add(1);
+                    if let Some(update) = update {
+                        update.apply(&mut body.latency, &mut body.queued_by);
+                    }
+                });
                 let event = new_event(
                     EventTarget::Entity(self.handle.id().clone()),
                     EventKind::ChannelSent,
@@ -220,7 +549,7 @@ impl<T> UnboundedSender<T> {
                 record_event(event);
                 Ok(())
             }
-            Err(err) => {
+            (Err(err), _) => {
                 let event = new_event(
                     EventTarget::Entity(self.handle.id().clone()),
                     EventKind::ChannelSent,
@@ -235,6 +564,25 @@ impl<T> UnboundedSender<T> {
     pub fn is_closed(&self) -> bool {
         self.inner.is_closed()
     }
+
+    /// Waits until the receiver is dropped or closed, matching [`tokio::sync::mpsc::UnboundedSender::closed`].
+    pub async fn closed(&self) {
+        self.inner.closed().await
+    }
+
+    /// Creates a sender that doesn't keep the channel open, matching [`tokio::sync::mpsc::UnboundedSender::downgrade`].
+    pub fn downgrade(&self) -> WeakUnboundedSender<T> {
+        WeakUnboundedSender {
+            inner: self.inner.downgrade(),
+            handle: self.handle.downgrade(),
+            stamps: Arc::clone(&self.stamps),
+        }
+    }
+
+    /// Returns whether both senders belong to the same channel.
+    pub fn same_channel(&self, other: &Self) -> bool {
+        self.inner.same_channel(&other.inner)
+    }
 }
 
 impl<T> UnboundedReceiver<T> {
@@ -242,26 +590,111 @@ impl<T> UnboundedReceiver<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::MpscRx> {
         &self.handle
     }
+
+    /// Renames the receiver entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Receives the next unbounded message, matching [`tokio::sync::mpsc::UnboundedReceiver::recv`].
     pub async fn recv(&mut self) -> Option<T> {
         let result = instrument_operation_on(&self.handle, self.inner.recv()).await;
         if result.is_some() {
-            let _ = self
-                .tx_handle
-                .mutate(|body| body.queue_len = body.queue_len.saturating_sub(1));
+            self.note_received(1);
         }
+        self.record_received_event();
+        result
+    }
+
+    /// Closes the unbounded receive half.
+    pub fn close(&mut self) {
+        self.inner.close();
+    }
+
+    /// Receives up to `limit` messages into `buffer`, matching [`tokio::sync::mpsc::UnboundedReceiver::recv_many`].
+    pub async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        let received =
+            instrument_operation_on(&self.handle, self.inner.recv_many(buffer, limit)).await;
+        self.note_received(received);
+        self.record_received_event();
+        received
+    }
+
+    /// Receives a message without waiting, matching [`tokio::sync::mpsc::UnboundedReceiver::try_recv`].
+    pub fn try_recv(&mut self) -> Result<T, mpsc::error::TryRecvError> {
+        let result = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.inner.try_recv()
+        };
+        if result.is_ok() {
+            self.note_received(1);
+            self.record_received_event();
+        }
+        result
+    }
+
+    /// Polls for the next message, matching [`tokio::sync::mpsc::UnboundedReceiver::poll_recv`].
+    ///
+    /// Polling directly records no `waiting_on` edge; prefer [`recv`](Self::recv).
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let poll = self.inner.poll_recv(cx);
+        if let Poll::Ready(message) = &poll {
+            if message.is_some() {
+                self.note_received(1);
+            }
+            self.record_received_event();
+        }
+        poll
+    }
+
+    /// Receives a message from synchronous code, matching [`tokio::sync::mpsc::UnboundedReceiver::blocking_recv`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an asynchronous execution context.
+    pub fn blocking_recv(&mut self) -> Option<T> {
+        block_on(self.recv())
+    }
+
+    fn note_received(&self, count: usize) {
+        note_received(
+            &self.tx_handle,
+            &self.stamps,
+            self.stamp_receiver,
+            count,
+            || self.inner.len(),
+        );
+    }
+
+    fn record_received_event(&self) {
         let event = new_event(
             EventTarget::Entity(self.handle.id().clone()),
             EventKind::ChannelReceived,
         );
         record_event(event);
-        result
     }
+}
 
-    /// Closes the unbounded receive half.
-    pub fn close(&mut self) {
-        self.inner.close();
+/// Moves receiver `stamp_receiver` past `count` messages and takes them off
+/// the sender's queue length. `remaining` is the receiver's queue length.
+fn note_received(
+    tx_handle: &WeakEntityHandle<moire_types::MpscTx>,
+    stamps: &QueueStamps,
+    stamp_receiver: u64,
+    count: usize,
+    remaining: impl FnOnce() -> usize,
+) {
+    if count == 0 {
+        return;
     }
+    let update = stamps.received(stamp_receiver, count, remaining);
+    let _ = tx_handle.mutate(|body| {
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        body.queue_len = body.queue_len.saturating_sub(count);
+        if let Some(update) = update {
+            update.apply(&mut body.latency, &mut body.queued_by);
+        }
+    });
 }
 
 /// Creates a bounded channel, equivalent to [`tokio::sync::mpsc::channel`].
@@ -275,10 +708,16 @@ pub fn channel<T>(name: impl Into<String>, capacity: usize) -> (Sender<T>, Recei
         MpscTxEntity {
             queue_len: 0,
             capacity: Some(capacity_u32),
+            cancelled_sends: 0,
+            latency: None,
+            queued_by: Vec::new(),
         },
     );
 
-    let rx_handle = EntityHandle::new(format!("{name}:rx"), MpscRxEntity {});
+    let stamps = Arc::new(QueueStamps::new(&LATENCY_DETAIL, None));
+    let (stamp_receiver, ()) = stamps.add_receiver(|| ());
+
+    let rx_handle = EntityHandle::new(format!("{name}:rx"), MpscRxEntity { cancelled_recvs: 0 });
 
     tx_handle.link_to_handle(&rx_handle, EdgeKind::PairedWith);
 
@@ -286,11 +725,14 @@ pub fn channel<T>(name: impl Into<String>, capacity: usize) -> (Sender<T>, Recei
         Sender {
             inner: tx,
             handle: tx_handle.clone(),
+            stamps: Arc::clone(&stamps),
         },
         Receiver {
             inner: rx,
             handle: rx_handle,
             tx_handle: tx_handle.downgrade(),
+            stamps,
+            stamp_receiver,
         },
     )
 }
@@ -305,10 +747,16 @@ pub fn unbounded_channel<T>(name: impl Into<String>) -> (UnboundedSender<T>, Unb
         MpscTxEntity {
             queue_len: 0,
             capacity: None,
+            cancelled_sends: 0,
+            latency: None,
+            queued_by: Vec::new(),
         },
     );
 
-    let rx_handle = EntityHandle::new(format!("{name}:rx"), MpscRxEntity {});
+    let stamps = Arc::new(QueueStamps::new(&LATENCY_DETAIL, None));
+    let (stamp_receiver, ()) = stamps.add_receiver(|| ());
+
+    let rx_handle = EntityHandle::new(format!("{name}:rx"), MpscRxEntity { cancelled_recvs: 0 });
 
     tx_handle.link_to_handle(&rx_handle, EdgeKind::PairedWith);
 
@@ -316,15 +764,30 @@ pub fn unbounded_channel<T>(name: impl Into<String>) -> (UnboundedSender<T>, Unb
         UnboundedSender {
             inner: tx,
             handle: tx_handle.clone(),
+            stamps: Arc::clone(&stamps),
         },
         UnboundedReceiver {
             inner: rx,
             handle: rx_handle,
             tx_handle: tx_handle.downgrade(),
+            stamps,
+            stamp_receiver,
         },
     )
 }
 
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.stamps.remove_receiver(self.stamp_receiver);
+    }
+}
+
+impl<T> Drop for UnboundedReceiver<T> {
+    fn drop(&mut self) {
+        self.stamps.remove_receiver(self.stamp_receiver);
+    }
+}
+
 impl<T> AsEntityRef for Sender<T> {
     fn as_entity_ref(&self) -> EntityRef {
         self.handle.entity_ref()
@@ -337,6 +800,18 @@ impl<T> AsEntityRef for UnboundedSender<T> {
     }
 }
 
+impl<T> AsEntityRef for Receiver<T> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
+impl<T> AsEntityRef for UnboundedReceiver<T> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
 impl<T> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
@@ -361,6 +836,24 @@ impl<T> fmt::Debug for UnboundedReceiver<T> {
     }
 }
 
+impl<T> fmt::Debug for Permit<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for WeakSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for WeakUnboundedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
 impl<T> fmt::Debug for OwnedPermit<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
@@ -2,49 +2,126 @@
 use moire_types::{EdgeKind, LockEntity, LockKind};
 use std::fmt;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
 
 use moire_runtime::{
-    AsEntityRef, EdgeHandle, EntityHandle, EntityRef, HELD_MUTEX_STACK,
-    current_causal_target_with_task_fallback, instrument_operation_on_with_actor,
+    AsEntityRef, EntityHandle, EntityRef, current_causal_target_with_task_fallback,
+    instrument_operation_on_with_actor,
 };
 
+use super::lock_state::{Access, Held, Holder, LockHold, LockWaiter};
+use super::value_inspect::{ValueInspector, render_debug};
+
 /// Instrumented version of [`tokio::sync::Mutex`].
 pub struct Mutex<T> {
     inner: tokio::sync::Mutex<T>,
     handle: EntityHandle<moire_types::Lock>,
+    inspector: Option<ValueInspector<T>>,
 }
 
 /// Guard returned by [`Mutex`], equivalent to [`tokio::sync::MutexGuard`].
 pub struct MutexGuard<'a, T> {
-    inner: tokio::sync::MutexGuard<'a, T>,
-    lock_id: moire_types::EntityId,
-    holds_edge: Option<EdgeHandle>,
+    held: Held<tokio::sync::MutexGuard<'a, T>>,
+    /// Set for mutexes made with `new_debug`: the value is rendered on release.
+    inspector: Option<&'a ValueInspector<T>>,
+}
+
+/// Guard returned by [`MutexGuard::map`], equivalent to [`tokio::sync::MappedMutexGuard`].
+pub struct MappedMutexGuard<'a, U: ?Sized> {
+    held: Held<tokio::sync::MappedMutexGuard<'a, U>>,
+}
+
+/// Guard returned by [`Mutex::lock_owned`], equivalent to [`tokio::sync::OwnedMutexGuard`].
+///
+/// The guard can move between tasks and threads; its `held_by` edge follows
+/// it to wherever it is used.
+pub struct OwnedMutexGuard<T: 'static> {
+    // Declared before `mutex`, which it borrows from.
+    held: Held<tokio::sync::MutexGuard<'static, T>>,
+    mutex: Arc<Mutex<T>>,
+}
+
+/// Guard returned by [`OwnedMutexGuard::map`], equivalent to
+/// [`tokio::sync::OwnedMappedMutexGuard`].
+pub struct OwnedMappedMutexGuard<T: 'static, U: ?Sized + 'static = T> {
+    held: Held<tokio::sync::MappedMutexGuard<'static, U>>,
+    _mutex: Arc<Mutex<T>>,
 }
 
 /// Instrumented version of [`parking_lot::Mutex`], preserving lock semantics with diagnostics.
 pub struct SyncMutex<T> {
     inner: parking_lot::Mutex<T>,
     handle: EntityHandle<moire_types::Lock>,
+    inspector: Option<ValueInspector<T>>,
 }
 
 /// Guard returned by [`SyncMutex`], equivalent to [`parking_lot::MutexGuard`].
 pub struct SyncMutexGuard<'a, T> {
     inner: parking_lot::MutexGuard<'a, T>,
-    lock_id: moire_types::EntityId,
-    holds_edge: Option<EdgeHandle>,
+    /// Set for mutexes made with `new_debug`: the value is rendered on release.
+    inspector: Option<&'a ValueInspector<T>>,
+    _holder: Holder,
+    _hold: LockHold,
 }
 
 impl<'a, T> Deref for MutexGuard<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &self.inner
+        self.held.guard()
     }
 }
 
 impl<'a, T> DerefMut for MutexGuard<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+        self.held.guard_mut()
+    }
+}
+
+impl<U: ?Sized> Deref for MappedMutexGuard<'_, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.guard()
+    }
+}
+
+impl<U: ?Sized> DerefMut for MappedMutexGuard<'_, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.held.guard_mut()
+    }
+}
+
+impl<T> Deref for OwnedMutexGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.holder().follow();
+        self.held.guard()
+    }
+}
+
+impl<T> DerefMut for OwnedMutexGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.held.holder().follow();
+        self.held.guard_mut()
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedMappedMutexGuard<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.holder().follow();
+        self.held.guard()
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedMappedMutexGuard<T, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.held.holder().follow();
+        self.held.guard_mut()
     }
 }
 
@@ -64,122 +141,308 @@ impl<'a, T> DerefMut for SyncMutexGuard<'a, T> {
 
 impl<T> Mutex<T> {
     /// Creates a new instrumented async mutex, equivalent to [`tokio::sync::Mutex::new`].
-    pub fn new(name: &'static str, value: T) -> Self {
-        let handle = EntityHandle::new(
-            name,
-            LockEntity {
-                kind: LockKind::Mutex,
-            },
-        );
+    pub fn new(name: impl Into<String>, value: T) -> Self {
+        let handle = EntityHandle::new(name, LockEntity::new(LockKind::Mutex));
         Self {
             inner: tokio::sync::Mutex::new(value),
             handle,
+            inspector: None,
         }
     }
 
+    /// Like [`new`](Self::new), but also records a truncated `Debug` rendering
+    /// of the protected value on the lock entity when guards are released.
+    pub fn new_debug(name: impl Into<String>, value: T) -> Self
+    where
+        T: fmt::Debug,
+    {
+        let mut entity = LockEntity::new(LockKind::Mutex);
+        entity.value = Some(render_debug(&value));
+        let handle = EntityHandle::new(name, entity);
+        Self {
+            inner: tokio::sync::Mutex::new(value),
+            inspector: Some(ValueInspector::debug(&handle, |body| &mut body.value)),
+            handle,
+        }
+    }
+
+    /// Renames the lock entity, e.g. to label it once the shard or
+    /// connection it guards is known.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Acquires the lock asynchronously, matching [`tokio::sync::Mutex::lock`].
     pub async fn lock(&self) -> MutexGuard<'_, T> {
         let owner_ref = current_causal_target_with_task_fallback();
+        if let Ok(inner) = self.inner.try_lock() {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            return self.wrap_guard(inner, owner_ref.as_ref(), None, hold);
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Exclusive);
         let inner =
             instrument_operation_on_with_actor(&self.handle, owner_ref.as_ref(), self.inner.lock())
                 .await;
-        self.wrap_guard(inner, owner_ref.as_ref(), None)
+        self.wrap_guard(inner, owner_ref.as_ref(), None, waiter.acquired())
     }
 
     /// Attempts lock acquisition without waiting, matching [`tokio::sync::Mutex::try_lock`].
     pub fn try_lock(&self) -> Result<MutexGuard<'_, T>, tokio::sync::TryLockError> {
         let owner_ref = current_causal_target_with_task_fallback();
-        self.inner
-            .try_lock()
-            .map(|inner| self.wrap_guard(inner, owner_ref.as_ref(), Some(EdgeKind::Polls)))
+        self.inner.try_lock().map(|inner| {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            self.wrap_guard(inner, owner_ref.as_ref(), Some(EdgeKind::Polls), hold)
+        })
     }
 
     fn wrap_guard<'a>(
-        &self,
+        &'a self,
         inner: tokio::sync::MutexGuard<'a, T>,
         owner_ref: Option<&EntityRef>,
         pre_edge_kind: Option<EdgeKind>,
+        hold: LockHold,
     ) -> MutexGuard<'a, T> {
         if let (Some(owner), Some(kind)) = (owner_ref, pre_edge_kind) {
             self.handle.link_to(owner, kind);
         }
 
-        let holds_edge = owner_ref.map(|owner| self.handle.link_to_owned(owner, EdgeKind::HeldBy));
-        let lock_id = self.handle.id().clone();
-
-        HELD_MUTEX_STACK.with(|stack| {
-            stack.borrow_mut().push(lock_id.clone());
-        });
+        let holder = Holder::new(&self.handle, owner_ref, true);
 
         MutexGuard {
-            inner,
-            lock_id,
-            holds_edge,
+            held: Held::new(inner, holder, hold),
+            inspector: self.inspector.as_ref(),
+        }
+    }
+}
+
+impl<T: 'static> Mutex<T> {
+    /// Acquires the lock through an `Arc`, like
+    /// [`tokio::sync::Mutex::lock_owned`]. The guard keeps a clone of the
+    /// `Arc`.
+    pub async fn lock_owned(self: &Arc<Self>) -> OwnedMutexGuard<T> {
+        let owner_ref = current_causal_target_with_task_fallback();
+        let inner = self.inner_for_owned_guard();
+        if let Ok(guard) = inner.try_lock() {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            return self.wrap_owned_guard(guard, owner_ref.as_ref(), None, hold);
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Exclusive);
+        let guard =
+            instrument_operation_on_with_actor(&self.handle, owner_ref.as_ref(), inner.lock())
+                .await;
+        self.wrap_owned_guard(guard, owner_ref.as_ref(), None, waiter.acquired())
+    }
+
+    /// Attempts owned lock acquisition without waiting, like
+    /// [`tokio::sync::Mutex::try_lock_owned`].
+    pub fn try_lock_owned(
+        self: &Arc<Self>,
+    ) -> Result<OwnedMutexGuard<T>, tokio::sync::TryLockError> {
+        let owner_ref = current_causal_target_with_task_fallback();
+        let guard = self.inner_for_owned_guard().try_lock()?;
+        let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+        Ok(self.wrap_owned_guard(guard, owner_ref.as_ref(), Some(EdgeKind::Polls), hold))
+    }
+
+    /// Borrows the Tokio mutex for as long as an owned guard needs it.
+    fn inner_for_owned_guard(self: &Arc<Self>) -> &'static tokio::sync::Mutex<T> {
+        // SAFETY: the mutex sits in the `Arc`'s allocation, which never moves.
+        // The borrow only outlives `self` inside an owned guard, and every
+        // owned guard holds a clone of the `Arc` and drops the Tokio guard
+        // before it.
+        unsafe { &*std::ptr::addr_of!(self.inner) }
+    }
+
+    fn wrap_owned_guard(
+        self: &Arc<Self>,
+        inner: tokio::sync::MutexGuard<'static, T>,
+        owner_ref: Option<&EntityRef>,
+        pre_edge_kind: Option<EdgeKind>,
+        hold: LockHold,
+    ) -> OwnedMutexGuard<T> {
+        if let (Some(owner), Some(kind)) = (owner_ref, pre_edge_kind) {
+            self.handle.link_to(owner, kind);
+        }
+
+        let holder = Holder::new(&self.handle, owner_ref, true);
+
+        OwnedMutexGuard {
+            held: Held::new(inner, holder, hold),
+            mutex: Arc::clone(self),
+        }
+    }
+}
+
+impl<'a, T> MutexGuard<'a, T> {
+    /// Narrows the guard to a part of the protected value, matching
+    /// [`tokio::sync::MutexGuard::map`].
+    ///
+    /// For mutexes made with `new_debug`, the value is rendered one last time
+    /// here; the mapped guard no longer records renderings.
+    pub fn map<U: ?Sized, F>(mut this: Self, f: F) -> MappedMutexGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        this.render_released();
+        let held = this.held.take_over();
+        MappedMutexGuard {
+            held: held.map(|inner| tokio::sync::MutexGuard::map(inner, f)),
+        }
+    }
+
+    /// Fallible variant of [`map`](Self::map), matching
+    /// [`tokio::sync::MutexGuard::try_map`].
+    // Returns the guard itself on failure, like Tokio's.
+    #[allow(clippy::result_large_err)]
+    pub fn try_map<U: ?Sized, F>(mut this: Self, f: F) -> Result<MappedMutexGuard<'a, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        this.render_released();
+        let held = this.held.take_over();
+        match held.try_map(|inner| tokio::sync::MutexGuard::try_map(inner, f)) {
+            Ok(held) => Ok(MappedMutexGuard { held }),
+            Err(held) => Err(MutexGuard {
+                held,
+                inspector: this.inspector,
+            }),
+        }
+    }
+
+    fn render_released(&self) {
+        if let Some(inspector) = self.inspector {
+            inspector.update(self.held.guard());
+        }
+    }
+}
+
+impl<T> OwnedMutexGuard<T> {
+    /// Narrows the guard to a part of the protected value, matching
+    /// [`tokio::sync::OwnedMutexGuard::map`].
+    ///
+    /// For mutexes made with `new_debug`, the value is rendered one last time
+    /// here; the mapped guard no longer records renderings.
+    pub fn map<U: ?Sized, F>(mut this: Self, f: F) -> OwnedMappedMutexGuard<T, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        this.render_released();
+        let held = this.held.take_over();
+        OwnedMappedMutexGuard {
+            held: held.map(|inner| tokio::sync::MutexGuard::map(inner, f)),
+            _mutex: Arc::clone(&this.mutex),
+        }
+    }
+
+    /// Fallible variant of [`map`](Self::map), matching
+    /// [`tokio::sync::OwnedMutexGuard::try_map`].
+    // Returns the guard itself on failure, like Tokio's.
+    #[allow(clippy::result_large_err)]
+    pub fn try_map<U: ?Sized, F>(mut this: Self, f: F) -> Result<OwnedMappedMutexGuard<T, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        this.render_released();
+        let held = this.held.take_over();
+        let mutex = Arc::clone(&this.mutex);
+        match held.try_map(|inner| tokio::sync::MutexGuard::try_map(inner, f)) {
+            Ok(held) => Ok(OwnedMappedMutexGuard {
+                held,
+                _mutex: mutex,
+            }),
+            Err(held) => Err(OwnedMutexGuard { held, mutex }),
+        }
+    }
+
+    fn render_released(&self) {
+        if let Some(inspector) = &self.mutex.inspector {
+            inspector.update(self.held.guard());
         }
     }
 }
 
 impl<T> SyncMutex<T> {
     /// Creates a new instrumented sync mutex, equivalent to [`parking_lot::Mutex::new`].
-    pub fn new(name: &'static str, value: T) -> Self {
-        let handle = EntityHandle::new(
-            name,
-            LockEntity {
-                kind: LockKind::Mutex,
-            },
-        );
+    pub fn new(name: impl Into<String>, value: T) -> Self {
+        let handle = EntityHandle::new(name, LockEntity::new(LockKind::Mutex));
         Self {
             inner: parking_lot::Mutex::new(value),
             handle,
+            inspector: None,
         }
     }
 
+    /// Like [`new`](Self::new), but also records a truncated `Debug` rendering
+    /// of the protected value on the lock entity when guards are released.
+    pub fn new_debug(name: impl Into<String>, value: T) -> Self
+    where
+        T: fmt::Debug,
+    {
+        let mut entity = LockEntity::new(LockKind::Mutex);
+        entity.value = Some(render_debug(&value));
+        let handle = EntityHandle::new(name, entity);
+        Self {
+            inner: parking_lot::Mutex::new(value),
+            inspector: Some(ValueInspector::debug(&handle, |body| &mut body.value)),
+            handle,
+        }
+    }
+
+    /// Renames the lock entity, e.g. to label it once the shard or
+    /// connection it guards is known.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Acquires the lock, matching [`parking_lot::Mutex::lock`].
     pub fn lock(&self) -> SyncMutexGuard<'_, T> {
         let owner_ref = current_causal_target_with_task_fallback();
 
         if let Some(inner) = self.inner.try_lock() {
-            return self.wrap_guard(inner, owner_ref.as_ref(), None);
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            return self.wrap_guard(inner, owner_ref.as_ref(), None, hold);
         }
 
+        let waiter = LockWaiter::new(&self.handle, Access::Exclusive);
         let waiting_edge = owner_ref
             .as_ref()
             .map(|owner| owner.link_to_owned(&self.handle, EdgeKind::WaitingOn));
+        let waiting_since = Instant::now();
         let inner = self.inner.lock();
+        self.handle
+            .record_wait(owner_ref.as_ref(), waiting_since.elapsed());
         drop(waiting_edge);
 
-        self.wrap_guard(inner, owner_ref.as_ref(), None)
+        self.wrap_guard(inner, owner_ref.as_ref(), None, waiter.acquired())
     }
 
     /// Attempts lock acquisition without blocking, matching [`parking_lot::Mutex::try_lock`].
     pub fn try_lock(&self) -> Option<SyncMutexGuard<'_, T>> {
         let owner_ref = current_causal_target_with_task_fallback();
-        self.inner
-            .try_lock()
-            .map(|inner| self.wrap_guard(inner, owner_ref.as_ref(), Some(EdgeKind::Polls)))
+        self.inner.try_lock().map(|inner| {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            self.wrap_guard(inner, owner_ref.as_ref(), Some(EdgeKind::Polls), hold)
+        })
     }
 
     fn wrap_guard<'a>(
-        &self,
+        &'a self,
         inner: parking_lot::MutexGuard<'a, T>,
         owner_ref: Option<&EntityRef>,
         pre_edge_kind: Option<EdgeKind>,
+        hold: LockHold,
     ) -> SyncMutexGuard<'a, T> {
         if let (Some(owner), Some(kind)) = (owner_ref, pre_edge_kind) {
             self.handle.link_to(owner, kind);
         }
 
-        let holds_edge = owner_ref.map(|owner| self.handle.link_to_owned(owner, EdgeKind::HeldBy));
-        let lock_id = self.handle.id().clone();
-
-        HELD_MUTEX_STACK.with(|stack| {
-            stack.borrow_mut().push(lock_id.clone());
-        });
+        let holder = Holder::new(&self.handle, owner_ref, true);
 
         SyncMutexGuard {
             inner,
-            lock_id,
-            holds_edge,
+            inspector: self.inspector.as_ref(),
+            _holder: holder,
+            _hold: hold,
         }
     }
 }
@@ -198,31 +461,25 @@ impl<T> AsEntityRef for SyncMutex<T> {
 
 impl<'a, T> Drop for MutexGuard<'a, T> {
     fn drop(&mut self) {
-        let _ = self.holds_edge.take();
-        HELD_MUTEX_STACK.with(|stack| {
-            let mut stack = stack.borrow_mut();
-            if let Some(pos) = stack
-                .iter()
-                .rposition(|id| id.as_str() == self.lock_id.as_str())
-            {
-                stack.remove(pos);
-            }
-        });
+        if self.held.is_held() {
+            self.render_released();
+        }
+    }
+}
+
+impl<T> Drop for OwnedMutexGuard<T> {
+    fn drop(&mut self) {
+        if self.held.is_held() {
+            self.render_released();
+        }
     }
 }
 
 impl<'a, T> Drop for SyncMutexGuard<'a, T> {
     fn drop(&mut self) {
-        let _ = self.holds_edge.take();
-        HELD_MUTEX_STACK.with(|stack| {
-            let mut stack = stack.borrow_mut();
-            if let Some(pos) = stack
-                .iter()
-                .rposition(|id| id.as_str() == self.lock_id.as_str())
-            {
-                stack.remove(pos);
-            }
-        });
+        if let Some(inspector) = self.inspector {
+            inspector.update(&self.inner);
+        }
     }
 }
 
@@ -234,7 +491,25 @@ impl<T: fmt::Debug> fmt::Debug for Mutex<T> {
 
 impl<T: fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.inner.fmt(f)
+        self.held.guard().fmt(f)
+    }
+}
+
+impl<U: ?Sized + fmt::Debug> fmt::Debug for MappedMutexGuard<'_, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OwnedMutexGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
+    }
+}
+
+impl<T, U: ?Sized + fmt::Debug> fmt::Debug for OwnedMappedMutexGuard<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
     }
 }
 
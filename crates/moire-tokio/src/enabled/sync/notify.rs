@@ -3,7 +3,7 @@ use moire_types::NotifyEntity;
 use std::fmt;
 use std::sync::Arc;
 
-use moire_runtime::{EntityHandle, instrument_operation_on};
+use moire_runtime::{AsEntityRef, EntityHandle, EntityRef, OperatingOn, instrument_operation_on};
 
 /// Instrumented version of [`tokio::sync::Notify`].
 #[derive(Clone)]
@@ -21,6 +21,11 @@ impl Notify {
             handle,
         }
     }
+
+    /// Renames the notify entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
     /// Waits for a notification, matching [`tokio::sync::Notify::notified`].
     pub async fn notified(&self) {
         let _ = self
@@ -36,15 +41,23 @@ impl Notify {
 
     /// Notifies one waiter, matching [`tokio::sync::Notify::notify_one`].
     pub fn notify_one(&self) {
+        let _operating = OperatingOn::enter(self.handle.id());
         self.inner.notify_one();
     }
 
     /// Notifies all waiters, matching [`tokio::sync::Notify::notify_waiters`].
     pub fn notify_waiters(&self) {
+        let _operating = OperatingOn::enter(self.handle.id());
         self.inner.notify_waiters();
     }
 }
 
+impl AsEntityRef for Notify {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
 impl fmt::Debug for Notify {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
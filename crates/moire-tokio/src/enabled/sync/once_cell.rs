@@ -2,13 +2,19 @@
 use moire_types::{OnceCellEntity, OnceCellState};
 use std::fmt;
 use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use moire_runtime::{EntityHandle, instrument_operation_on};
+use moire_runtime::{AsEntityRef, EntityHandle, EntityRef, OperatingOn, instrument_operation_on};
+
+use super::value_inspect::render_debug;
 
 /// Instrumented version of [`tokio::sync::OnceCell`].
 pub struct OnceCell<T> {
     inner: tokio::sync::OnceCell<T>,
     handle: EntityHandle<moire_types::OnceCell>,
+    /// Set for cells made with `new_debug`.
+    render: Option<fn(&T) -> String>,
+    rendered: AtomicBool,
 }
 
 impl<T> OnceCell<T> {
@@ -19,11 +25,31 @@ impl<T> OnceCell<T> {
             OnceCellEntity {
                 waiter_count: 0,
                 state: OnceCellState::Empty,
+                value: None,
             },
         );
         Self {
             inner: tokio::sync::OnceCell::new(),
             handle,
+            render: None,
+            rendered: AtomicBool::new(false),
+        }
+    }
+
+    /// Renames the cell entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
+    /// Like [`new`](Self::new), but also records a truncated `Debug` rendering
+    /// of the value on the once-cell entity once it is set.
+    pub fn new_debug(name: impl Into<String>) -> Self
+    where
+        T: fmt::Debug,
+    {
+        Self {
+            render: Some(render_debug::<T>),
+            ..Self::new(name)
         }
     }
 
@@ -51,8 +77,12 @@ impl<T> OnceCell<T> {
         let result = instrument_operation_on(&self.handle, self.inner.get_or_init(f)).await;
 
         let initialized = self.inner.initialized();
+        let value = self.render_value();
         let _ = self.handle.mutate(|body| {
             body.waiter_count = body.waiter_count.saturating_sub(1);
+            if value.is_some() {
+                body.value = value;
+            }
             body.state = if initialized {
                 OnceCellState::Initialized
             } else if body.waiter_count > 0 {
@@ -78,8 +108,12 @@ impl<T> OnceCell<T> {
         let result = instrument_operation_on(&self.handle, self.inner.get_or_try_init(f)).await;
 
         let initialized = self.inner.initialized();
+        let value = self.render_value();
         let _ = self.handle.mutate(|body| {
             body.waiter_count = body.waiter_count.saturating_sub(1);
+            if value.is_some() {
+                body.value = value;
+            }
             body.state = if initialized {
                 OnceCellState::Initialized
             } else if body.waiter_count > 0 {
@@ -94,12 +128,20 @@ impl<T> OnceCell<T> {
 
     /// Sets the value, matching [`tokio::sync::OnceCell::set`].
     pub fn set(&self, value: T) -> Result<(), T> {
-        let result = self.inner.set(value).map_err(|e| match e {
+        let result = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.inner.set(value)
+        }
+        .map_err(|e| match e {
             tokio::sync::SetError::AlreadyInitializedError(v) => v,
             tokio::sync::SetError::InitializingError(v) => v,
         });
         let initialized = self.inner.initialized();
+        let value = self.render_value();
         let _ = self.handle.mutate(|body| {
+            if value.is_some() {
+                body.value = value;
+            }
             body.state = if initialized {
                 OnceCellState::Initialized
             } else if body.waiter_count > 0 {
@@ -110,6 +152,19 @@ impl<T> OnceCell<T> {
         });
         result
     }
+
+    /// Renders the value for `new_debug` cells, the first time it is seen set.
+    fn render_value(&self) -> Option<String> {
+        let render = self.render?;
+        let value = self.inner.get()?;
+        (!self.rendered.swap(true, Ordering::Relaxed)).then(|| render(value))
+    }
+}
+
+impl<T> AsEntityRef for OnceCell<T> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for OnceCell<T> {
@@ -3,7 +3,8 @@
 pub use tokio::sync::oneshot::error;
 
 use moire_runtime::{
-    EntityHandle, WeakEntityHandle, instrument_operation_on, new_event, record_event,
+    AsEntityRef, EntityHandle, EntityRef, OperatingOn, WeakEntityHandle, instrument_operation_on,
+    new_event, record_event,
 };
 use moire_types::{EdgeKind, EventKind, EventTarget, OneshotRxEntity, OneshotTxEntity};
 use std::fmt;
@@ -73,13 +74,23 @@ impl<T> Sender<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::OneshotTx> {
         &self.handle
     }
+
+    /// Renames the sender entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Sends a single value, equivalent to [`tokio::sync::oneshot::Sender::send`].
     /// Records a one-shot send event and consumption status.
     pub fn send(mut self, value: T) -> Result<(), T> {
         let Some(inner) = self.inner.take() else {
             return Err(value);
         };
-        match inner.send(value) {
+        let sent = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            inner.send(value)
+        };
+        match sent {
             Ok(()) => {
                 let _ = self.handle.mutate(|body| body.sent = true);
                 let event = new_event(
@@ -106,6 +117,11 @@ impl<T> Receiver<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::OneshotRx> {
         &self.handle
     }
+
+    /// Renames the receiver entity.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
 }
 
 /// Creates an instrumented oneshot channel, equivalent to [`tokio::sync::oneshot::channel`].
@@ -115,7 +131,7 @@ pub fn channel<T>(name: impl Into<String>) -> (Sender<T>, Receiver<T>) {
 
     let tx_handle = EntityHandle::new(format!("{name}:tx"), OneshotTxEntity { sent: false });
 
-    let rx_handle = EntityHandle::new(format!("{name}:rx"), OneshotRxEntity {});
+    let rx_handle = EntityHandle::new(format!("{name}:rx"), OneshotRxEntity { cancelled_recvs: 0 });
 
     tx_handle.link_to_handle(&rx_handle, EdgeKind::PairedWith);
 
@@ -132,6 +148,18 @@ pub fn channel<T>(name: impl Into<String>) -> (Sender<T>, Receiver<T>) {
     )
 }
 
+impl<T> AsEntityRef for Sender<T> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
+impl<T> AsEntityRef for Receiver<T> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
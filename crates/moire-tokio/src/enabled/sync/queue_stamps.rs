@@ -0,0 +1,301 @@
+//! Send stamps kept beside a channel's queue: when each message was sent, by
+//! whom and from where. Used to measure how long messages sit in the queue and
+//! who filled it.
+//!
+//! The wrapped tokio channel can't carry anything extra per message, so while
+//! tracking every send pushes one entry here while holding the lock. A
+//! receiver finds the entry of the message it just got from how many messages
+//! are still queued behind it. Entries stay in the same order as the messages
+//! as long as sends go through [`QueueStamps::send`].
+//!
+//! Tracking starts with the first stamped send and stops once detail is off
+//! and no stamped message is left in the queue. While not tracking, sends and
+//! receives don't touch the stamp state at all. A send racing the one that starts
+//! tracking may be left out, which only skews the stamps of the messages
+//! queued around it until it has been received.
+
+use moire_runtime::{
+    KindDetail, capture_callsite_backtrace, current_causal_target_with_task_fallback,
+    send_provenance_enabled,
+};
+use moire_types::{BacktraceId, EntityId, PTime, QueueLatency, QueuedBy};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex as StdMutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+struct SendStamp {
+    at: Instant,
+    ptime: PTime,
+    /// Only recorded while send provenance is on.
+    provenance: Option<Provenance>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Provenance {
+    producer: Option<EntityId>,
+    backtrace: BacktraceId,
+}
+
+pub(crate) struct QueueStamps {
+    /// Detail setting that turns stamping on.
+    detail: &'static KindDetail,
+    /// How many messages the channel keeps around for slow receivers; `None`
+    /// when it keeps them until they're received.
+    retain: Option<usize>,
+    /// Whether sends push entries into `state`. Only changes under its lock.
+    tracking: AtomicBool,
+    state: StdMutex<StampState>,
+}
+
+#[derive(Default)]
+struct StampState {
+    /// One entry per message sent while tracking, in send order. `None` for
+    /// messages sent while stamping was off.
+    stamps: VecDeque<Option<SendStamp>>,
+    /// Sequence number of `stamps[0]`.
+    first_seq: u64,
+    /// Sequence number of the next message each receiver will get, as of its
+    /// last receive.
+    cursors: HashMap<u64, u64>,
+    next_receiver_id: u64,
+    /// Stamped entries in `stamps`.
+    stamped: usize,
+    /// Stamped entries in `stamps` that have a provenance, by provenance.
+    queued_by: HashMap<Provenance, u32>,
+}
+
+impl StampState {
+    fn end_seq(&self) -> u64 {
+        self.first_seq + self.stamps.len() as u64
+    }
+
+    fn stamp_at(&self, seq: u64) -> Option<&SendStamp> {
+        let index = seq.checked_sub(self.first_seq)?;
+        self.stamps.get(index as usize)?.as_ref()
+    }
+
+    fn push(&mut self, stamp: Option<SendStamp>) {
+        if let Some(stamp) = stamp.as_ref() {
+            self.stamped += 1;
+            if let Some(provenance) = stamp.provenance.as_ref() {
+                *self.queued_by.entry(provenance.clone()).or_default() += 1;
+            }
+        }
+        self.stamps.push_back(stamp);
+    }
+
+    fn forget(&mut self, stamp: Option<SendStamp>) {
+        let Some(stamp) = stamp else {
+            return;
+        };
+        self.stamped -= 1;
+        let Some(provenance) = stamp.provenance else {
+            return;
+        };
+        if let Some(count) = self.queued_by.get_mut(&provenance) {
+            *count -= 1;
+            if *count == 0 {
+                self.queued_by.remove(&provenance);
+            }
+        }
+    }
+
+    /// Drops entries every receiver is past, or that the channel itself has
+    /// let go of.
+    fn trim(&mut self, retain: Option<usize>) {
+        let min_cursor = self
+            .cursors
+            .values()
+            .copied()
+            .min()
+            .unwrap_or(self.end_seq());
+        while self.first_seq < min_cursor || retain.is_some_and(|retain| self.stamps.len() > retain)
+        {
+            let Some(stamp) = self.stamps.pop_front() else {
+                break;
+            };
+            self.forget(stamp);
+            self.first_seq += 1;
+        }
+    }
+
+    /// Drops every entry, once none of them is stamped.
+    fn clear(&mut self) {
+        let end_seq = self.end_seq();
+        self.stamps.clear();
+        self.first_seq = end_seq;
+        for cursor in self.cursors.values_mut() {
+            *cursor = end_seq;
+        }
+    }
+
+    fn update(&self, latencies_ns: Vec<u64>) -> QueueUpdate {
+        // Send time of the oldest message some receiver still has to get.
+        let oldest_queued_at = self.cursors.values().copied().min().and_then(|min_cursor| {
+            self.stamp_at(min_cursor.max(self.first_seq))
+                .map(|stamp| stamp.ptime)
+        });
+        let mut queued_by = self
+            .queued_by
+            .iter()
+            .map(|(provenance, count)| QueuedBy {
+                producer: provenance.producer.clone(),
+                backtrace: provenance.backtrace,
+                count: *count,
+            })
+            .collect::<Vec<_>>();
+        queued_by.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.backtrace.cmp(&b.backtrace))
+        });
+        queued_by.truncate(QueuedBy::MAX_GROUPS);
+        QueueUpdate {
+            latencies_ns,
+            oldest_queued_at,
+            queued_by,
+        }
+    }
+}
+
+/// What a send or receive changed, to be published on the sender entity.
+pub(crate) struct QueueUpdate {
+    latencies_ns: Vec<u64>,
+    oldest_queued_at: Option<PTime>,
+    queued_by: Vec<QueuedBy>,
+}
+
+impl QueueStamps {
+    pub(crate) fn new(detail: &'static KindDetail, retain: Option<usize>) -> Self {
+        Self {
+            detail,
+            retain,
+            tracking: AtomicBool::new(false),
+            state: StdMutex::new(StampState::default()),
+        }
+    }
+
+    /// Registers a receiver whose first message is the next one sent.
+    /// `subscribe` runs under the lock so no send can slip in between.
+    pub(crate) fn add_receiver<R>(&self, subscribe: impl FnOnce() -> R) -> (u64, R) {
+        let mut state = self.lock();
+        let id = state.next_receiver_id;
+        state.next_receiver_id += 1;
+        let cursor = state.end_seq();
+        state.cursors.insert(id, cursor);
+        (id, subscribe())
+    }
+
+    pub(crate) fn remove_receiver(&self, id: u64) {
+        let mut state = self.lock();
+        state.cursors.remove(&id);
+        state.trim(self.retain);
+    }
+
+    /// Stamps a message and enqueues it with `send`. A failed send leaves no
+    /// entry behind.
+    ///
+    /// Returns what to publish when stamping is on.
+    pub(crate) fn send<R, E>(
+        &self,
+        send: impl FnOnce() -> Result<R, E>,
+    ) -> (Result<R, E>, Option<QueueUpdate>) {
+        let stamped = self.detail.enabled();
+        if !stamped && !self.tracking.load(Ordering::Acquire) {
+            return (send(), None);
+        }
+        let mut state = self.lock();
+        if !stamped && state.stamped == 0 {
+            // Detail is off and nothing stamped is left to follow.
+            state.clear();
+            self.tracking.store(false, Ordering::Release);
+            drop(state);
+            return (send(), None);
+        }
+        let stamp = stamped.then(|| SendStamp {
+            at: Instant::now(),
+            ptime: PTime::now(),
+            provenance: send_provenance_enabled().then(|| Provenance {
+                producer: current_causal_target_with_task_fallback()
+                    .map(|target| target.id().clone()),
+                backtrace: capture_callsite_backtrace(),
+            }),
+        });
+        self.tracking.store(true, Ordering::Release);
+        state.push(stamp);
+        let result = send();
+        if result.is_err() {
+            let stamp = state.stamps.pop_back().flatten();
+            state.forget(stamp);
+        }
+        state.trim(self.retain);
+        let update = stamped.then(|| state.update(Vec::new()));
+        (result, update)
+    }
+
+    /// Moves receiver `id` past the `count` messages it just got, with
+    /// `remaining` (called under the lock) the number still queued for it.
+    ///
+    /// Returns what to publish, or `None` while not tracking.
+    pub(crate) fn received(
+        &self,
+        id: u64,
+        count: usize,
+        remaining: impl FnOnce() -> usize,
+    ) -> Option<QueueUpdate> {
+        if count == 0 || !self.tracking.load(Ordering::Acquire) {
+            return None;
+        }
+        let mut state = self.lock();
+        let end_seq = state.end_seq();
+        let mut latencies_ns = Vec::new();
+        // Messages queued from before tracking started have no entry.
+        if let Some(next_seq) = end_seq.checked_sub(remaining() as u64) {
+            for seq in next_seq.saturating_sub(count as u64)..next_seq {
+                if let Some(stamp) = state.stamp_at(seq) {
+                    latencies_ns
+                        .push(u64::try_from(stamp.at.elapsed().as_nanos()).unwrap_or(u64::MAX));
+                }
+            }
+            if let Some(cursor) = state.cursors.get_mut(&id) {
+                *cursor = next_seq;
+            }
+        }
+        state.trim(self.retain);
+        Some(state.update(latencies_ns))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StampState> {
+        // The state is only ever left half-updated by a panicking `send`, and
+        // even then it's merely off by one entry.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl QueueUpdate {
+    /// Publishes this update into a sender entity's fields. Leaves an
+    /// untracked channel alone.
+    pub(crate) fn apply(self, latency: &mut Option<QueueLatency>, queued_by: &mut Vec<QueuedBy>) {
+        let tracked = self.is_tracked();
+        for latency_ns in self.latencies_ns {
+            latency
+                .get_or_insert_with(Default::default)
+                .record(latency_ns);
+        }
+        if tracked || latency.is_some() {
+            latency
+                .get_or_insert_with(Default::default)
+                .oldest_queued_at = self.oldest_queued_at;
+        }
+        *queued_by = self.queued_by;
+    }
+
+    /// Whether there's anything to publish: either a message was stamped, or
+    /// older stamped ones are still queued.
+    pub(crate) fn is_tracked(&self) -> bool {
+        !self.latencies_ns.is_empty()
+            || self.oldest_queued_at.is_some()
+            || !self.queued_by.is_empty()
+    }
+}
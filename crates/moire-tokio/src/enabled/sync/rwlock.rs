@@ -2,12 +2,16 @@
 use moire_types::{EdgeKind, LockEntity, LockKind};
 use std::fmt;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
 
 use moire_runtime::{
-    AsEntityRef, EdgeHandle, EntityHandle, EntityRef, current_causal_target_with_task_fallback,
+    AsEntityRef, EntityHandle, EntityRef, current_causal_target_with_task_fallback,
     instrument_operation_on_with_actor,
 };
 
+use super::lock_state::{Access, Held, Holder, LockHold, LockWaiter};
+
 /// Instrumented version of [`tokio::sync::RwLock`].
 pub struct RwLock<T> {
     inner: tokio::sync::RwLock<T>,
@@ -15,24 +19,140 @@ pub struct RwLock<T> {
 }
 
 /// Read guard returned by [`RwLock::read`].
-pub struct RwLockReadGuard<'a, T> {
-    inner: tokio::sync::RwLockReadGuard<'a, T>,
-    holds_edge: Option<EdgeHandle>,
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    held: Held<tokio::sync::RwLockReadGuard<'a, T>>,
 }
 
 /// Write guard returned by [`RwLock::write`].
-pub struct RwLockWriteGuard<'a, T> {
-    inner: tokio::sync::RwLockWriteGuard<'a, T>,
-    holds_edge: Option<EdgeHandle>,
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    held: Held<tokio::sync::RwLockWriteGuard<'a, T>>,
+}
+
+/// Write guard returned by [`RwLockWriteGuard::map`], equivalent to
+/// [`tokio::sync::RwLockMappedWriteGuard`].
+pub struct RwLockMappedWriteGuard<'a, T: ?Sized> {
+    held: Held<tokio::sync::RwLockMappedWriteGuard<'a, T>>,
+}
+
+/// Read guard returned by [`RwLock::read_owned`], equivalent to
+/// [`tokio::sync::OwnedRwLockReadGuard`].
+///
+/// Owned guards can move between tasks and threads; their `held_by` edge
+/// follows them to wherever they are used.
+pub struct OwnedRwLockReadGuard<T: 'static, U: ?Sized + 'static = T> {
+    // Owned guards declare `held` before `lock`, which it borrows from.
+    held: Held<tokio::sync::RwLockReadGuard<'static, U>>,
+    lock: Arc<RwLock<T>>,
 }
 
+/// Write guard returned by [`RwLock::write_owned`], equivalent to
+/// [`tokio::sync::OwnedRwLockWriteGuard`].
+pub struct OwnedRwLockWriteGuard<T: 'static> {
+    held: Held<tokio::sync::RwLockWriteGuard<'static, T>>,
+    lock: Arc<RwLock<T>>,
+}
+
+/// Write guard returned by [`OwnedRwLockWriteGuard::map`], equivalent to
+/// [`tokio::sync::OwnedRwLockMappedWriteGuard`].
+pub struct OwnedRwLockMappedWriteGuard<T: 'static, U: ?Sized + 'static = T> {
+    held: Held<tokio::sync::RwLockMappedWriteGuard<'static, U>>,
+    _lock: Arc<RwLock<T>>,
+}
 /// Instrumented version of [`parking_lot::RwLock`].
 pub struct SyncRwLock<T> {
     inner: parking_lot::RwLock<T>,
     handle: EntityHandle<moire_types::Lock>,
 }
 
-impl<'a, T> Deref for RwLockReadGuard<'a, T> {
+/// Read guard returned by [`SyncRwLock::read`], equivalent to [`parking_lot::RwLockReadGuard`].
+pub struct SyncRwLockReadGuard<'a, T> {
+    inner: parking_lot::RwLockReadGuard<'a, T>,
+    _hold: LockHold,
+}
+
+/// Write guard returned by [`SyncRwLock::write`], equivalent to [`parking_lot::RwLockWriteGuard`].
+pub struct SyncRwLockWriteGuard<'a, T> {
+    inner: parking_lot::RwLockWriteGuard<'a, T>,
+    _hold: LockHold,
+}
+
+impl<'a, T: ?Sized> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.guard()
+    }
+}
+
+impl<'a, T: ?Sized> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.guard()
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for RwLockWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.held.guard_mut()
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockMappedWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.guard()
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockMappedWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.held.guard_mut()
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedRwLockReadGuard<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.holder().follow();
+        self.held.guard()
+    }
+}
+
+impl<T> Deref for OwnedRwLockWriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.holder().follow();
+        self.held.guard()
+    }
+}
+
+impl<T> DerefMut for OwnedRwLockWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.held.holder().follow();
+        self.held.guard_mut()
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedRwLockMappedWriteGuard<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        self.held.holder().follow();
+        self.held.guard()
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedRwLockMappedWriteGuard<T, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.held.holder().follow();
+        self.held.guard_mut()
+    }
+}
+impl<'a, T> Deref for SyncRwLockReadGuard<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -40,7 +160,7 @@ impl<'a, T> Deref for RwLockReadGuard<'a, T> {
     }
 }
 
-impl<'a, T> Deref for RwLockWriteGuard<'a, T> {
+impl<'a, T> Deref for SyncRwLockWriteGuard<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -48,7 +168,7 @@ impl<'a, T> Deref for RwLockWriteGuard<'a, T> {
     }
 }
 
-impl<'a, T> DerefMut for RwLockWriteGuard<'a, T> {
+impl<'a, T> DerefMut for SyncRwLockWriteGuard<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.inner
     }
@@ -56,128 +176,408 @@ impl<'a, T> DerefMut for RwLockWriteGuard<'a, T> {
 
 impl<T> RwLock<T> {
     /// Creates a new instrumented async read-write lock, matching [`tokio::sync::RwLock::new`].
-    pub fn new(name: &'static str, value: T) -> Self {
-        let handle = EntityHandle::new(
-            name,
-            LockEntity {
-                kind: LockKind::RwLock,
-            },
-        );
+    pub fn new(name: impl Into<String>, value: T) -> Self {
+        let handle = EntityHandle::new(name, LockEntity::new(LockKind::RwLock));
         Self {
             inner: tokio::sync::RwLock::new(value),
             handle,
         }
     }
 
+    /// Renames the lock entity, e.g. to label it once the shard or
+    /// connection it guards is known.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Acquires a shared read guard asynchronously, matching [`tokio::sync::RwLock::read`].
     pub async fn read(&self) -> RwLockReadGuard<'_, T> {
         let owner_ref = current_causal_target_with_task_fallback();
+        if let Ok(inner) = self.inner.try_read() {
+            let hold = LockHold::uncontended(&self.handle, Access::Shared);
+            return RwLockReadGuard {
+                held: self.held(inner, owner_ref.as_ref(), None, hold),
+            };
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Shared);
         let inner =
             instrument_operation_on_with_actor(&self.handle, owner_ref.as_ref(), self.inner.read())
                 .await;
-        self.wrap_read_guard(inner, owner_ref.as_ref(), None)
+        RwLockReadGuard {
+            held: self.held(inner, owner_ref.as_ref(), None, waiter.acquired()),
+        }
     }
 
     /// Acquires an exclusive write guard asynchronously, matching [`tokio::sync::RwLock::write`].
     pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
         let owner_ref = current_causal_target_with_task_fallback();
+        if let Ok(inner) = self.inner.try_write() {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            return RwLockWriteGuard {
+                held: self.held(inner, owner_ref.as_ref(), None, hold),
+            };
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Exclusive);
         let inner = instrument_operation_on_with_actor(
             &self.handle,
             owner_ref.as_ref(),
             self.inner.write(),
         )
         .await;
-        self.wrap_write_guard(inner, owner_ref.as_ref(), None)
+        RwLockWriteGuard {
+            held: self.held(inner, owner_ref.as_ref(), None, waiter.acquired()),
+        }
     }
 
     /// Attempts a non-blocking read lock, matching [`tokio::sync::RwLock::try_read`].
     pub fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, tokio::sync::TryLockError> {
         let owner_ref = current_causal_target_with_task_fallback();
-        self.inner
-            .try_read()
-            .map(|inner| self.wrap_read_guard(inner, owner_ref.as_ref(), Some(EdgeKind::Polls)))
+        self.inner.try_read().map(|inner| {
+            let hold = LockHold::uncontended(&self.handle, Access::Shared);
+            RwLockReadGuard {
+                held: self.held(inner, owner_ref.as_ref(), Some(EdgeKind::Polls), hold),
+            }
+        })
     }
 
     /// Attempts a non-blocking write lock, matching [`tokio::sync::RwLock::try_write`].
     pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, T>, tokio::sync::TryLockError> {
         let owner_ref = current_causal_target_with_task_fallback();
-        self.inner
-            .try_write()
-            .map(|inner| self.wrap_write_guard(inner, owner_ref.as_ref(), Some(EdgeKind::Polls)))
+        self.inner.try_write().map(|inner| {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            RwLockWriteGuard {
+                held: self.held(inner, owner_ref.as_ref(), Some(EdgeKind::Polls), hold),
+            }
+        })
     }
 
-    fn wrap_read_guard<'a>(
+    fn held<G>(
         &self,
-        inner: tokio::sync::RwLockReadGuard<'a, T>,
+        guard: G,
         owner_ref: Option<&EntityRef>,
         pre_edge_kind: Option<EdgeKind>,
-    ) -> RwLockReadGuard<'a, T> {
+        hold: LockHold,
+    ) -> Held<G> {
         if let (Some(owner), Some(kind)) = (owner_ref, pre_edge_kind) {
             self.handle.link_to(owner, kind);
         }
-        let holds_edge = owner_ref.map(|owner| self.handle.link_to_owned(owner, EdgeKind::HeldBy));
-        RwLockReadGuard { inner, holds_edge }
+        Held::new(guard, Holder::new(&self.handle, owner_ref, false), hold)
     }
+}
 
-    fn wrap_write_guard<'a>(
-        &self,
-        inner: tokio::sync::RwLockWriteGuard<'a, T>,
-        owner_ref: Option<&EntityRef>,
-        pre_edge_kind: Option<EdgeKind>,
-    ) -> RwLockWriteGuard<'a, T> {
-        if let (Some(owner), Some(kind)) = (owner_ref, pre_edge_kind) {
-            self.handle.link_to(owner, kind);
+impl<T: 'static> RwLock<T> {
+    /// Acquires a shared read guard through an `Arc`, like
+    /// [`tokio::sync::RwLock::read_owned`]. The guard keeps a clone of the
+    /// `Arc`.
+    pub async fn read_owned(self: &Arc<Self>) -> OwnedRwLockReadGuard<T> {
+        let owner_ref = current_causal_target_with_task_fallback();
+        let inner = self.inner_for_owned_guard();
+        if let Ok(guard) = inner.try_read() {
+            let hold = LockHold::uncontended(&self.handle, Access::Shared);
+            return OwnedRwLockReadGuard {
+                held: self.held(guard, owner_ref.as_ref(), None, hold),
+                lock: Arc::clone(self),
+            };
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Shared);
+        let guard =
+            instrument_operation_on_with_actor(&self.handle, owner_ref.as_ref(), inner.read())
+                .await;
+        OwnedRwLockReadGuard {
+            held: self.held(guard, owner_ref.as_ref(), None, waiter.acquired()),
+            lock: Arc::clone(self),
         }
-        let holds_edge = owner_ref.map(|owner| self.handle.link_to_owned(owner, EdgeKind::HeldBy));
-        RwLockWriteGuard { inner, holds_edge }
+    }
+
+    /// Acquires an exclusive write guard through an `Arc`, like
+    /// [`tokio::sync::RwLock::write_owned`]. The guard keeps a clone of the
+    /// `Arc`.
+    pub async fn write_owned(self: &Arc<Self>) -> OwnedRwLockWriteGuard<T> {
+        let owner_ref = current_causal_target_with_task_fallback();
+        let inner = self.inner_for_owned_guard();
+        if let Ok(guard) = inner.try_write() {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            return OwnedRwLockWriteGuard {
+                held: self.held(guard, owner_ref.as_ref(), None, hold),
+                lock: Arc::clone(self),
+            };
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Exclusive);
+        let guard =
+            instrument_operation_on_with_actor(&self.handle, owner_ref.as_ref(), inner.write())
+                .await;
+        OwnedRwLockWriteGuard {
+            held: self.held(guard, owner_ref.as_ref(), None, waiter.acquired()),
+            lock: Arc::clone(self),
+        }
+    }
+
+    /// Attempts a non-blocking owned read lock, like
+    /// [`tokio::sync::RwLock::try_read_owned`].
+    pub fn try_read_owned(
+        self: &Arc<Self>,
+    ) -> Result<OwnedRwLockReadGuard<T>, tokio::sync::TryLockError> {
+        let owner_ref = current_causal_target_with_task_fallback();
+        let guard = self.inner_for_owned_guard().try_read()?;
+        let hold = LockHold::uncontended(&self.handle, Access::Shared);
+        Ok(OwnedRwLockReadGuard {
+            held: self.held(guard, owner_ref.as_ref(), Some(EdgeKind::Polls), hold),
+            lock: Arc::clone(self),
+        })
+    }
+
+    /// Attempts a non-blocking owned write lock, like
+    /// [`tokio::sync::RwLock::try_write_owned`].
+    pub fn try_write_owned(
+        self: &Arc<Self>,
+    ) -> Result<OwnedRwLockWriteGuard<T>, tokio::sync::TryLockError> {
+        let owner_ref = current_causal_target_with_task_fallback();
+        let guard = self.inner_for_owned_guard().try_write()?;
+        let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+        Ok(OwnedRwLockWriteGuard {
+            held: self.held(guard, owner_ref.as_ref(), Some(EdgeKind::Polls), hold),
+            lock: Arc::clone(self),
+        })
+    }
+
+    /// Borrows the Tokio lock for as long as an owned guard needs it.
+    fn inner_for_owned_guard(self: &Arc<Self>) -> &'static tokio::sync::RwLock<T> {
+        // SAFETY: the lock sits in the `Arc`'s allocation, which never moves.
+        // The borrow only outlives `self` inside an owned guard, and every
+        // owned guard holds a clone of the `Arc` and drops the Tokio guard
+        // before it.
+        unsafe { &*std::ptr::addr_of!(self.inner) }
+    }
+}
+
+impl<'a, T: ?Sized> RwLockReadGuard<'a, T> {
+    /// Narrows the guard to a part of the protected value, matching
+    /// [`tokio::sync::RwLockReadGuard::map`].
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> RwLockReadGuard<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        RwLockReadGuard {
+            held: this
+                .held
+                .map(|inner| tokio::sync::RwLockReadGuard::map(inner, f)),
+        }
+    }
+
+    /// Fallible variant of [`map`](Self::map), matching
+    /// [`tokio::sync::RwLockReadGuard::try_map`].
+    // Returns the guard itself on failure, like Tokio's.
+    #[allow(clippy::result_large_err)]
+    pub fn try_map<U: ?Sized, F>(this: Self, f: F) -> Result<RwLockReadGuard<'a, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        this.held
+            .try_map(|inner| tokio::sync::RwLockReadGuard::try_map(inner, f))
+            .map(|held| RwLockReadGuard { held })
+            .map_err(|held| Self { held })
     }
 }
 
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    /// Narrows the guard to a part of the protected value, matching
+    /// [`tokio::sync::RwLockWriteGuard::map`].
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> RwLockMappedWriteGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        RwLockMappedWriteGuard {
+            held: this
+                .held
+                .map(|inner| tokio::sync::RwLockWriteGuard::map(inner, f)),
+        }
+    }
+
+    /// Fallible variant of [`map`](Self::map), matching
+    /// [`tokio::sync::RwLockWriteGuard::try_map`].
+    // Returns the guard itself on failure, like Tokio's.
+    #[allow(clippy::result_large_err)]
+    pub fn try_map<U: ?Sized, F>(this: Self, f: F) -> Result<RwLockMappedWriteGuard<'a, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        this.held
+            .try_map(|inner| tokio::sync::RwLockWriteGuard::try_map(inner, f))
+            .map(|held| RwLockMappedWriteGuard { held })
+            .map_err(|held| Self { held })
+    }
+
+    /// Atomically turns the write guard into a read guard, matching
+    /// [`tokio::sync::RwLockWriteGuard::downgrade`]. The lock entity moves
+    /// the hold from the writer to the readers.
+    pub fn downgrade(self) -> RwLockReadGuard<'a, T> {
+        RwLockReadGuard {
+            held: self
+                .held
+                .downgrade(tokio::sync::RwLockWriteGuard::downgrade),
+        }
+    }
+}
+
+impl<T, U: ?Sized> OwnedRwLockReadGuard<T, U> {
+    /// Narrows the guard to a part of the protected value, matching
+    /// [`tokio::sync::OwnedRwLockReadGuard::map`].
+    pub fn map<V: ?Sized, F>(this: Self, f: F) -> OwnedRwLockReadGuard<T, V>
+    where
+        F: FnOnce(&U) -> &V,
+    {
+        OwnedRwLockReadGuard {
+            held: this
+                .held
+                .map(|inner| tokio::sync::RwLockReadGuard::map(inner, f)),
+            lock: this.lock,
+        }
+    }
+
+    /// Fallible variant of [`map`](Self::map), matching
+    /// [`tokio::sync::OwnedRwLockReadGuard::try_map`].
+    // Returns the guard itself on failure, like Tokio's.
+    #[allow(clippy::result_large_err)]
+    pub fn try_map<V: ?Sized, F>(this: Self, f: F) -> Result<OwnedRwLockReadGuard<T, V>, Self>
+    where
+        F: FnOnce(&U) -> Option<&V>,
+    {
+        let Self { held, lock } = this;
+        match held.try_map(|inner| tokio::sync::RwLockReadGuard::try_map(inner, f)) {
+            Ok(held) => Ok(OwnedRwLockReadGuard { held, lock }),
+            Err(held) => Err(Self { held, lock }),
+        }
+    }
+}
+
+impl<T> OwnedRwLockWriteGuard<T> {
+    /// Narrows the guard to a part of the protected value, matching
+    /// [`tokio::sync::OwnedRwLockWriteGuard::map`].
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> OwnedRwLockMappedWriteGuard<T, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        OwnedRwLockMappedWriteGuard {
+            held: this
+                .held
+                .map(|inner| tokio::sync::RwLockWriteGuard::map(inner, f)),
+            _lock: this.lock,
+        }
+    }
+
+    /// Fallible variant of [`map`](Self::map), matching
+    /// [`tokio::sync::OwnedRwLockWriteGuard::try_map`].
+    // Returns the guard itself on failure, like Tokio's.
+    #[allow(clippy::result_large_err)]
+    pub fn try_map<U: ?Sized, F>(
+        this: Self,
+        f: F,
+    ) -> Result<OwnedRwLockMappedWriteGuard<T, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let Self { held, lock } = this;
+        match held.try_map(|inner| tokio::sync::RwLockWriteGuard::try_map(inner, f)) {
+            Ok(held) => Ok(OwnedRwLockMappedWriteGuard { held, _lock: lock }),
+            Err(held) => Err(Self { held, lock }),
+        }
+    }
+
+    /// Atomically turns the write guard into a read guard, matching
+    /// [`tokio::sync::OwnedRwLockWriteGuard::downgrade`].
+    pub fn downgrade(self) -> OwnedRwLockReadGuard<T> {
+        OwnedRwLockReadGuard {
+            held: self
+                .held
+                .downgrade(tokio::sync::RwLockWriteGuard::downgrade),
+            lock: self.lock,
+        }
+    }
+}
 impl<T> SyncRwLock<T> {
     /// Creates a new instrumented sync read-write lock, matching [`parking_lot::RwLock::new`].
-    pub fn new(name: &'static str, value: T) -> Self {
-        let handle = EntityHandle::new(
-            name,
-            LockEntity {
-                kind: LockKind::RwLock,
-            },
-        );
+    pub fn new(name: impl Into<String>, value: T) -> Self {
+        let handle = EntityHandle::new(name, LockEntity::new(LockKind::RwLock));
         Self {
             inner: parking_lot::RwLock::new(value),
             handle,
         }
     }
 
+    /// Renames the lock entity, e.g. to label it once the shard or
+    /// connection it guards is known.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Acquires a shared read guard, equivalent to [`parking_lot::RwLock::read`].
-    pub fn read(&self) -> parking_lot::RwLockReadGuard<'_, T> {
-        if let Some(caller) = current_causal_target_with_task_fallback() {
-            self.handle.link_to(&caller, EdgeKind::Polls);
+    pub fn read(&self) -> SyncRwLockReadGuard<'_, T> {
+        let caller = current_causal_target_with_task_fallback();
+        if let Some(caller) = caller.as_ref() {
+            self.handle.link_to(caller, EdgeKind::Polls);
+        }
+        if let Some(inner) = self.inner.try_read() {
+            let hold = LockHold::uncontended(&self.handle, Access::Shared);
+            return SyncRwLockReadGuard { inner, _hold: hold };
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Shared);
+        let inner = self.block_on_contended(caller.as_ref(), || self.inner.read());
+        SyncRwLockReadGuard {
+            inner,
+            _hold: waiter.acquired(),
         }
-        self.inner.read()
     }
 
     /// Acquires an exclusive write guard, equivalent to [`parking_lot::RwLock::write`].
-    pub fn write(&self) -> parking_lot::RwLockWriteGuard<'_, T> {
-        if let Some(caller) = current_causal_target_with_task_fallback() {
-            self.handle.link_to(&caller, EdgeKind::Polls);
+    pub fn write(&self) -> SyncRwLockWriteGuard<'_, T> {
+        let caller = current_causal_target_with_task_fallback();
+        if let Some(caller) = caller.as_ref() {
+            self.handle.link_to(caller, EdgeKind::Polls);
+        }
+        if let Some(inner) = self.inner.try_write() {
+            let hold = LockHold::uncontended(&self.handle, Access::Exclusive);
+            return SyncRwLockWriteGuard { inner, _hold: hold };
+        }
+        let waiter = LockWaiter::new(&self.handle, Access::Exclusive);
+        let inner = self.block_on_contended(caller.as_ref(), || self.inner.write());
+        SyncRwLockWriteGuard {
+            inner,
+            _hold: waiter.acquired(),
         }
-        self.inner.write()
     }
 
     /// Attempts a non-blocking read lock, matching [`parking_lot::RwLock::try_read`].
-    pub fn try_read(&self) -> Option<parking_lot::RwLockReadGuard<'_, T>> {
+    pub fn try_read(&self) -> Option<SyncRwLockReadGuard<'_, T>> {
         if let Some(caller) = current_causal_target_with_task_fallback() {
             self.handle.link_to(&caller, EdgeKind::Polls);
         }
-        self.inner.try_read()
+        self.inner.try_read().map(|inner| SyncRwLockReadGuard {
+            inner,
+            _hold: LockHold::uncontended(&self.handle, Access::Shared),
+        })
     }
 
     /// Attempts a non-blocking write lock, matching [`parking_lot::RwLock::try_write`].
-    pub fn try_write(&self) -> Option<parking_lot::RwLockWriteGuard<'_, T>> {
+    pub fn try_write(&self) -> Option<SyncRwLockWriteGuard<'_, T>> {
         if let Some(caller) = current_causal_target_with_task_fallback() {
             self.handle.link_to(&caller, EdgeKind::Polls);
         }
-        self.inner.try_write()
+        self.inner.try_write().map(|inner| SyncRwLockWriteGuard {
+            inner,
+            _hold: LockHold::uncontended(&self.handle, Access::Exclusive),
+        })
+    }
+
+    /// Blocks in `acquire` with a `waiting_on` edge from the caller, and adds
+    /// the time blocked to the lock's wait statistics.
+    fn block_on_contended<G>(&self, caller: Option<&EntityRef>, acquire: impl FnOnce() -> G) -> G {
+        let waiting_edge =
+            caller.map(|caller| caller.link_to_owned(&self.handle, EdgeKind::WaitingOn));
+        let waiting_since = Instant::now();
+        let guard = acquire();
+        self.handle.record_wait(caller, waiting_since.elapsed());
+        drop(waiting_edge);
+        guard
     }
 }
 
@@ -193,31 +593,55 @@ impl<T> AsEntityRef for SyncRwLock<T> {
     }
 }
 
-impl<T> Drop for RwLockReadGuard<'_, T> {
-    fn drop(&mut self) {
-        let _ = self.holds_edge.take();
+impl<T: fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
     }
 }
 
-impl<T> Drop for RwLockWriteGuard<'_, T> {
-    fn drop(&mut self) {
-        let _ = self.holds_edge.take();
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for RwLock<T> {
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockMappedWriteGuard<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.inner.fmt(f)
+        self.held.guard().fmt(f)
+    }
+}
+
+impl<T, U: ?Sized + fmt::Debug> fmt::Debug for OwnedRwLockReadGuard<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OwnedRwLockWriteGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
+    }
+}
+
+impl<T, U: ?Sized + fmt::Debug> fmt::Debug for OwnedRwLockMappedWriteGuard<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.held.guard().fmt(f)
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+impl<T: fmt::Debug> fmt::Debug for SyncRwLockReadGuard<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+impl<T: fmt::Debug> fmt::Debug for SyncRwLockWriteGuard<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
     }
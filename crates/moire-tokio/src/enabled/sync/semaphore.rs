@@ -7,7 +7,7 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
 
 use moire_runtime::{
-    AsEntityRef, EdgeHandle, EntityHandle, EntityRef, WeakEntityHandle,
+    AsEntityRef, EdgeHandle, EntityHandle, EntityRef, OperatingOn, WeakEntityHandle,
     current_causal_target_with_task_fallback, instrument_operation_on_with_actor,
 };
 
@@ -64,6 +64,11 @@ impl Semaphore {
         }
     }
 
+    /// Renames the semaphore entity, e.g. once the pool it limits is known.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Returns available permits, matching [`tokio::sync::Semaphore::available_permits`].
     pub fn available_permits(&self) -> usize {
         self.inner.available_permits()
@@ -81,7 +86,10 @@ impl Semaphore {
 
     /// Adds permits, equivalent to [`tokio::sync::Semaphore::add_permits`].
     pub fn add_permits(&self, n: usize) {
-        self.inner.add_permits(n);
+        {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.inner.add_permits(n);
+        }
         let delta = n.min(u32::MAX as usize) as u32;
         let max = self
             .max_permits
@@ -353,7 +361,13 @@ impl<'a> DerefMut for SemaphorePermit<'a> {
 
 impl<'a> Drop for SemaphorePermit<'a> {
     fn drop(&mut self) {
-        let _ = self.inner.take();
+        {
+            let semaphore = self.semaphore_handle.upgrade();
+            let _operating = semaphore
+                .as_ref()
+                .map(|handle| OperatingOn::enter(handle.id()));
+            let _ = self.inner.take();
+        }
         holder_released(&mut self.holder_ref, &self.holder_counts);
         sync_state_from_permit(&self.semaphore_handle, &self.semaphore, &self.max_permits);
     }
@@ -379,7 +393,13 @@ impl DerefMut for OwnedSemaphorePermit {
 
 impl Drop for OwnedSemaphorePermit {
     fn drop(&mut self) {
-        let _ = self.inner.take();
+        {
+            let semaphore = self.semaphore_handle.upgrade();
+            let _operating = semaphore
+                .as_ref()
+                .map(|handle| OperatingOn::enter(handle.id()));
+            let _ = self.inner.take();
+        }
         holder_released(&mut self.holder_ref, &self.holder_counts);
         sync_state_from_permit(&self.semaphore_handle, &self.semaphore, &self.max_permits);
     }
@@ -0,0 +1,128 @@
+// r[impl model.value-inspection]
+//! Opt-in `Debug` renderings of the values behind watch channels, mutexes and
+//! once-cells, for the `*_debug` constructors.
+
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use moire_runtime::{DeferredUpdate, EntityHandle, defer_update};
+use moire_types::EntityBodySlot;
+
+/// Renderings are cut off after this many bytes.
+pub(super) const MAX_RENDER_BYTES: usize = 1024;
+
+/// Minimum time between two renderings of the same value reaching the entity.
+pub(super) const MIN_RENDER_INTERVAL: Duration = Duration::from_millis(100);
+
+const ELLIPSIS: &str = "…";
+
+/// Renders `value` with `Debug`, cut off at [`MAX_RENDER_BYTES`].
+pub(super) fn render_debug<T: fmt::Debug + ?Sized>(value: &T) -> String {
+    let mut out = String::new();
+    // The writer errors out once the cap is hit, which stops formatting early.
+    let _ = write!(CappedWriter(&mut out), "{value:?}");
+    out
+}
+
+/// Records renderings of a value as it is updated, throttled so that the
+/// entity sees at most one every [`MIN_RENDER_INTERVAL`].
+pub(super) struct ValueInspector<T> {
+    render: fn(&T) -> String,
+    slot: Arc<RenderSlot>,
+}
+
+/// The entity field renderings go to, and the one the throttle is holding
+/// back. The runtime flushes a held-back rendering before it next reads the
+/// graph, so a value updated just before a hang still shows up.
+struct RenderSlot {
+    state: parking_lot::Mutex<RenderState>,
+    publish: Box<dyn Fn(String) + Send + Sync>,
+}
+
+struct RenderState {
+    published_at: Instant,
+    pending: Option<String>,
+    /// Queued with the runtime for a flush.
+    deferred: bool,
+}
+
+impl<T> ValueInspector<T> {
+    /// Inspects the value behind `handle`, whose body the constructor already
+    /// filled with [`render_debug`] of the initial value. `field` picks the
+    /// body's `value` field.
+    pub(super) fn debug<S>(
+        handle: &EntityHandle<S>,
+        field: fn(&mut S::Value) -> &mut Option<String>,
+    ) -> Self
+    where
+        T: fmt::Debug,
+        S: EntityBodySlot + Send + Sync + 'static,
+    {
+        let handle = handle.downgrade();
+        Self {
+            render: render_debug::<T>,
+            slot: Arc::new(RenderSlot {
+                state: parking_lot::Mutex::new(RenderState {
+                    published_at: Instant::now(),
+                    pending: None,
+                    deferred: false,
+                }),
+                publish: Box::new(move |rendered| {
+                    let _ = handle.mutate(|body| *field(body) = Some(rendered));
+                }),
+            }),
+        }
+    }
+
+    /// Records `value` after an update. Within [`MIN_RENDER_INTERVAL`] of the
+    /// last rendering that reached the entity, it is held back until the next
+    /// update past the interval or the runtime's next read, whichever is first.
+    pub(super) fn update(&self, value: &T) {
+        let rendered = (self.render)(value);
+        let now = Instant::now();
+        let mut state = self.slot.state.lock();
+        if now.duration_since(state.published_at) >= MIN_RENDER_INTERVAL {
+            state.published_at = now;
+            state.pending = None;
+            // Published under the state lock, so renderings land in order.
+            (self.slot.publish)(rendered);
+            return;
+        }
+        state.pending = Some(rendered);
+        if !std::mem::replace(&mut state.deferred, true) {
+            let slot: Weak<dyn DeferredUpdate> = Arc::downgrade(&self.slot) as _;
+            defer_update(slot);
+        }
+    }
+}
+
+impl DeferredUpdate for RenderSlot {
+    fn flush(&self) {
+        let mut state = self.state.lock();
+        state.deferred = false;
+        if let Some(rendered) = state.pending.take() {
+            state.published_at = Instant::now();
+            (self.publish)(rendered);
+        }
+    }
+}
+
+/// Appends to a string until [`MAX_RENDER_BYTES`], then ends it with `…` and
+/// fails every further write.
+struct CappedWriter<'a>(&'a mut String);
+
+impl fmt::Write for CappedWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let budget = MAX_RENDER_BYTES - ELLIPSIS.len();
+        let room = budget.saturating_sub(self.0.len());
+        if s.len() <= room {
+            self.0.push_str(s);
+            return Ok(());
+        }
+        let cut = s.floor_char_boundary(room);
+        self.0.push_str(&s[..cut]);
+        self.0.push_str(ELLIPSIS);
+        Err(fmt::Error)
+    }
+}
@@ -1,19 +1,23 @@
 // r[impl api.watch]
 
 use moire_runtime::{
-    AsEntityRef, EntityHandle, EntityRef, WeakEntityHandle, instrument_operation_on, new_event,
-    record_event,
+    AsEntityRef, EntityHandle, EntityRef, OperatingOn, WeakEntityHandle, instrument_operation_on,
+    new_event, record_event,
 };
 use moire_types::{EdgeKind, EventKind, EventTarget, WatchRxEntity, WatchTxEntity};
 use std::fmt;
+use std::sync::Arc;
 use tokio::sync::watch;
 
+use super::value_inspect::{ValueInspector, render_debug};
+
 /// Instrumented version of [`tokio::sync::watch::Sender`].
 ///
 /// Records watch state transitions and notifications for diagnostics.
 pub struct Sender<T> {
     inner: tokio::sync::watch::Sender<T>,
     handle: EntityHandle<moire_types::WatchTx>,
+    inspector: Option<Arc<ValueInspector<T>>>,
 }
 
 /// Instrumented version of [`tokio::sync::watch::Receiver`].
@@ -30,6 +34,7 @@ impl<T> Clone for Sender<T> {
         Self {
             inner: self.inner.clone(),
             handle: self.handle.clone(),
+            inspector: self.inspector.clone(),
         }
     }
 }
@@ -49,15 +54,22 @@ impl<T: Clone> Sender<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::WatchTx> {
         &self.handle
     }
+
+    /// Renames the sender entity, which every clone of this sender shares.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Sends a new value, matching [`tokio::sync::watch::Sender::send`].
     ///
     /// Updates receiver metadata and records a channel-sent event.
     pub fn send(&self, value: T) -> Result<(), watch::error::SendError<T>> {
-        let result = self.inner.send(value);
+        let result = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.inner.send(value)
+        };
         if result.is_ok() {
-            let _ = self
-                .handle
-                .mutate(|body| body.last_update_at = Some(moire_types::PTime::now()));
+            self.record_update();
         }
         let event = new_event(
             EventTarget::Entity(self.handle.id().clone()),
@@ -70,10 +82,11 @@ impl<T: Clone> Sender<T> {
     ///
     /// Mirrors [`tokio::sync::watch::Sender::send_replace`].
     pub fn send_replace(&self, value: T) -> T {
-        let old = self.inner.send_replace(value);
-        let _ = self
-            .handle
-            .mutate(|body| body.last_update_at = Some(moire_types::PTime::now()));
+        let old = {
+            let _operating = OperatingOn::enter(self.handle.id());
+            self.inner.send_replace(value)
+        };
+        self.record_update();
         let event = new_event(
             EventTarget::Entity(self.handle.id().clone()),
             EventKind::ChannelSent,
@@ -85,7 +98,7 @@ impl<T: Clone> Sender<T> {
     ///
     /// Returns a linked sender/receiver pair with diagnostic metadata.
     pub fn subscribe(&self) -> Receiver<T> {
-        let handle = EntityHandle::new("watch:rx.subscribe", WatchRxEntity {});
+        let handle = EntityHandle::new("watch:rx.subscribe", WatchRxEntity { cancelled_recvs: 0 });
         self.handle.link_to_handle(&handle, EdgeKind::PairedWith);
         Receiver {
             inner: self.inner.subscribe(),
@@ -93,6 +106,15 @@ impl<T: Clone> Sender<T> {
             tx_handle: self.handle.downgrade(),
         }
     }
+
+    fn record_update(&self) {
+        let _ = self
+            .handle
+            .mutate(|body| body.last_update_at = Some(moire_types::PTime::now()));
+        if let Some(inspector) = &self.inspector {
+            inspector.update(&self.inner.borrow());
+        }
+    }
 }
 
 impl<T: Clone> Receiver<T> {
@@ -100,6 +122,12 @@ impl<T: Clone> Receiver<T> {
     pub fn handle(&self) -> &EntityHandle<moire_types::WatchRx> {
         &self.handle
     }
+
+    /// Renames the receiver entity, which every clone of this receiver shares.
+    pub fn rename(&self, name: impl Into<String>) {
+        let _ = self.handle.rename(name);
+    }
+
     /// Waits for a value change, matching [`tokio::sync::watch::Receiver::changed`].
     ///
     /// Records notification wait timing for diagnostics.
@@ -137,17 +165,39 @@ impl<T: Clone> Receiver<T> {
 
 /// Creates an instrumented watch channel, equivalent to [`tokio::sync::watch::channel`].
 pub fn channel<T: Clone>(name: impl Into<String>, initial: T) -> (Sender<T>, Receiver<T>) {
-    let name = name.into();
+    new_channel(name.into(), initial, None)
+}
+
+/// Like [`channel`], but also records a truncated `Debug` rendering of the
+/// current value on the sender entity as it is updated.
+pub fn channel_debug<T: Clone + fmt::Debug>(
+    name: impl Into<String>,
+    initial: T,
+) -> (Sender<T>, Receiver<T>) {
+    let value = render_debug(&initial);
+    let (mut tx, rx) = new_channel(name.into(), initial, Some(value));
+    tx.inspector = Some(Arc::new(ValueInspector::debug(&tx.handle, |body| {
+        &mut body.value
+    })));
+    (tx, rx)
+}
+
+fn new_channel<T: Clone>(
+    name: String,
+    initial: T,
+    value: Option<String>,
+) -> (Sender<T>, Receiver<T>) {
     let (tx, rx) = tokio::sync::watch::channel(initial);
 
     let tx_handle = EntityHandle::new(
         format!("{name}:tx"),
         WatchTxEntity {
             last_update_at: None,
+            value,
         },
     );
 
-    let rx_handle = EntityHandle::new(format!("{name}:rx"), WatchRxEntity {});
+    let rx_handle = EntityHandle::new(format!("{name}:rx"), WatchRxEntity { cancelled_recvs: 0 });
 
     tx_handle.link_to_handle(&rx_handle, EdgeKind::PairedWith);
 
@@ -155,6 +205,7 @@ pub fn channel<T: Clone>(name: impl Into<String>, initial: T) -> (Sender<T>, Rec
         Sender {
             inner: tx,
             handle: tx_handle.clone(),
+            inspector: None,
         },
         Receiver {
             inner: rx,
@@ -170,6 +221,12 @@ impl<T: Clone> AsEntityRef for Sender<T> {
     }
 }
 
+impl<T: Clone> AsEntityRef for Receiver<T> {
+    fn as_entity_ref(&self) -> EntityRef {
+        self.handle.entity_ref()
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
@@ -25,10 +25,11 @@ use std::cell::RefCell;
 use std::future::{Future, IntoFuture};
 
 use moire_runtime::{
-    EntityHandle, FUTURE_CAUSAL_STACK, InstrumentedFuture, instrument_future,
-    instrument_future_with_handle, register_current_task_scope,
+    AbortNote, EntityHandle, FUTURE_CAUSAL_STACK, InstrumentedFuture,
+    current_causal_target_with_task_fallback, instrument_future, instrument_future_with_handle,
+    register_current_task_scope,
 };
-use moire_types::FutureEntity;
+use moire_types::{FutureEntity, FutureOutcome};
 
 /// Extension trait for attaching a diagnostic name to any future.
 ///
@@ -56,13 +57,17 @@ where
     T: Send + 'static,
     F: Future<Output = T> + Send + 'static,
 {
-    let handle = EntityHandle::new("task.spawn", FutureEntity::default());
+    let handle = EntityHandle::new("task.spawn", spawned_future_entity());
+    let abort_note = AbortNote::default();
     let future_handle = handle.clone();
+    let future_abort_note = abort_note.clone();
     let fut = FUTURE_CAUSAL_STACK.scope(RefCell::new(Vec::new()), async move {
         let _task_scope = register_current_task_scope("spawn");
-        instrument_future_with_handle(future_handle, future, None, None).await
+        instrument_future_with_handle(future_handle, future, None, None)
+            .abort_note(future_abort_note)
+            .await
     });
-    JoinHandle::new(tokio::spawn(fut), handle)
+    JoinHandle::new(tokio::spawn(fut), handle, abort_note)
 }
 
 /// Spawns a blocking task, equivalent to [`tokio::task::spawn_blocking`].
@@ -71,10 +76,22 @@ where
     T: Send + 'static,
     F: FnOnce() -> T + Send + 'static,
 {
-    let handle = EntityHandle::new("task.spawn_blocking", FutureEntity::default());
+    let handle = EntityHandle::new("task.spawn_blocking", spawned_future_entity());
+    let future_handle = handle.clone();
     let inner = tokio::task::spawn_blocking(move || {
         let _task_scope = register_current_task_scope("spawn_blocking");
-        f()
+        let output = f();
+        future_handle.mutate(|future| future.outcome = Some(FutureOutcome::Ok));
+        output
     });
-    JoinHandle::new(inner, handle)
+    JoinHandle::new(inner, handle, AbortNote::default())
+}
+
+/// Body for a task being spawned from the current causal target.
+// r[impl model.future.spawned-by]
+pub(crate) fn spawned_future_entity() -> FutureEntity {
+    FutureEntity {
+        spawned_by: current_causal_target_with_task_fallback().map(|target| target.id().clone()),
+        ..FutureEntity::default()
+    }
 }
@@ -3,21 +3,27 @@ use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-use moire_runtime::EntityHandle;
+use moire_runtime::{AbortNote, EntityHandle};
 use moire_types::FutureEntity;
 
 /// Instrumented equivalent of [`tokio::task::JoinHandle`].
 pub struct JoinHandle<T> {
     inner: tokio::task::JoinHandle<T>,
     handle: EntityHandle<FutureEntity>,
+    abort_note: AbortNote,
 }
 
 impl<T> JoinHandle<T> {
     pub(crate) fn new(
         inner: tokio::task::JoinHandle<T>,
         handle: EntityHandle<FutureEntity>,
+        abort_note: AbortNote,
     ) -> Self {
-        Self { inner, handle }
+        Self {
+            inner,
+            handle,
+            abort_note,
+        }
     }
 
     /// Renames the underlying task entity.
@@ -26,8 +32,9 @@ impl<T> JoinHandle<T> {
         self
     }
 
-    /// Aborts the associated task.
+    /// Aborts the associated task, recording the caller as where it was aborted.
     pub fn abort(&self) {
+        self.abort_note.record();
         self.inner.abort();
     }
 
@@ -3,7 +3,8 @@ use std::fmt;
 use std::future::Future;
 
 use moire_runtime::{
-    EntityHandle, FUTURE_CAUSAL_STACK, instrument_future_with_handle, register_current_task_scope,
+    AbortNote, EntityHandle, FUTURE_CAUSAL_STACK, instrument_future_with_handle,
+    register_current_task_scope,
 };
 use moire_types::FutureEntity;
 
@@ -11,6 +12,8 @@ use moire_types::FutureEntity;
 pub struct JoinSet<T> {
     pub(super) inner: tokio::task::JoinSet<T>,
     pub(super) handle: EntityHandle<FutureEntity>,
+    /// Shared with the tasks spawned since the last `abort_all`.
+    abort_note: AbortNote,
 }
 
 // r[impl api.joinset]
@@ -23,6 +26,7 @@ where
         Self {
             inner: tokio::task::JoinSet::new(),
             handle: EntityHandle::new("joinset", FutureEntity::default()),
+            abort_note: AbortNote::default(),
         }
     }
 
@@ -33,6 +37,7 @@ where
         Self {
             inner: tokio::task::JoinSet::new(),
             handle,
+            abort_note: AbortNote::default(),
         }
     }
 
@@ -42,7 +47,8 @@ where
         F: Future<Output = T> + Send + 'static,
     {
         let joinset_handle = self.handle.clone();
-        let task_handle = EntityHandle::new("joinset.task", FutureEntity::default());
+        let task_handle = EntityHandle::new("joinset.task", super::spawned_future_entity());
+        let abort_note = self.abort_note.clone();
         self.inner.spawn(
             FUTURE_CAUSAL_STACK.scope(RefCell::new(Vec::new()), async move {
                 let _task_scope = register_current_task_scope("joinset.spawn");
@@ -52,6 +58,7 @@ where
                     Some(joinset_handle.entity_ref()),
                     None,
                 )
+                .abort_note(abort_note)
                 .await
             }),
         );
@@ -69,6 +76,7 @@ where
 
     /// Aborts all in-flight tasks, equivalent to [`tokio::task::JoinSet::abort_all`].
     pub fn abort_all(&mut self) {
+        std::mem::take(&mut self.abort_note).record();
         self.inner.abort_all();
     }
 
@@ -92,6 +100,15 @@ where
     }
 }
 
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        // Dropping the set aborts whatever is still running in it.
+        if !self.inner.is_empty() {
+            self.abort_note.record();
+        }
+    }
+}
+
 impl<T> fmt::Debug for JoinSet<T>
 where
     T: Send + 'static,
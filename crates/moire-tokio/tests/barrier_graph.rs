@@ -0,0 +1,57 @@
+//! Checks the round bookkeeping on instrumented barrier entities.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use std::future::Future;
+use std::pin::{Pin, pin};
+use std::task::{Context, Poll, Waker};
+
+use moire_tokio::sync::Barrier;
+use moire_types::EntityBody;
+
+use common::{body, id_of};
+
+/// `(target, waiting, generations)`.
+fn round(body: &EntityBody) -> (u32, u32, u64) {
+    match body {
+        EntityBody::Barrier(barrier) => (barrier.target, barrier.waiting, barrier.generations),
+        _ => panic!("expected a barrier"),
+    }
+}
+
+fn poll_once<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+// r[verify api.barrier]
+// r[verify model.barrier.state]
+#[tokio::test]
+async fn waiting_counts_the_current_round_and_generations_the_finished_ones() {
+    let barrier = Barrier::new("barrier.rounds", 3);
+    let barrier_id = id_of(&barrier);
+    assert_eq!(body(&barrier_id, round), (3, 0, 0));
+
+    let mut first = pin!(barrier.wait());
+    let mut second = pin!(barrier.wait());
+    assert!(poll_once(first.as_mut()).is_pending());
+    assert!(poll_once(second.as_mut()).is_pending());
+    assert_eq!(body(&barrier_id, round), (3, 2, 0));
+
+    // The last arrival leads and retires the whole round before the others
+    // even see they were released.
+    assert!(barrier.wait().await.is_leader());
+    assert_eq!(body(&barrier_id, round), (3, 0, 1));
+    for released in [first, second] {
+        let Poll::Ready(result) = poll_once(released) else {
+            panic!("the round is complete");
+        };
+        assert!(!result.is_leader());
+    }
+    assert_eq!(body(&barrier_id, round), (3, 0, 1));
+
+    let mut next_round = pin!(barrier.wait());
+    assert!(poll_once(next_round.as_mut()).is_pending());
+    assert_eq!(body(&barrier_id, round), (3, 1, 1));
+}
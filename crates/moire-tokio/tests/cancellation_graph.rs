@@ -0,0 +1,140 @@
+//! Checks how cancellation shows up on instrumented token entities.
+
+#![cfg(all(feature = "diagnostics", feature = "tokio-util"))]
+
+mod common;
+
+use std::future::{Future, pending};
+use std::pin::{Pin, pin};
+use std::task::{Context, Poll, Waker};
+
+use moire_tokio::sync::CancellationToken;
+use moire_types::{CancellationTokenEntity, EdgeKind, EntityBody, EntityId};
+
+use common::{body, edges, id_of};
+
+fn is_cancelled(token: &EntityId) -> bool {
+    body(token, |body| match body {
+        EntityBody::CancellationToken(token) => token.cancelled_at.is_some(),
+        _ => panic!("expected a cancellation token"),
+    })
+}
+
+fn token_state<R>(token: &EntityId, read: impl FnOnce(&CancellationTokenEntity) -> R) -> R {
+    body(token, |body| match body {
+        EntityBody::CancellationToken(token) => read(token),
+        _ => panic!("expected a cancellation token"),
+    })
+}
+
+/// `(waiter_count, observed)`.
+fn waiters_and_observed(token: &CancellationTokenEntity) -> (u32, u64) {
+    (token.waiter_count, token.observed)
+}
+
+fn poll_once<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+// r[verify api.cancellation-token]
+#[test]
+fn cancelling_a_parent_marks_every_descendant() {
+    let parent = CancellationToken::new("cancel.parent");
+    let child = parent.child_token("cancel.child");
+    let grandchild = child.child_token("cancel.grandchild");
+    assert_eq!(edges(&id_of(&parent), &id_of(&child)), [EdgeKind::Cancels]);
+
+    parent.cancel();
+
+    assert!(child.is_cancelled() && grandchild.is_cancelled());
+    for token in [&parent, &child, &grandchild] {
+        assert!(is_cancelled(&id_of(token)));
+    }
+}
+
+#[test]
+fn cancellation_reaches_past_a_dropped_token() {
+    let parent = CancellationToken::new("cancel.kept-parent");
+    let child = parent.child_token("cancel.dropped-child");
+    let grandchild = child.child_token("cancel.orphan");
+    let grandchild_id = id_of(&grandchild);
+    drop(child);
+
+    parent.cancel();
+
+    assert!(grandchild.is_cancelled());
+    assert!(is_cancelled(&grandchild_id));
+}
+
+// r[verify model.cancellation-token.state]
+#[test]
+fn drop_guard_cancels_unless_disarmed() {
+    let disarmed = CancellationToken::new("cancel.disarmed");
+    let token = disarmed.clone().drop_guard().disarm();
+    drop(token);
+    assert!(!disarmed.is_cancelled());
+    assert!(!is_cancelled(&id_of(&disarmed)));
+
+    let guarded = CancellationToken::new("cancel.guarded");
+    let child = guarded.child_token("cancel.guarded-child");
+    drop(guarded.clone().drop_guard());
+    assert!(guarded.is_cancelled());
+    assert!(is_cancelled(&id_of(&guarded)) && is_cancelled(&id_of(&child)));
+}
+
+// r[verify model.cancellation-token.state]
+#[test]
+fn waiter_count_follows_pending_cancelled_futures() {
+    let token = CancellationToken::new("cancel.waiters");
+    let token_id = id_of(&token);
+
+    let mut kept = pin!(token.cancelled());
+    let mut abandoned = Box::pin(token.cancelled());
+    assert!(poll_once(kept.as_mut()).is_pending());
+    assert!(poll_once(abandoned.as_mut()).is_pending());
+    assert_eq!(token_state(&token_id, waiters_and_observed), (2, 0));
+
+    // The other branch of a `select!` won.
+    drop(abandoned);
+    assert_eq!(token_state(&token_id, waiters_and_observed), (1, 0));
+
+    token.cancel();
+    assert!(poll_once(kept.as_mut()).is_ready());
+    assert_eq!(token_state(&token_id, waiters_and_observed), (0, 1));
+}
+
+// r[verify model.cancellation-token.state]
+#[test]
+fn observed_counts_calls_that_saw_the_cancellation() {
+    let token = CancellationToken::new("cancel.observed");
+    let token_id = id_of(&token);
+    assert!(!token.is_cancelled());
+    assert_eq!(token_state(&token_id, |token| token.observed), 0);
+
+    token.cancel();
+    for _ in 0..3 {
+        assert!(token.is_cancelled());
+    }
+    assert_eq!(token_state(&token_id, |token| token.observed), 3);
+
+    // Calls between two reads are all counted.
+    assert!(token.is_cancelled());
+    assert!(poll_once(pin!(token.cancelled())).is_ready());
+    assert_eq!(token_state(&token_id, |token| token.observed), 5);
+}
+
+// r[verify api.cancellation-token]
+#[tokio::test]
+async fn run_until_cancelled_stops_at_cancellation() {
+    let token = CancellationToken::new("cancel.run-until");
+    let token_id = id_of(&token);
+    assert_eq!(token.run_until_cancelled(async { 7 }).await, Some(7));
+
+    let mut parked = pin!(token.run_until_cancelled(pending::<u32>()));
+    assert!(poll_once(parked.as_mut()).is_pending());
+    assert_eq!(token_state(&token_id, waiters_and_observed), (1, 0));
+
+    token.cancel();
+    assert_eq!(poll_once(parked.as_mut()), Poll::Ready(None));
+    assert_eq!(token_state(&token_id, waiters_and_observed), (0, 1));
+}
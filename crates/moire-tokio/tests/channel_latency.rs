@@ -0,0 +1,112 @@
+//! Checks the queue latency and `queued_by` stamps the channel senders carry
+//! while extra detail is on for their kind.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use moire_runtime::{SettingsOverride, apply_setting_change};
+use moire_tokio::sync::{broadcast, mpsc};
+use moire_types::{
+    EntityBody, EntityId, KindDetailSetting, QueueLatency, QueuedBy, RuntimeSettingChange,
+};
+
+use common::{body, id_of};
+
+fn detail(kind: &str, enabled: bool) -> RuntimeSettingChange {
+    RuntimeSettingChange::KindDetail(KindDetailSetting {
+        kind: kind.into(),
+        enabled,
+    })
+}
+
+fn set_detail(kind: &str, enabled: bool) {
+    apply_setting_change(&detail(kind, enabled)).unwrap();
+}
+
+fn stamps(tx: &EntityId) -> (Option<QueueLatency>, Vec<QueuedBy>) {
+    body(tx, |body| match body {
+        EntityBody::MpscTx(tx) => (tx.latency.clone(), tx.queued_by.clone()),
+        EntityBody::BroadcastTx(tx) => (tx.latency.clone(), tx.queued_by.clone()),
+        _ => panic!("expected a channel sender"),
+    })
+}
+
+fn latency_count(tx: &EntityId) -> u64 {
+    stamps(tx).0.map_or(0, |latency| latency.count)
+}
+
+fn oldest_queued(tx: &EntityId) -> bool {
+    stamps(tx)
+        .0
+        .is_some_and(|latency| latency.oldest_queued_at.is_some())
+}
+
+fn queued(tx: &EntityId) -> u32 {
+    stamps(tx).1.iter().map(|group| group.count).sum()
+}
+
+// r[verify model.channel.queue-latency]
+#[tokio::test]
+async fn mpsc_stamps_only_messages_sent_while_detail_is_on() {
+    // Put back whatever the toggles below change once the test is done.
+    let _settings = SettingsOverride::apply([detail("mpsc_tx", false)]);
+    let (tx, mut rx) = mpsc::unbounded_channel("latency.mpsc");
+    let tx_id = id_of(&tx);
+
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(stamps(&tx_id), (None, Vec::new()));
+
+    set_detail("mpsc_tx", true);
+    tx.send(3).unwrap();
+    tx.send(4).unwrap();
+    assert!(oldest_queued(&tx_id));
+
+    // The two unstamped messages come out first and count for nothing.
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(rx.recv().await, Some(2));
+    assert_eq!(latency_count(&tx_id), 0);
+    assert!(oldest_queued(&tx_id));
+
+    let mut batch = Vec::new();
+    assert_eq!(rx.recv_many(&mut batch, 8).await, 2);
+    assert_eq!(batch, [3, 4]);
+    assert_eq!(latency_count(&tx_id), 2);
+    assert!(!oldest_queued(&tx_id));
+}
+
+// r[verify model.channel.queued-by]
+#[tokio::test]
+async fn broadcast_records_provenance_only_when_asked_to() {
+    let _settings = SettingsOverride::apply([
+        detail("broadcast_tx", false),
+        RuntimeSettingChange::SendProvenance(false),
+    ]);
+    let (tx, mut first) = broadcast::channel("latency.broadcast", 8);
+    let mut second = tx.subscribe();
+    let tx_id = id_of(&tx);
+
+    set_detail("broadcast_tx", true);
+    tx.send(1).unwrap();
+    assert!(oldest_queued(&tx_id));
+    assert_eq!(queued(&tx_id), 0);
+
+    apply_setting_change(&RuntimeSettingChange::SendProvenance(true)).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(queued(&tx_id), 1);
+    for rx in [&mut first, &mut second] {
+        assert_eq!(rx.recv().await.unwrap(), 1);
+        assert_eq!(rx.recv().await.unwrap(), 2);
+    }
+    // Every receiver's delay counts.
+    assert_eq!(latency_count(&tx_id), 4);
+    assert_eq!(queued(&tx_id), 0);
+
+    set_detail("broadcast_tx", false);
+    tx.send(3).unwrap();
+    assert_eq!(first.recv().await.unwrap(), 3);
+    assert_eq!(second.recv().await.unwrap(), 3);
+    assert_eq!(latency_count(&tx_id), 4);
+    assert!(!oldest_queued(&tx_id));
+}
@@ -0,0 +1,157 @@
+//! Reads back the runtime graph the instrumented backend builds, for tests
+//! that check edges and entity state rather than the API surface.
+
+#![allow(dead_code)]
+
+use moire_runtime::{AsEntityRef, SnapshotSink, pull_changes_since, write_snapshot_to};
+use moire_types::{
+    Change, Edge, EdgeKind, Entity, EntityBody, EntityId, Event, EventKind, EventTarget, SeqNo,
+};
+
+enum Item<'a> {
+    Entity(&'a Entity),
+    Edge(&'a Edge),
+    Event(&'a Event),
+}
+
+struct Collect<F, T> {
+    pick: F,
+    out: Vec<T>,
+}
+
+impl<F, T> SnapshotSink for Collect<F, T>
+where
+    F: FnMut(Item<'_>) -> Option<T>,
+{
+    fn entity(&mut self, entity: &Entity) {
+        self.out.extend((self.pick)(Item::Entity(entity)));
+    }
+
+    fn edge(&mut self, edge: &Edge) {
+        self.out.extend((self.pick)(Item::Edge(edge)));
+    }
+
+    fn event(&mut self, event: &Event) {
+        self.out.extend((self.pick)(Item::Event(event)));
+    }
+}
+
+fn collect<T>(pick: impl FnMut(Item<'_>) -> Option<T>) -> Vec<T> {
+    let mut sink = Collect {
+        pick,
+        out: Vec::new(),
+    };
+    write_snapshot_to(&mut sink);
+    sink.out
+}
+
+pub fn id_of(entity: &impl AsEntityRef) -> EntityId {
+    entity.as_entity_ref().id().clone()
+}
+
+/// Reads the current body of `entity`.
+pub fn body<R>(entity: &EntityId, read: impl FnOnce(&EntityBody) -> R) -> R {
+    let mut read = Some(read);
+    collect(|item| match item {
+        Item::Entity(found) if found.id == *entity && found.removed_at.is_none() => {
+            read.take().map(|read| read(&found.body))
+        }
+        _ => None,
+    })
+    .pop()
+    .unwrap_or_else(|| panic!("no live entity {entity:?}"))
+}
+
+/// Reads the last body published for `entity`. Unlike [`body`], this also
+/// works once the entity was removed and swept from the graph.
+pub fn final_body<R>(entity: &EntityId, read: impl FnOnce(&EntityBody) -> R) -> R {
+    let last = pull_changes_since(SeqNo::ZERO, u32::MAX)
+        .changes
+        .into_iter()
+        .filter_map(|stamped| match stamped.change {
+            Change::UpsertEntity(found) if found.id == *entity => Some(found),
+            _ => None,
+        })
+        .next_back()
+        .unwrap_or_else(|| panic!("no entity {entity:?}"));
+    read(&last.body)
+}
+
+/// The current name of `entity`.
+pub fn name_of(entity: &EntityId) -> String {
+    collect(|item| match item {
+        Item::Entity(found) if found.id == *entity && found.removed_at.is_none() => {
+            Some(found.name.clone())
+        }
+        _ => None,
+    })
+    .pop()
+    .unwrap_or_else(|| panic!("no live entity {entity:?}"))
+}
+
+/// Ids of the live entities named `name`.
+pub fn named(name: &str) -> Vec<EntityId> {
+    collect(|item| match item {
+        Item::Entity(found) if found.name == name && found.removed_at.is_none() => {
+            Some(found.id.clone())
+        }
+        _ => None,
+    })
+}
+
+/// Kinds of the edges from `src` to `dst`.
+pub fn edges(src: &EntityId, dst: &EntityId) -> Vec<EdgeKind> {
+    collect(|item| match item {
+        Item::Edge(edge) if edge.src == *src && edge.dst == *dst => Some(edge.kind),
+        _ => None,
+    })
+}
+
+/// Destinations of the `kind` edges leaving `src`.
+pub fn edges_from(src: &EntityId, kind: EdgeKind) -> Vec<EntityId> {
+    collect(|item| match item {
+        Item::Edge(edge) if edge.src == *src && edge.kind == kind => Some(edge.dst.clone()),
+        _ => None,
+    })
+}
+
+/// How many events on `entity` match `kind`.
+pub fn events_on(entity: &EntityId, kind: impl Fn(&EventKind) -> bool) -> usize {
+    collect(|item| match item {
+        Item::Event(event)
+            if matches!(&event.target, EventTarget::Entity(target) if target == entity)
+                && kind(&event.kind) =>
+        {
+            Some(())
+        }
+        _ => None,
+    })
+    .len()
+}
+
+/// `(by, via)` of the wakes recorded on `entity`, oldest first.
+pub fn wakes_of(entity: &EntityId) -> Vec<(EntityId, Option<EntityId>)> {
+    collect(|item| match item {
+        Item::Event(event) if matches!(&event.target, EventTarget::Entity(target) if target == entity) => {
+            match &event.kind {
+                EventKind::Woken(wake) => Some((wake.by.clone(), wake.via.clone())),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// The entity operations started here are attributed to: the innermost
+/// instrumented future being polled, or the current task's entity.
+pub fn current_target() -> EntityId {
+    moire_runtime::current_causal_target_with_task_fallback()
+        .expect("running inside an instrumented task")
+        .id()
+        .clone()
+}
+
+/// Lock ids on this thread's held-lock stack, innermost last.
+pub fn held_locks() -> Vec<EntityId> {
+    moire_runtime::HELD_MUTEX_STACK.with(|stack| stack.lock().unwrap().clone())
+}
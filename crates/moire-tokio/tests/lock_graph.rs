@@ -0,0 +1,230 @@
+//! Checks that lock guards keep the `held_by` edge and the held-lock stack
+//! pointing at whoever has the guard now, including owned guards that move
+//! into other tasks and threads.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use std::future::Future;
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use moire_tokio::sync::{Mutex, MutexGuard, RwLock};
+use moire_types::{EdgeKind, EntityBody, EntityId, LockEntity};
+
+use common::{body, current_target, edges_from, held_locks, id_of};
+
+fn readers_and_writer(body: &EntityBody) -> (u32, bool) {
+    match body {
+        EntityBody::Lock(lock) => (lock.readers, lock.writer_held),
+        _ => panic!("expected a lock"),
+    }
+}
+
+/// `(readers_waiting, writers_waiting, acquisitions, contended_acquisitions)`.
+fn queue_and_counts(lock: &LockEntity) -> (u32, u32, u64, u64) {
+    (
+        lock.readers_waiting,
+        lock.writers_waiting,
+        lock.acquisitions,
+        lock.contended_acquisitions,
+    )
+}
+
+fn lock_state<R>(lock_id: &EntityId, read: impl FnOnce(&LockEntity) -> R) -> R {
+    body(lock_id, |body| match body {
+        EntityBody::Lock(lock) => read(lock),
+        _ => panic!("expected a lock"),
+    })
+}
+
+fn poll_once<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+// r[verify model.lock.state]
+#[tokio::test]
+async fn writer_queued_behind_readers_is_counted_as_waiting() {
+    let lock = RwLock::new("graph.queued-writer", 0);
+    let lock_id = id_of(&lock);
+
+    let first = lock.read().await;
+    let second = lock.read().await;
+    let mut write = pin!(lock.write());
+    assert!(poll_once(write.as_mut()).is_pending());
+    assert_eq!(body(&lock_id, readers_and_writer), (2, false));
+    assert_eq!(lock_state(&lock_id, queue_and_counts), (0, 1, 2, 0));
+
+    // Tokio's lock is fair: a reader arriving after the writer queues too.
+    let mut late_read = pin!(lock.read());
+    assert!(poll_once(late_read.as_mut()).is_pending());
+    assert_eq!(lock_state(&lock_id, queue_and_counts), (1, 1, 2, 0));
+
+    drop((first, second));
+    let Poll::Ready(mut write) = poll_once(write.as_mut()) else {
+        panic!("the writer is next once the readers leave");
+    };
+    *write += 1;
+    assert_eq!(body(&lock_id, readers_and_writer), (0, true));
+    assert_eq!(lock_state(&lock_id, queue_and_counts), (1, 0, 3, 1));
+
+    drop(write);
+    let Poll::Ready(read) = poll_once(late_read.as_mut()) else {
+        panic!("the reader is next once the writer leaves");
+    };
+    assert_eq!(*read, 1);
+    assert_eq!(body(&lock_id, readers_and_writer), (1, false));
+    assert_eq!(lock_state(&lock_id, queue_and_counts), (0, 0, 4, 2));
+}
+
+// r[verify model.lock.state]
+#[tokio::test]
+async fn cancelled_waiter_leaves_the_queue() {
+    let mutex = Mutex::new("graph.cancelled-waiter", ());
+    let lock_id = id_of(&mutex);
+    let held = mutex.lock().await;
+
+    let mut waiting = Box::pin(mutex.lock());
+    assert!(poll_once(waiting.as_mut()).is_pending());
+    assert_eq!(lock_state(&lock_id, queue_and_counts), (0, 1, 1, 0));
+
+    drop(waiting);
+    assert_eq!(lock_state(&lock_id, queue_and_counts), (0, 0, 1, 0));
+    drop(held);
+    assert_eq!(body(&lock_id, readers_and_writer), (0, false));
+}
+
+// r[verify model.lock.state]
+#[tokio::test]
+async fn longest_hold_is_kept() {
+    let mutex = Mutex::new("graph.max-hold", ());
+    let lock_id = id_of(&mutex);
+    assert_eq!(lock_state(&lock_id, |lock| lock.max_hold_ns), 0);
+
+    let guard = mutex.lock().await;
+    std::thread::sleep(Duration::from_millis(20));
+    drop(guard);
+    let longest = lock_state(&lock_id, |lock| lock.max_hold_ns);
+    assert!(longest >= 20_000_000, "{longest}");
+
+    // A shorter hold afterwards leaves the maximum alone.
+    drop(mutex.lock().await);
+    assert_eq!(lock_state(&lock_id, |lock| lock.max_hold_ns), longest);
+}
+
+// r[verify api.mutex]
+#[tokio::test]
+async fn held_by_follows_an_owned_guard_into_another_task() {
+    let mutex = Arc::new(Mutex::new("graph.owned-mutex", 0));
+    let lock_id = id_of(&*mutex);
+
+    // A finished task's entity goes away with its edges, so each task checks
+    // the edge while it is still running.
+    let (guard, locker) = moire_tokio::spawn({
+        let mutex = Arc::clone(&mutex);
+        let lock_id = lock_id.clone();
+        async move {
+            let guard = mutex.lock_owned().await;
+            let locker = current_target();
+            assert_eq!(
+                edges_from(&lock_id, EdgeKind::HeldBy),
+                std::slice::from_ref(&locker)
+            );
+            (guard, locker)
+        }
+    })
+    .await
+    .unwrap();
+
+    let user = moire_tokio::spawn({
+        let lock_id = lock_id.clone();
+        async move {
+            let mut guard = guard;
+            *guard += 1;
+            let user = current_target();
+            assert_eq!(
+                edges_from(&lock_id, EdgeKind::HeldBy),
+                std::slice::from_ref(&user)
+            );
+            user
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_ne!(user, locker);
+    assert_eq!(edges_from(&lock_id, EdgeKind::HeldBy), []);
+    assert_eq!(*mutex.lock().await, 1);
+}
+
+#[tokio::test]
+async fn mapped_guard_keeps_the_lock_held() {
+    let mutex = Mutex::new("graph.mapped-mutex", (1, 2));
+    let lock_id = id_of(&mutex);
+
+    moire_tokio::spawn(async move {
+        let guard = mutex.lock().await;
+        let mut second = MutexGuard::map(guard, |pair| &mut pair.1);
+        *second += 1;
+        assert_eq!(edges_from(&lock_id, EdgeKind::HeldBy), [current_target()]);
+        assert_eq!(held_locks(), std::slice::from_ref(&lock_id));
+        assert_eq!(body(&lock_id, readers_and_writer), (0, true));
+
+        drop(second);
+        assert_eq!(edges_from(&lock_id, EdgeKind::HeldBy), []);
+        assert_eq!(held_locks(), []);
+        assert_eq!(body(&lock_id, readers_and_writer), (0, false));
+    })
+    .await
+    .unwrap();
+}
+
+// r[verify api.rwlock]
+#[tokio::test]
+async fn downgraded_write_guard_counts_as_a_reader() {
+    let lock = Arc::new(RwLock::new("graph.downgrade", 0));
+    let lock_id = id_of(&*lock);
+
+    let mut write = lock.write_owned().await;
+    *write += 1;
+    assert_eq!(body(&lock_id, readers_and_writer), (0, true));
+
+    let read = write.downgrade();
+    assert_eq!(*read, 1);
+    assert_eq!(body(&lock_id, readers_and_writer), (1, false));
+
+    drop(read);
+    assert_eq!(body(&lock_id, readers_and_writer), (0, false));
+}
+
+#[test]
+fn held_lock_stack_follows_an_owned_guard_across_threads() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mutex = Arc::new(Mutex::new("graph.thread-mutex", 0));
+    let lock_id = id_of(&*mutex);
+
+    let guard = runtime.block_on(mutex.lock_owned());
+    assert_eq!(held_locks(), std::slice::from_ref(&lock_id));
+
+    std::thread::spawn({
+        let lock_id = lock_id.clone();
+        move || {
+            let mut guard = guard;
+            assert_eq!(held_locks(), []);
+            *guard += 1;
+            assert_eq!(held_locks(), [lock_id]);
+            drop(guard);
+            assert_eq!(held_locks(), []);
+        }
+    })
+    .join()
+    .unwrap();
+
+    // The other thread removed this thread's entry when it took the guard over.
+    assert_eq!(held_locks(), []);
+}
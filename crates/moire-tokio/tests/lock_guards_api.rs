@@ -0,0 +1,95 @@
+//! The owned and mapped guard types must exist, with the same
+//! signatures, with and without `diagnostics`. The `held_by` edges and the
+//! held-lock stack these guards maintain are checked in `lock_graph.rs`.
+
+use std::sync::Arc;
+
+use moire_tokio::sync::{
+    MappedMutexGuard, Mutex, MutexGuard, OwnedMutexGuard, OwnedRwLockReadGuard,
+    OwnedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, SyncMutex, SyncMutexGuard,
+    SyncRwLock, SyncRwLockReadGuard, SyncRwLockWriteGuard,
+};
+
+#[tokio::test]
+async fn owned_mutex_guard_moves_into_a_task() {
+    let mutex = Arc::new(Mutex::new("api.owned-mutex", vec![1, 2]));
+    let mut guard: OwnedMutexGuard<Vec<u32>> = mutex.lock_owned().await;
+    assert!(mutex.try_lock_owned().is_err());
+
+    let worker = tokio::spawn(async move {
+        guard.push(3);
+        let mut last = OwnedMutexGuard::map(guard, |values| values.last_mut().unwrap());
+        *last += 1;
+    });
+    worker.await.unwrap();
+
+    assert_eq!(*mutex.lock().await, [1, 2, 4]);
+}
+
+#[tokio::test]
+async fn mutex_guard_map_and_try_map() {
+    let mutex = Mutex::new("api.mapped-mutex", (1u32, None::<u32>));
+    let guard: MutexGuard<'_, _> = mutex.lock().await;
+    let guard = MutexGuard::try_map(guard, |(_, second)| second.as_mut()).unwrap_err();
+    let mut first: MappedMutexGuard<'_, u32> = MutexGuard::map(guard, |(first, _)| first);
+    *first = 7;
+    drop(first);
+
+    assert_eq!(mutex.try_lock().unwrap().0, 7);
+}
+
+#[tokio::test]
+async fn rwlock_write_guard_downgrades() {
+    let lock = RwLock::new("api.downgrade", 1u32);
+    let mut write: RwLockWriteGuard<'_, u32> = lock.write().await;
+    *write = 2;
+    let read: RwLockReadGuard<'_, u32> = write.downgrade();
+    assert!(lock.try_read().is_ok());
+    assert!(lock.try_write().is_err());
+    assert_eq!(*read, 2);
+    drop(read);
+
+    let write = lock.write().await;
+    let mut mapped = RwLockWriteGuard::map(write, |value| value);
+    *mapped += 1;
+    drop(mapped);
+    let read = RwLockReadGuard::map(lock.read().await, |value| value);
+    assert_eq!(*read, 3);
+}
+
+#[tokio::test]
+async fn owned_rwlock_guards_move_into_a_task() {
+    let lock = Arc::new(RwLock::new("api.owned-rwlock", String::from("a")));
+    let mut write: OwnedRwLockWriteGuard<String> = lock.write_owned().await;
+    assert!(lock.try_read_owned().is_err());
+
+    let worker = tokio::spawn(async move {
+        write.push('b');
+        let read = write.downgrade();
+        OwnedRwLockReadGuard::map(read, |value| value.as_str()).len()
+    });
+    assert_eq!(worker.await.unwrap(), 2);
+
+    let read: OwnedRwLockReadGuard<String> = lock.read_owned().await;
+    assert!(lock.try_read_owned().is_ok());
+    assert!(lock.try_write_owned().is_err());
+    assert_eq!(*read, "ab");
+}
+
+#[test]
+fn sync_lock_guards_are_named_types() {
+    let mutex = SyncMutex::new("api.sync-guard", 1u32);
+    let mut guard: SyncMutexGuard<'_, u32> = mutex.lock();
+    *guard += 1;
+    assert!(mutex.try_lock().is_none());
+    drop(guard);
+
+    let lock = SyncRwLock::new("api.sync-rwlock-guards", 1u32);
+    let mut write: SyncRwLockWriteGuard<'_, u32> = lock.write();
+    *write += 1;
+    assert!(lock.try_read().is_none());
+    drop(write);
+    let read: SyncRwLockReadGuard<'_, u32> = lock.read();
+    assert!(lock.try_write().is_none());
+    assert_eq!(*read + *mutex.lock(), 4);
+}
@@ -0,0 +1,110 @@
+//! Compile-and-run coverage of the `sync::mpsc` surface. The same code builds
+//! against the instrumented wrappers (`--features diagnostics`) and the Tokio
+//! pass-throughs, so a method or signature missing from either side fails to
+//! compile. What the wrappers record is checked in `mpsc_graph.rs`.
+
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use moire_tokio::sync::mpsc::{
+    self, OwnedPermit, Permit, Receiver, Sender, UnboundedReceiver, UnboundedSender, WeakSender,
+    WeakUnboundedSender, error,
+};
+
+type TryReserveFn = for<'a> fn(&'a Sender<u32>) -> Result<Permit<'a, u32>, error::TrySendError<()>>;
+type TryReserveOwnedFn =
+    fn(Sender<u32>) -> Result<OwnedPermit<u32>, error::TrySendError<Sender<u32>>>;
+
+#[test]
+fn sync_signatures_match() {
+    let _: fn(&Sender<u32>) -> bool = Sender::is_closed;
+    let _: fn(&Sender<u32>) -> usize = Sender::capacity;
+    let _: fn(&Sender<u32>) -> usize = Sender::max_capacity;
+    let _: fn(&Sender<u32>) -> WeakSender<u32> = Sender::downgrade;
+    let _: fn(&Sender<u32>, &Sender<u32>) -> bool = Sender::same_channel;
+    let _: fn(&Sender<u32>, u32) -> Result<(), error::TrySendError<u32>> = Sender::try_send;
+    let _: fn(&Sender<u32>, u32) -> Result<(), error::SendError<u32>> = Sender::blocking_send;
+    let _: TryReserveFn = Sender::try_reserve;
+    let _: TryReserveOwnedFn = Sender::try_reserve_owned;
+    let _: fn(Permit<'static, u32>, u32) = Permit::send;
+    let _: fn(OwnedPermit<u32>, u32) -> Sender<u32> = OwnedPermit::send;
+    let _: fn(OwnedPermit<u32>) -> Sender<u32> = OwnedPermit::release;
+    let _: fn(&WeakSender<u32>) -> Option<Sender<u32>> = WeakSender::upgrade;
+
+    let _: fn(&mut Receiver<u32>) -> Result<u32, error::TryRecvError> = Receiver::try_recv;
+    let _: fn(&mut Receiver<u32>, &mut Context<'_>) -> Poll<Option<u32>> = Receiver::poll_recv;
+    let _: fn(&mut Receiver<u32>) -> Option<u32> = Receiver::blocking_recv;
+    let _: fn(&mut Receiver<u32>) = Receiver::close;
+
+    let _: fn(&UnboundedSender<u32>, u32) -> Result<(), error::SendError<u32>> =
+        UnboundedSender::send;
+    let _: fn(&UnboundedSender<u32>) -> bool = UnboundedSender::is_closed;
+    let _: fn(&UnboundedSender<u32>) -> WeakUnboundedSender<u32> = UnboundedSender::downgrade;
+    let _: fn(&UnboundedSender<u32>, &UnboundedSender<u32>) -> bool = UnboundedSender::same_channel;
+    let _: fn(&WeakUnboundedSender<u32>) -> Option<UnboundedSender<u32>> =
+        WeakUnboundedSender::upgrade;
+
+    let _: fn(&mut UnboundedReceiver<u32>) -> Result<u32, error::TryRecvError> =
+        UnboundedReceiver::try_recv;
+    let _: fn(&mut UnboundedReceiver<u32>, &mut Context<'_>) -> Poll<Option<u32>> =
+        UnboundedReceiver::poll_recv;
+    let _: fn(&mut UnboundedReceiver<u32>) -> Option<u32> = UnboundedReceiver::blocking_recv;
+    let _: fn(&mut UnboundedReceiver<u32>) = UnboundedReceiver::close;
+}
+
+#[tokio::test]
+async fn bounded_channel_round_trip() {
+    let (tx, mut rx) = mpsc::channel::<u32>("api.bounded", 4);
+    assert_eq!(tx.max_capacity(), 4);
+
+    tx.send(1).await.unwrap();
+    tx.reserve().await.unwrap().send(2);
+    tx.try_reserve().unwrap().send(3);
+    tx.send_timeout(4, Duration::from_secs(1)).await.unwrap();
+    assert_eq!(tx.capacity(), 0);
+    assert!(matches!(
+        tx.send_timeout(5, Duration::from_millis(1)).await,
+        Err(error::SendTimeoutError::Timeout(5))
+    ));
+
+    assert_eq!(rx.try_recv(), Ok(1));
+    let mut buffer = Vec::new();
+    assert_eq!(rx.recv_many(&mut buffer, 8).await, 3);
+    assert_eq!(buffer, [2, 3, 4]);
+    assert_eq!(rx.try_recv(), Err(error::TryRecvError::Empty));
+
+    let weak = tx.downgrade();
+    assert!(weak.upgrade().is_some());
+    drop(tx);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(rx.recv().await, None);
+}
+
+#[tokio::test]
+async fn unbounded_channel_round_trip() {
+    let (tx, mut rx) = mpsc::unbounded_channel::<u32>("api.unbounded");
+    let weak = tx.downgrade();
+    weak.upgrade().unwrap().send(1).unwrap();
+    tx.send(2).unwrap();
+
+    let mut buffer = Vec::new();
+    assert_eq!(rx.recv_many(&mut buffer, 8).await, 2);
+    assert_eq!(buffer, [1, 2]);
+
+    rx.close();
+    tx.closed().await;
+    assert!(tx.is_closed());
+}
+
+#[test]
+fn blocking_variants_work_outside_a_runtime() {
+    let (tx, mut rx) = mpsc::channel::<u32>("api.blocking", 1);
+    let producer = std::thread::spawn(move || {
+        tx.blocking_send(1).unwrap();
+        tx.blocking_send(2).unwrap();
+    });
+    assert_eq!(rx.blocking_recv(), Some(1));
+    assert_eq!(rx.blocking_recv(), Some(2));
+    producer.join().unwrap();
+    assert_eq!(rx.blocking_recv(), None);
+}
@@ -0,0 +1,155 @@
+//! Checks what the instrumented mpsc channels record in the runtime graph.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use std::future::poll_fn;
+use std::pin::pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use moire_tokio::sync::mpsc;
+use moire_types::{EdgeKind, EntityBody, EventKind};
+
+use common::{body, current_target, edges, events_on, id_of};
+
+fn cancelled_sends(body: &EntityBody) -> u32 {
+    match body {
+        EntityBody::MpscTx(tx) => tx.cancelled_sends,
+        _ => panic!("expected an mpsc sender"),
+    }
+}
+
+fn cancelled_recvs(body: &EntityBody) -> u32 {
+    match body {
+        EntityBody::MpscRx(rx) => rx.cancelled_recvs,
+        _ => panic!("expected an mpsc receiver"),
+    }
+}
+
+fn is_cancelled(kind: &EventKind) -> bool {
+    matches!(kind, EventKind::OperationCancelled)
+}
+
+// r[verify model.event.operation-cancelled]
+#[tokio::test]
+async fn timed_out_send_is_recorded_as_cancelled() {
+    let (tx, _rx) = mpsc::channel::<u32>("graph.cancel-send", 1);
+    let tx_id = id_of(&tx);
+    tx.send(1).await.unwrap();
+
+    let full = tokio::time::timeout(Duration::from_millis(10), tx.send(2)).await;
+    assert!(
+        full.is_err(),
+        "the channel is full, so the send must time out"
+    );
+
+    assert_eq!(body(&tx_id, cancelled_sends), 1);
+    assert_eq!(events_on(&tx_id, is_cancelled), 1);
+}
+
+// r[verify model.event.operation-cancelled]
+#[tokio::test]
+async fn recv_dropped_by_select_is_recorded_as_cancelled() {
+    let (_tx, mut rx) = mpsc::channel::<u32>("graph.cancel-recv", 1);
+    let rx_id = id_of(&rx);
+
+    tokio::select! {
+        biased;
+        _ = rx.recv() => panic!("nothing was sent"),
+        _ = std::future::ready(()) => {}
+    }
+
+    assert_eq!(body(&rx_id, cancelled_recvs), 1);
+    assert_eq!(events_on(&rx_id, is_cancelled), 1);
+}
+
+#[tokio::test]
+async fn completed_operations_are_not_cancellations() {
+    let (tx, mut rx) = mpsc::channel::<u32>("graph.no-cancel", 1);
+    let (tx_id, rx_id) = (id_of(&tx), id_of(&rx));
+    tx.send(1).await.unwrap();
+    assert_eq!(rx.recv().await, Some(1));
+
+    // Dropped before its first poll, so it never waited.
+    drop(rx.recv());
+
+    assert_eq!(body(&tx_id, cancelled_sends), 0);
+    assert_eq!(body(&rx_id, cancelled_recvs), 0);
+    assert_eq!(events_on(&rx_id, is_cancelled), 0);
+}
+
+fn queue_len(body: &EntityBody) -> u32 {
+    match body {
+        EntityBody::MpscTx(tx) => tx.queue_len,
+        _ => panic!("expected an mpsc sender"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_calls_work_from_spawn_blocking() {
+    let (tx, mut rx) = mpsc::channel::<u32>("graph.blocking", 1);
+    let tx_id = id_of(&tx);
+
+    let tx = tokio::task::spawn_blocking(move || {
+        tx.blocking_send(1).unwrap();
+        tx
+    })
+    .await
+    .unwrap();
+    assert_eq!(body(&tx_id, queue_len), 1);
+
+    let received = tokio::task::spawn_blocking(move || rx.blocking_recv())
+        .await
+        .unwrap();
+    assert_eq!(received, Some(1));
+    assert_eq!(body(&tx_id, queue_len), 0);
+    drop(tx);
+}
+
+// r[verify api.mpsc]
+#[tokio::test]
+async fn every_send_and_receive_path_moves_queue_len() {
+    let (tx, mut rx) = mpsc::channel::<u32>("graph.queue-len", 8);
+    let tx_id = id_of(&tx);
+
+    tx.reserve().await.unwrap().send(1);
+    tx.clone().reserve_owned().await.unwrap().send(2);
+    tx.try_send(3).unwrap();
+    tx.send_timeout(4, Duration::from_secs(1)).await.unwrap();
+    tx.send(5).await.unwrap();
+    assert_eq!(body(&tx_id, queue_len), 5);
+
+    let mut batch = Vec::new();
+    assert_eq!(rx.recv_many(&mut batch, 3).await, 3);
+    assert_eq!(body(&tx_id, queue_len), 2);
+    assert_eq!(rx.try_recv(), Ok(4));
+    assert_eq!(rx.recv().await, Some(5));
+    assert_eq!(body(&tx_id, queue_len), 0);
+}
+
+#[tokio::test]
+async fn send_on_a_full_channel_waits_on_the_sender() {
+    moire_tokio::spawn(async {
+        let (tx, mut rx) = mpsc::channel::<u32>("graph.full", 1);
+        let tx_id = id_of(&tx);
+        let task = current_target();
+        tx.send(1).await.unwrap();
+
+        let mut send = pin!(tx.send(2));
+        assert!(
+            poll_fn(|cx| Poll::Ready(send.as_mut().poll(cx)))
+                .await
+                .is_pending()
+        );
+        assert_eq!(edges(&task, &tx_id), [EdgeKind::WaitingOn]);
+
+        assert_eq!(rx.recv().await, Some(1));
+        send.await.unwrap();
+        assert_eq!(edges(&task, &tx_id), []);
+        assert_eq!(body(&tx_id, queue_len), 1);
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,60 @@
+//! Every named primitive must take a dynamic name and accept `rename`, with
+//! and without `diagnostics`. `rename_graph.rs` checks the names reach the
+//! entities.
+
+use moire_tokio::sync::{
+    Barrier, Mutex, Notify, OnceCell, RwLock, Semaphore, SyncMutex, SyncRwLock, broadcast, mpsc,
+    oneshot, watch,
+};
+
+#[test]
+fn locks_accept_dynamic_names() {
+    let shard = 3;
+    Mutex::new(format!("api.shard.{shard}"), ()).rename("api.shard.renamed");
+    SyncMutex::new(String::from("api.sync"), ()).rename(format!("api.sync.{shard}"));
+    RwLock::new(format!("api.rwlock.{shard}"), ()).rename("api.rwlock");
+    SyncRwLock::new(format!("api.sync-rwlock.{shard}"), ()).rename("api.sync-rwlock");
+}
+
+#[test]
+fn primitives_accept_renames() {
+    Semaphore::new("api.semaphore", 1).rename("api.semaphore.renamed");
+    Notify::new("api.notify").rename("api.notify.renamed");
+    OnceCell::<u32>::new("api.once-cell").rename("api.once-cell.renamed");
+    Barrier::new("api.barrier", 1).rename("api.barrier.renamed");
+    #[cfg(feature = "tokio-util")]
+    moire_tokio::sync::CancellationToken::new("api.token").rename("api.token.renamed");
+}
+
+#[tokio::test]
+async fn channel_ends_accept_renames() {
+    let (tx, mut rx) = mpsc::channel("api.mpsc", 1);
+    tx.rename("api.mpsc.tx");
+    rx.rename("api.mpsc.rx");
+    tx.send(1).await.unwrap();
+    assert_eq!(rx.recv().await, Some(1));
+
+    let (tx, mut rx) = mpsc::unbounded_channel("api.unbounded");
+    tx.rename("api.unbounded.tx");
+    rx.rename("api.unbounded.rx");
+    tx.send(2).unwrap();
+    assert_eq!(rx.recv().await, Some(2));
+
+    let (tx, rx) = oneshot::channel("api.oneshot");
+    tx.rename("api.oneshot.tx");
+    rx.rename("api.oneshot.rx");
+    tx.send(3).unwrap();
+    assert_eq!(rx.await, Ok(3));
+
+    let (tx, rx) = watch::channel("api.watch", 0);
+    tx.rename("api.watch.tx");
+    rx.rename("api.watch.rx");
+    tx.send(4).unwrap();
+    assert_eq!(*rx.borrow(), 4);
+
+    let (tx, mut rx) = broadcast::channel("api.broadcast", 1);
+    tx.rename("api.broadcast.tx");
+    rx.rename("api.broadcast.rx");
+    tx.send(5).unwrap();
+    assert_eq!(rx.recv().await, Ok(5));
+}
@@ -0,0 +1,108 @@
+//! Checks that names given at construction and through `rename` reach the
+//! entity.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use moire_runtime::AsEntityRef;
+use moire_tokio::sync::{
+    Barrier, Mutex, Notify, OnceCell, RwLock, Semaphore, SyncMutex, SyncRwLock, broadcast, mpsc,
+    oneshot, watch,
+};
+
+use common::{id_of, name_of};
+
+/// Checks the name an entity was created with, renames it through `rename`
+/// and checks the new name.
+fn assert_renames<E: AsEntityRef>(entity: &E, created: &str, rename: impl FnOnce(&E, String)) {
+    let id = id_of(entity);
+    assert_eq!(name_of(&id), created);
+    let renamed = format!("{created}.renamed");
+    rename(entity, renamed.clone());
+    assert_eq!(name_of(&id), renamed);
+}
+
+// r[verify api.rename]
+#[test]
+fn locks_take_dynamic_names_and_renames() {
+    let shard = 3;
+    assert_renames(
+        &Mutex::new(format!("rename.mutex.{shard}"), ()),
+        "rename.mutex.3",
+        |lock, name| lock.rename(name),
+    );
+    assert_renames(
+        &SyncMutex::new(format!("rename.sync-mutex.{shard}"), ()),
+        "rename.sync-mutex.3",
+        |lock, name| lock.rename(name),
+    );
+    assert_renames(
+        &RwLock::new(format!("rename.rwlock.{shard}"), ()),
+        "rename.rwlock.3",
+        |lock, name| lock.rename(name),
+    );
+    assert_renames(
+        &SyncRwLock::new(format!("rename.sync-rwlock.{shard}"), ()),
+        "rename.sync-rwlock.3",
+        |lock, name| lock.rename(name),
+    );
+}
+
+// r[verify api.rename]
+#[test]
+fn primitives_can_be_renamed() {
+    assert_renames(
+        &Semaphore::new("rename.semaphore", 1),
+        "rename.semaphore",
+        |semaphore, name| semaphore.rename(name),
+    );
+    assert_renames(
+        &Notify::new("rename.notify"),
+        "rename.notify",
+        |notify, name| notify.rename(name),
+    );
+    assert_renames(
+        &OnceCell::<u32>::new("rename.once-cell"),
+        "rename.once-cell",
+        |cell, name| cell.rename(name),
+    );
+    assert_renames(
+        &Barrier::new("rename.barrier", 2),
+        "rename.barrier",
+        |barrier, name| barrier.rename(name),
+    );
+}
+
+#[cfg(feature = "tokio-util")]
+#[test]
+fn cancellation_tokens_can_be_renamed() {
+    let token = moire_tokio::sync::CancellationToken::new("rename.token");
+    let child = token.child_token("rename.child");
+    assert_renames(&token, "rename.token", |token, name| token.rename(name));
+    assert_eq!(name_of(&id_of(&child)), "rename.child");
+}
+
+// r[verify api.rename]
+#[tokio::test]
+async fn channel_ends_can_be_renamed() {
+    let (tx, rx) = mpsc::channel::<()>("rename.mpsc", 1);
+    assert_renames(&tx, "rename.mpsc:tx", |tx, name| tx.rename(name));
+    assert_renames(&rx, "rename.mpsc:rx", |rx, name| rx.rename(name));
+
+    let (tx, rx) = mpsc::unbounded_channel::<()>("rename.unbounded");
+    assert_renames(&tx, "rename.unbounded:tx", |tx, name| tx.rename(name));
+    assert_renames(&rx, "rename.unbounded:rx", |rx, name| rx.rename(name));
+
+    let (tx, rx) = oneshot::channel::<()>("rename.oneshot");
+    assert_renames(&tx, "rename.oneshot:tx", |tx, name| tx.rename(name));
+    assert_renames(&rx, "rename.oneshot:rx", |rx, name| rx.rename(name));
+
+    let (tx, rx) = watch::channel("rename.watch", 0);
+    assert_renames(&tx, "rename.watch:tx", |tx, name| tx.rename(name));
+    assert_renames(&rx, "rename.watch:rx", |rx, name| rx.rename(name));
+
+    let (tx, rx) = broadcast::channel::<u32>("rename.broadcast", 1);
+    assert_renames(&tx, "rename.broadcast:tx", |tx, name| tx.rename(name));
+    assert_renames(&rx, "rename.broadcast:rx", |rx, name| rx.rename(name));
+}
@@ -0,0 +1,44 @@
+//! Values through `moire_tokio::stream` come out unchanged whether or not the
+//! streams are instrumented; `stream_graph.rs` covers their edges and stats.
+
+use std::future::poll_fn;
+use std::pin::Pin;
+
+use moire_tokio::stream::{ReceiverStream, Stream, StreamExt as _, UnboundedReceiverStream};
+use moire_tokio::sync::mpsc;
+
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+#[tokio::test]
+async fn named_receiver_stream_yields_every_message() {
+    let (tx, rx) = mpsc::channel::<u32>("api.stream.bounded", 4);
+    let mut stream = Box::pin(ReceiverStream::new(rx).named("api.stream.named"));
+
+    let producer = tokio::spawn(async move {
+        for value in 0..3 {
+            tx.send(value).await.unwrap();
+        }
+    });
+
+    let mut received = Vec::new();
+    while let Some(value) = next(&mut stream).await {
+        received.push(value);
+    }
+    producer.await.unwrap();
+    assert_eq!(received, [0, 1, 2]);
+}
+
+#[tokio::test]
+async fn unbounded_receiver_stream_round_trip() {
+    let (tx, rx) = mpsc::unbounded_channel::<u32>("api.stream.unbounded");
+    let mut stream = UnboundedReceiverStream::from(rx);
+    tx.send(7).unwrap();
+    assert_eq!(next(&mut stream).await, Some(7));
+
+    stream.close();
+    assert!(tx.send(8).is_err());
+    assert_eq!(next(&mut stream).await, None);
+    assert!(stream.into_inner().try_recv().is_err());
+}
@@ -0,0 +1,102 @@
+//! Checks the edges and item stats named streams leave in the runtime graph.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use moire_tokio::stream::{ReceiverStream, Stream, StreamExt as _};
+use moire_tokio::sync::mpsc;
+use moire_types::{EdgeKind, EntityBody};
+
+use common::{body, current_target, edges, id_of};
+
+/// `(items, finished, has last_item_at, gap total, gap max)`.
+fn stats(body: &EntityBody) -> (u64, bool, bool, u64, u64) {
+    match body {
+        EntityBody::Stream(stream) => (
+            stream.items,
+            stream.finished,
+            stream.last_item_at.is_some(),
+            stream.item_gap_total_ns,
+            stream.item_gap_max_ns,
+        ),
+        _ => panic!("expected a stream"),
+    }
+}
+
+async fn poll_once<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+    poll_fn(|cx| Poll::Ready(Pin::new(&mut *stream).poll_next(cx))).await
+}
+
+// r[verify api.stream]
+#[tokio::test]
+async fn pending_poll_links_the_task_through_the_stream_to_the_receiver() {
+    moire_tokio::spawn(async {
+        let (tx, rx) = mpsc::channel::<u32>("graph.stream.rx", 1);
+        let rx_id = id_of(&rx);
+        let mut stream = ReceiverStream::new(rx).named("graph.stream.pending");
+        let stream_id = id_of(&stream);
+        let task = current_target();
+
+        assert!(poll_once(&mut stream).await.is_pending());
+        assert_eq!(edges(&task, &stream_id), [EdgeKind::WaitingOn]);
+        assert_eq!(edges(&stream_id, &rx_id), [EdgeKind::WaitingOn]);
+
+        tx.send(7).await.unwrap();
+        assert_eq!(poll_once(&mut stream).await, Poll::Ready(Some(7)));
+        assert_eq!(edges(&task, &stream_id), []);
+        assert_eq!(edges(&stream_id, &rx_id), []);
+    })
+    .await
+    .unwrap();
+}
+
+// r[verify model.stream.state]
+#[tokio::test]
+async fn item_stats_count_items_and_the_gaps_between_them() {
+    let (tx, rx) = mpsc::channel::<u32>("graph.stream.gaps-rx", 4);
+    let mut stream = ReceiverStream::new(rx).named("graph.stream.gaps");
+    let stream_id = id_of(&stream);
+
+    let producer = tokio::spawn(async move {
+        for value in 0..3 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            tx.send(value).await.unwrap();
+        }
+    });
+    while poll_fn(|cx| Pin::new(&mut stream).poll_next(cx))
+        .await
+        .is_some()
+    {}
+    producer.await.unwrap();
+
+    let (items, finished, last_item, gap_total, gap_max) = body(&stream_id, stats);
+    assert_eq!((items, finished, last_item), (3, true, true));
+    assert!(gap_max >= 15_000_000, "longest gap {gap_max}ns");
+    assert!(gap_max <= gap_total);
+}
+
+// r[verify model.stream.state]
+#[tokio::test]
+async fn item_counts_are_current_while_the_stream_is_pending() {
+    let (tx, rx) = mpsc::channel::<u32>("graph.stream.burst-rx", 8);
+    let mut stream = ReceiverStream::new(rx).named("graph.stream.burst");
+    let stream_id = id_of(&stream);
+
+    // A burst well inside the publish interval, then a stall.
+    for value in 0..5 {
+        tx.send(value).await.unwrap();
+    }
+    for value in 0..5 {
+        assert_eq!(poll_once(&mut stream).await, Poll::Ready(Some(value)));
+    }
+    assert!(poll_once(&mut stream).await.is_pending());
+
+    let (items, finished, last_item, _, _) = body(&stream_id, stats);
+    assert_eq!((items, finished, last_item), (5, false, true));
+}
@@ -0,0 +1,138 @@
+//! Checks the terminal outcome recorded on futures that end without
+//! completing, or complete with an error.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use std::future::{Future, pending, poll_fn};
+use std::pin::pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use moire_tokio::__internal::note_result;
+use moire_tokio::task::{FutureExt as _, JoinSet};
+use moire_types::{EntityBody, EntityId, FutureOutcome};
+
+use common::{body, current_target, final_body, named};
+
+/// `(outcome, has outcome_backtrace)`.
+fn outcome(body: &EntityBody) -> (Option<FutureOutcome>, bool) {
+    match body {
+        EntityBody::Future(future) => (future.outcome, future.outcome_backtrace.is_some()),
+        _ => panic!("expected a future"),
+    }
+}
+
+fn only_named(name: &str) -> EntityId {
+    let mut ids = named(name);
+    assert_eq!(ids.len(), 1, "entities named {name}");
+    ids.pop().unwrap()
+}
+
+/// Waits for the runtime to drop a task it was told to abort.
+async fn settled_outcome(task: &EntityId) -> (Option<FutureOutcome>, bool) {
+    for _ in 0..1000 {
+        let recorded = final_body(task, outcome);
+        if recorded.0.is_some() {
+            return recorded;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    panic!("no outcome recorded on {task:?}");
+}
+
+// r[verify model.future.outcome]
+#[tokio::test]
+async fn aborting_a_join_handle_records_aborted() {
+    let mut handle = moire_tokio::spawn(pending::<()>());
+    let task = handle.entity_handle().id().clone();
+    tokio::task::yield_now().await;
+
+    handle.abort();
+    assert!((&mut handle).await.unwrap_err().is_cancelled());
+
+    assert_eq!(body(&task, outcome), (Some(FutureOutcome::Aborted), true));
+}
+
+// r[verify model.future.outcome]
+#[tokio::test]
+async fn dropping_a_pending_named_future_records_cancelled_by_drop() {
+    let mut future = Box::pin(pending::<()>().named("outcome.dropped"));
+    let id = only_named("outcome.dropped");
+    assert!(
+        poll_fn(|cx| Poll::Ready(future.as_mut().poll(cx)))
+            .await
+            .is_pending()
+    );
+
+    drop(future);
+
+    assert_eq!(
+        final_body(&id, outcome),
+        (Some(FutureOutcome::CancelledByDrop), true)
+    );
+}
+
+// r[verify model.future.outcome]
+#[tokio::test]
+async fn an_err_result_records_error() {
+    // What `#[moire::instrument]` expands to for a function returning `Result`.
+    let future = async {
+        let result: Result<(), &str> = Err("refused");
+        note_result(&result);
+        result
+    }
+    .named("outcome.error");
+    let id = only_named("outcome.error");
+
+    assert_eq!(future.await, Err("refused"));
+
+    // The first outcome sticks: completing afterwards does not make it `ok`.
+    assert_eq!(
+        final_body(&id, outcome),
+        (Some(FutureOutcome::Error), false)
+    );
+}
+
+// r[verify model.future.outcome]
+#[tokio::test]
+async fn shutting_down_a_join_set_records_aborted() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let spawn_parked = |set: &mut JoinSet<()>| {
+        let tx = tx.clone();
+        set.spawn(async move {
+            tx.send(current_target()).unwrap();
+            pending::<()>().await
+        });
+    };
+
+    let mut aborted = JoinSet::named("outcome.abort-all");
+    spawn_parked(&mut aborted);
+    spawn_parked(&mut aborted);
+    let dropped_with_set = {
+        let mut set = JoinSet::named("outcome.dropped-set");
+        spawn_parked(&mut set);
+        let mut started = Vec::new();
+        for _ in 0..3 {
+            started.push(rx.recv().await.unwrap());
+        }
+        drop(set);
+        started
+    };
+
+    aborted.abort_all();
+    let mut joined = 0;
+    while let Some(result) = pin!(aborted.join_next()).await {
+        assert!(result.unwrap_err().is_cancelled());
+        joined += 1;
+    }
+    assert_eq!(joined, 2);
+
+    for task in &dropped_with_set {
+        assert_eq!(
+            settled_outcome(task).await,
+            (Some(FutureOutcome::Aborted), true)
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Checks the `Debug` renderings the `*_debug` constructors record on their
+//! entities.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use moire_runtime::{current_cursor, pull_changes_since};
+use moire_tokio::sync::{Mutex, OnceCell, SyncMutex, watch};
+use moire_types::{Change, EntityBody, EntityId};
+
+use common::{body, id_of};
+
+/// Reading the graph flushes every held-back rendering, so tests that check
+/// the throttle must not run alongside tests that read.
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn value_of(body: &EntityBody) -> Option<String> {
+    match body {
+        EntityBody::Lock(lock) => lock.value.clone(),
+        EntityBody::WatchTx(tx) => tx.value.clone(),
+        EntityBody::OnceCell(cell) => cell.value.clone(),
+        _ => panic!("expected a lock, watch sender or once-cell"),
+    }
+}
+
+fn value(entity: &EntityId) -> Option<String> {
+    body(entity, value_of)
+}
+
+// r[verify model.value-inspection]
+#[tokio::test]
+async fn debug_constructors_record_the_value() {
+    let _serial = SERIAL.lock().await;
+    let mutex = Mutex::new_debug("inspect.mutex", vec![1, 2]);
+    let sync_mutex = SyncMutex::new_debug("inspect.sync-mutex", "sync");
+    let (tx, _rx) = watch::channel_debug("inspect.watch", 3u32);
+    let cell = OnceCell::new_debug("inspect.once-cell");
+    let plain = Mutex::new("inspect.plain", 0u32);
+
+    assert_eq!(value(&id_of(&mutex)).as_deref(), Some("[1, 2]"));
+    assert_eq!(value(&id_of(&sync_mutex)).as_deref(), Some("\"sync\""));
+    assert_eq!(value(&id_of(&tx)).as_deref(), Some("3"));
+    assert_eq!(value(&id_of(&cell)), None);
+    assert_eq!(value(&id_of(&plain)), None);
+
+    cell.set(Some('x')).unwrap();
+    assert_eq!(value(&id_of(&cell)).as_deref(), Some("Some('x')"));
+}
+
+// r[verify model.value-inspection]
+#[tokio::test]
+async fn renderings_are_cut_off_at_the_byte_cap() {
+    let _serial = SERIAL.lock().await;
+    let mutex = Mutex::new_debug("inspect.long", "é".repeat(2000));
+
+    let rendered = value(&id_of(&mutex)).unwrap();
+    assert!(rendered.len() <= 1024, "{} bytes", rendered.len());
+    assert!(rendered.starts_with("\"éé"));
+    assert!(rendered.ends_with("é…"));
+}
+
+// r[verify model.value-inspection]
+#[tokio::test]
+async fn updates_within_the_interval_wait_for_the_next_read() {
+    let _serial = SERIAL.lock().await;
+    let mutex = Mutex::new_debug("inspect.throttle", 0u32);
+    let (tx, _rx) = watch::channel_debug("inspect.throttle-watch", 0u32);
+    let (mutex_id, tx_id) = (id_of(&mutex), id_of(&tx));
+    let from = current_cursor().next_seq_no;
+
+    // Well inside the interval that started when they were created.
+    for n in 1..=5 {
+        *mutex.lock().await += 1;
+        tx.send(n).unwrap();
+    }
+
+    // Nothing touches either after the burst: the read alone brings the
+    // entities up to date, and the renderings in between never got there.
+    let seen = |id: &EntityId| {
+        let mut values = Vec::new();
+        for stamped in pull_changes_since(from, u32::MAX).changes {
+            if let Change::UpsertEntity(entity) = stamped.change
+                && entity.id == *id
+            {
+                let value = value_of(&entity.body);
+                if values.last() != Some(&value) {
+                    values.push(value);
+                }
+            }
+        }
+        values
+    };
+    let expected = [Some("0".to_owned()), Some("5".to_owned())];
+    assert_eq!(seen(&mutex_id), expected);
+    assert_eq!(seen(&tx_id), expected);
+}
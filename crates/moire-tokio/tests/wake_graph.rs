@@ -0,0 +1,91 @@
+//! Checks that wakes are attributed to the resource whose send, notify or
+//! release woke the waiting task.
+
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use std::future::Future;
+use std::sync::Arc;
+
+use moire_tokio::sync::{Mutex, Notify, mpsc};
+use moire_types::EntityId;
+
+use common::{current_target, id_of, wakes_of};
+
+/// Spawns a task that signals it is about to wait, waits on `wait`, and then
+/// returns the wakes recorded on its own entity. On the current-thread
+/// runtime the task is parked inside `wait` by the time the signal arrives.
+async fn wakes_while_waiting<F>(
+    wait: impl FnOnce() -> F + Send + 'static,
+) -> (
+    tokio::sync::oneshot::Receiver<()>,
+    moire_tokio::task::JoinHandle<Vec<(EntityId, Option<EntityId>)>>,
+)
+where
+    F: Future<Output = ()> + Send,
+{
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    let waiter = moire_tokio::spawn(async move {
+        let me = current_target();
+        let _ = ready_tx.send(());
+        wait().await;
+        wakes_of(&me)
+    });
+    (ready_rx, waiter)
+}
+
+// r[verify model.event.woken]
+#[tokio::test]
+async fn send_wakes_are_attributed_to_the_sender() {
+    let (tx, mut rx) = mpsc::channel::<u32>("wake.mpsc", 1);
+    let (tx_id, rx_id) = (id_of(&tx), id_of(&rx));
+    let (ready, waiter) = wakes_while_waiting(move || async move {
+        assert_eq!(rx.recv().await, Some(1));
+    })
+    .await;
+    ready.await.unwrap();
+
+    tx.send(1).await.unwrap();
+
+    assert_eq!(waiter.await.unwrap(), [(tx_id, Some(rx_id))]);
+}
+
+// r[verify model.event.woken]
+#[tokio::test]
+async fn notify_wakes_are_attributed_to_the_notify() {
+    let notify = Arc::new(Notify::new("wake.notify"));
+    let notify_id = id_of(&*notify);
+    let (ready, waiter) = wakes_while_waiting({
+        let notify = Arc::clone(&notify);
+        move || async move { notify.notified().await }
+    })
+    .await;
+    ready.await.unwrap();
+
+    notify.notify_one();
+
+    assert_eq!(
+        waiter.await.unwrap(),
+        [(notify_id.clone(), Some(notify_id))]
+    );
+}
+
+// r[verify model.event.woken]
+#[tokio::test]
+async fn guard_releases_wake_as_the_lock() {
+    let mutex = Arc::new(Mutex::new("wake.mutex", 0u32));
+    let lock_id = id_of(&*mutex);
+    let guard = mutex.lock().await;
+    let (ready, waiter) = wakes_while_waiting({
+        let mutex = Arc::clone(&mutex);
+        move || async move { *mutex.lock().await += 1 }
+    })
+    .await;
+    ready.await.unwrap();
+
+    drop(guard);
+
+    assert_eq!(waiter.await.unwrap(), [(lock_id.clone(), Some(lock_id))]);
+    assert_eq!(*mutex.lock().await, 1);
+}
@@ -1,4 +1,4 @@
-use crate::{ConnectionId, CutId, ProcessId, SessionId};
+use crate::{ConnectionId, CutId, EntityId, ProcessId, SessionId};
 use facet::Facet;
 use moire_trace_types::{BacktraceId, FrameId, RelPc};
 
@@ -15,6 +15,37 @@ pub struct ConnectedProcessInfo {
     pub process_id: ProcessId,
     pub process_name: String,
     pub pid: u32,
+    #[facet(skip_unless_truthy)]
+    pub liveness: Option<RuntimeLiveness>,
+    #[facet(skip_unless_truthy)]
+    pub redaction: Option<RedactionSummary>,
+    /// Settings the process rejected, as reported in its handshake.
+    #[facet(default, skip_unless_truthy)]
+    pub config_errors: Vec<String>,
+}
+
+/// Redaction rules a process applied before sending anything, as reported in
+/// its handshake. Literal scrubbers are listed as `literal`, never by value.
+#[derive(Facet, Clone, Debug, Default)]
+pub struct RedactionSummary {
+    /// Environment variable name patterns that are sent; everything else is redacted.
+    /// Empty means every variable not denied is sent.
+    pub allow_env: Vec<String>,
+    /// Key patterns whose values are redacted in env, args and JSON payloads.
+    pub deny_keys: Vec<String>,
+    /// Value scrubbers, by name.
+    pub scrubbers: Vec<String>,
+}
+
+/// Runtime liveness derived from a process's heartbeats.
+#[derive(Facet, Clone)]
+pub struct RuntimeLiveness {
+    /// Heartbeats keep arriving but the runtime stopped running liveness probes.
+    pub runtime_unresponsive: bool,
+    /// Milliseconds since the runtime last ran a liveness probe.
+    pub runtime_stalled_ms: u64,
+    /// Futures the runtime polled most recently, newest first.
+    pub last_active_entities: Vec<EntityId>,
 }
 
 #[derive(Facet)]
@@ -141,6 +172,75 @@ pub struct TimedOutProcess {
     pub process_id: ProcessId,
     pub process_name: String,
     pub pid: u32,
+    #[facet(skip_unless_truthy)]
+    pub liveness: Option<RuntimeLiveness>,
+}
+
+/// One live setting change for an instrumented process.
+#[derive(Facet, Clone, Debug)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+pub enum RuntimeSettingChange {
+    /// How often the process pushes change batches, in milliseconds.
+    PushIntervalMs(u64),
+    /// Whether new entities, edges and events capture a backtrace.
+    BacktraceCapture(bool),
+    /// Capacity of the process's event ring buffer.
+    MaxEvents(u32),
+    /// Turns extra detail on or off for one entity kind.
+    KindDetail(KindDetailSetting),
+    /// Polls slower than this record a `long_poll` event, in milliseconds.
+    /// Zero turns long-poll events off.
+    LongPollThresholdMs(u64),
+    /// Whether stamped channel sends also record their producer and send
+    /// site, for `queued_by`.
+    SendProvenance(bool),
+}
+
+#[derive(Facet, Clone, Debug)]
+pub struct KindDetailSetting {
+    /// Entity kind name, e.g. `mpsc_tx`.
+    pub kind: String,
+    pub enabled: bool,
+}
+
+/// The live settings of an instrumented process.
+#[derive(Facet, Clone, Debug)]
+pub struct RuntimeSettings {
+    pub push_interval_ms: u64,
+    pub backtrace_capture: bool,
+    pub max_events: u32,
+    pub detailed_kinds: Vec<String>,
+    pub long_poll_threshold_ms: u64,
+    pub send_provenance: bool,
+}
+
+/// Body of `POST /api/control`.
+#[derive(Facet)]
+pub struct RuntimeControlRequest {
+    /// Only send the changes to this process. All connected processes otherwise.
+    #[facet(skip_unless_truthy)]
+    pub process_id: Option<ProcessId>,
+    pub changes: Vec<RuntimeSettingChange>,
+}
+
+#[derive(Facet)]
+pub struct RuntimeControlResponse {
+    pub control_id: i64,
+    pub acks: Vec<ProcessControlAck>,
+    pub timed_out_processes: Vec<TimedOutProcess>,
+}
+
+#[derive(Facet)]
+pub struct ProcessControlAck {
+    pub process_id: ProcessId,
+    pub process_name: String,
+    pub pid: u32,
+    /// Set when the process rejected at least one change.
+    #[facet(skip_unless_truthy)]
+    pub error: Option<String>,
+    /// Settings in effect after the changes were applied.
+    pub settings: RuntimeSettings,
 }
 
 #[derive(Facet)]
@@ -281,3 +381,16 @@ pub struct RecordingImportBody {
     pub session: RecordingSessionInfo,
     pub frames: Vec<RecordingImportFrame>,
 }
+
+/// API response for importing a wire trace recorded with `MOIRE_DASHBOARD=file:...`.
+#[derive(Facet)]
+pub struct WireTraceImportResponse {
+    pub conn_id: ConnectionId,
+    pub process_id: ProcessId,
+    pub process_name: String,
+    pub pid: u32,
+    /// The trace ended partway through a frame. Everything before that frame
+    /// was imported.
+    #[facet(skip_unless_truthy)]
+    pub truncated: bool,
+}
@@ -56,6 +56,11 @@ pub enum EdgeKind {
     ///
     /// Example: semaphore is held_by the holder of an acquired permit.
     HeldBy,
+
+    /// Cancellation relationship (parent token -> child token it cancels).
+    ///
+    /// Example: a cancellation token cancels each token made with `child_token`.
+    Cancels,
 }
 
 crate::impl_sqlite_json!(EdgeKind);
@@ -65,4 +70,5 @@ crate::declare_edge_kind_slots!(
     WaitingOnEdgeKindSlot::WaitingOn,
     PairedWithEdgeKindSlot::PairedWith,
     HeldByEdgeKindSlot::HeldBy,
+    CancelsEdgeKindSlot::Cancels,
 );
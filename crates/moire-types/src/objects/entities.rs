@@ -25,6 +25,96 @@ pub struct Entity {
 
     /// More specific info about the entity (depending on its kind)
     pub body: EntityBody,
+
+    /// Time operations spent waiting on this entity. Unset until one waits.
+    #[facet(skip_unless_truthy)]
+    pub wait_stats: Option<WaitStats>,
+
+    /// The same waits broken down by the actor that waited, busiest first.
+    /// At most [`WaiterWaitStats::MAX_WAITERS`] actors are kept.
+    #[facet(default, skip_unless_truthy)]
+    pub waiter_wait_stats: Vec<WaiterWaitStats>,
+}
+
+/// Completed waits on a resource: each one runs from the operation's first
+/// `Pending` until it completes or is dropped.
+// r[impl model.entity.wait-stats]
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WaitStats {
+    pub count: u64,
+    pub total_ns: u64,
+    pub max_ns: u64,
+    /// Log-scale histogram: `buckets[0]` counts waits under 1µs, `buckets[i]`
+    /// waits of `2^(i-1)` to `2^i` µs. The last of [`WaitStats::BUCKETS`]
+    /// also counts anything longer. Trailing empty buckets are left out.
+    pub buckets: Vec<u64>,
+}
+
+impl WaitStats {
+    /// Number of histogram buckets. The last one starts at about 4.5 minutes.
+    pub const BUCKETS: usize = 30;
+
+    pub fn record(&mut self, wait_ns: u64) {
+        self.count = self.count.saturating_add(1);
+        self.total_ns = self.total_ns.saturating_add(wait_ns);
+        self.max_ns = self.max_ns.max(wait_ns);
+        let bucket = Self::bucket_for(wait_ns);
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] = self.buckets[bucket].saturating_add(1);
+    }
+
+    /// Histogram bucket a wait of `wait_ns` falls into.
+    pub fn bucket_for(wait_ns: u64) -> usize {
+        let wait_us = wait_ns / 1_000;
+        let bucket = (u64::BITS - wait_us.leading_zeros()) as usize;
+        bucket.min(Self::BUCKETS - 1)
+    }
+}
+
+/// Completed waits on a resource by one actor: the edge `waiter → resource`
+/// while it was `waiting_on`.
+// r[impl model.entity.wait-stats]
+#[derive(Facet, Clone, Debug, PartialEq, Eq)]
+pub struct WaiterWaitStats {
+    pub waiter: EntityId,
+    pub stats: WaitStats,
+}
+
+impl WaiterWaitStats {
+    /// Actors kept per resource. Once full, a new actor replaces the one with
+    /// the least total wait, if its own wait is longer.
+    pub const MAX_WAITERS: usize = 16;
+
+    /// Adds a wait by `waiter` to `list`, keeping it sorted by descending
+    /// `total_ns` and at most [`WaiterWaitStats::MAX_WAITERS`] long.
+    pub fn record(list: &mut Vec<WaiterWaitStats>, waiter: &EntityId, wait_ns: u64) {
+        let mut index = match list.iter().position(|entry| &entry.waiter == waiter) {
+            Some(index) => index,
+            None => {
+                if list.len() >= Self::MAX_WAITERS {
+                    if list
+                        .last()
+                        .is_some_and(|least| least.stats.total_ns >= wait_ns)
+                    {
+                        return;
+                    }
+                    list.pop();
+                }
+                list.push(WaiterWaitStats {
+                    waiter: waiter.clone(),
+                    stats: WaitStats::default(),
+                });
+                list.len() - 1
+            }
+        };
+        list[index].stats.record(wait_ns);
+        while index > 0 && list[index - 1].stats.total_ns < list[index].stats.total_ns {
+            list.swap(index - 1, index);
+            index -= 1;
+        }
+    }
 }
 
 impl Entity {
@@ -37,6 +127,8 @@ impl Entity {
             backtrace,
             name: name.into(),
             body,
+            wait_stats: None,
+            waiter_wait_stats: Vec::new(),
         }
     }
 }
@@ -58,6 +150,9 @@ crate::define_entity_body! {
         Semaphore(SemaphoreEntity),
         Notify(NotifyEntity),
         OnceCell(OnceCellEntity),
+        Barrier(BarrierEntity),
+        CancellationToken(CancellationTokenEntity),
+        Stream(StreamEntity),
 
         // System and I/O boundaries
         Command(CommandEntity),
@@ -88,12 +183,81 @@ pub struct FutureEntity {
     /// and the callsite is shown instead.
     #[facet(skip_unless_truthy)]
     pub skip_entry_frames: Option<u8>,
+    /// How the future ended. Unset while it is still running.
+    #[facet(skip_unless_truthy)]
+    pub outcome: Option<FutureOutcome>,
+    /// Where the future was aborted or dropped, for those outcomes.
+    #[facet(skip_unless_truthy)]
+    pub outcome_backtrace: Option<BacktraceId>,
+    /// Entity that was being polled when this task was spawned. Kept after the
+    /// spawner is gone, so task trees survive their parents.
+    #[facet(skip_unless_truthy)]
+    pub spawned_by: Option<EntityId>,
+    /// Wall time spent inside `poll` so far, in nanoseconds. Unset until the
+    /// first poll.
+    #[facet(skip_unless_truthy)]
+    pub poll_total_ns: Option<u64>,
+    /// Longest single `poll`, in nanoseconds.
+    #[facet(skip_unless_truthy)]
+    pub poll_max_ns: Option<u64>,
+}
+
+#[derive(Facet, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+pub enum FutureOutcome {
+    /// The future completed.
+    Ok,
+    /// The future completed with an `Err`.
+    Error,
+    /// The future was dropped before it completed.
+    CancelledByDrop,
+    /// The task was aborted through its `JoinHandle` or `JoinSet`.
+    Aborted,
+    /// The future panicked while being polled.
+    Panicked,
 }
 
+// r[impl model.lock.state]
 #[derive(Facet)]
 pub struct LockEntity {
     /// Kind of lock primitive.
     pub kind: LockKind,
+    /// Read guards currently held. Always zero for mutexes.
+    pub readers: u32,
+    /// Whether an exclusive guard (a mutex guard or a write guard) is held.
+    pub writer_held: bool,
+    /// Acquisitions waiting for a read guard.
+    pub readers_waiting: u32,
+    /// Acquisitions waiting for an exclusive guard.
+    pub writers_waiting: u32,
+    /// Guards handed out so far.
+    pub acquisitions: u64,
+    /// Acquisitions that found the lock taken and had to wait.
+    pub contended_acquisitions: u64,
+    /// Longest time any guard was held, in nanoseconds.
+    pub max_hold_ns: u64,
+    /// Debug rendering of the protected value, for locks created with
+    /// `new_debug`.
+    #[facet(skip_unless_truthy)]
+    pub value: Option<String>,
+}
+
+impl LockEntity {
+    /// A lock nobody has acquired yet.
+    pub fn new(kind: LockKind) -> Self {
+        Self {
+            kind,
+            readers: 0,
+            writer_held: false,
+            readers_waiting: 0,
+            writers_waiting: 0,
+            acquisitions: 0,
+            contended_acquisitions: 0,
+            max_hold_ns: 0,
+            value: None,
+        }
+    }
 }
 
 #[derive(Facet)]
@@ -111,28 +275,105 @@ pub struct MpscTxEntity {
     pub queue_len: u32,
     /// Configured capacity (`None` for unbounded).
     pub capacity: Option<u32>,
+    /// Sends dropped while waiting for capacity.
+    pub cancelled_sends: u32,
+    /// Time messages spent queued. Only kept with `mpsc_tx` detail enabled.
+    #[facet(skip_unless_truthy)]
+    pub latency: Option<QueueLatency>,
+    /// Queued messages by producer and send site, largest group first. Only
+    /// kept with `mpsc_tx` detail enabled.
+    #[facet(skip_unless_truthy)]
+    pub queued_by: Vec<QueuedBy>,
 }
 
 #[derive(Facet, Clone, Copy, Debug, PartialEq, Eq)]
-pub struct MpscRxEntity {}
+pub struct MpscRxEntity {
+    /// Receives dropped while waiting for a message.
+    pub cancelled_recvs: u32,
+}
 
 #[derive(Facet)]
 pub struct BroadcastTxEntity {
     pub capacity: u32,
+    /// Time messages spent queued, across all receivers. Only kept with
+    /// `broadcast_tx` detail enabled.
+    #[facet(skip_unless_truthy)]
+    pub latency: Option<QueueLatency>,
+    /// Messages some receiver has yet to get, by producer and send site,
+    /// largest group first. Only kept with `broadcast_tx` detail enabled.
+    #[facet(skip_unless_truthy)]
+    pub queued_by: Vec<QueuedBy>,
+}
+
+/// Delay between a message being sent and received.
+// r[impl model.channel.queue-latency]
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueueLatency {
+    pub count: u64,
+    pub total_ns: u64,
+    pub max_ns: u64,
+    /// Moving average weighted towards the last [`QueueLatency::RECENT_WINDOW`]
+    /// messages or so.
+    pub recent_ns: u64,
+    /// When the oldest message still queued was sent, if any is.
+    #[facet(skip_unless_truthy)]
+    pub oldest_queued_at: Option<PTime>,
+}
+
+/// Messages still queued on a channel that were sent by the same producer from
+/// the same place.
+// r[impl model.channel.queued-by]
+#[derive(Facet, Clone, Debug, PartialEq, Eq)]
+pub struct QueuedBy {
+    /// Task or future that sent them, if the send happened inside one.
+    #[facet(skip_unless_truthy)]
+    pub producer: Option<EntityId>,
+    /// Where they were sent from.
+    pub backtrace: BacktraceId,
+    pub count: u32,
+}
+
+impl QueuedBy {
+    /// At most this many groups are kept on an entity.
+    pub const MAX_GROUPS: usize = 16;
+}
+
+impl QueueLatency {
+    pub const RECENT_WINDOW: u64 = 16;
+
+    pub fn record(&mut self, latency_ns: u64) {
+        self.count = self.count.saturating_add(1);
+        self.total_ns = self.total_ns.saturating_add(latency_ns);
+        self.max_ns = self.max_ns.max(latency_ns);
+        self.recent_ns = if self.count == 1 {
+            latency_ns
+        } else {
+            self.recent_ns - self.recent_ns / Self::RECENT_WINDOW + latency_ns / Self::RECENT_WINDOW
+        };
+    }
 }
 
 #[derive(Facet)]
 pub struct BroadcastRxEntity {
     pub lag: u32,
+    /// Receives dropped while waiting for a message.
+    pub cancelled_recvs: u32,
 }
 
 #[derive(Facet)]
 pub struct WatchTxEntity {
     pub last_update_at: Option<PTime>,
+    /// Debug rendering of the current value, for channels created with
+    /// `channel_debug`.
+    #[facet(skip_unless_truthy)]
+    pub value: Option<String>,
 }
 
 #[derive(Facet)]
-pub struct WatchRxEntity {}
+pub struct WatchRxEntity {
+    /// `changed()` calls dropped while waiting for a new value.
+    pub cancelled_recvs: u32,
+}
 
 #[derive(Facet)]
 pub struct OneshotTxEntity {
@@ -140,7 +381,10 @@ pub struct OneshotTxEntity {
 }
 
 #[derive(Facet, Clone, Copy, Debug, PartialEq, Eq)]
-pub struct OneshotRxEntity {}
+pub struct OneshotRxEntity {
+    /// Receives dropped while waiting for the value.
+    pub cancelled_recvs: u32,
+}
 
 #[derive(Facet)]
 pub struct SemaphoreEntity {
@@ -162,6 +406,10 @@ pub struct OnceCellEntity {
     pub waiter_count: u32,
     /// Current once-cell lifecycle state.
     pub state: OnceCellState,
+    /// Debug rendering of the value once set, for cells created with
+    /// `new_debug`.
+    #[facet(skip_unless_truthy)]
+    pub value: Option<String>,
 }
 
 #[derive(Facet, Clone, Copy, Debug, PartialEq, Eq)]
@@ -173,6 +421,46 @@ pub enum OnceCellState {
     Initialized,
 }
 
+// r[impl model.barrier.state]
+#[derive(Facet)]
+pub struct BarrierEntity {
+    /// Number of tasks that must call `wait` before they are all released.
+    pub target: u32,
+    /// Tasks that have arrived in the current round and are still waiting.
+    pub waiting: u32,
+    /// Rounds completed so far.
+    pub generations: u64,
+}
+
+// r[impl model.cancellation-token.state]
+#[derive(Facet)]
+pub struct CancellationTokenEntity {
+    /// When this token was cancelled, directly or through a parent.
+    pub cancelled_at: Option<PTime>,
+    /// Number of `cancelled()` futures currently waiting on this token.
+    pub waiter_count: u32,
+    /// Times cancellation was observed, through `cancelled()` completing or
+    /// `is_cancelled()` returning true.
+    pub observed: u64,
+}
+
+// r[impl model.stream.state]
+#[derive(Facet)]
+pub struct StreamEntity {
+    /// Items yielded so far.
+    pub items: u64,
+    /// When the last item was yielded.
+    #[facet(skip_unless_truthy)]
+    pub last_item_at: Option<PTime>,
+    /// Sum of the gaps between consecutive items, in nanoseconds. The first
+    /// gap runs from the first poll to the first item.
+    pub item_gap_total_ns: u64,
+    /// Longest gap between consecutive items, in nanoseconds.
+    pub item_gap_max_ns: u64,
+    /// The stream has returned `None`.
+    pub finished: bool,
+}
+
 #[derive(Facet)]
 pub struct CommandEntity {
     /// Executable path or program name.
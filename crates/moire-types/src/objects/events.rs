@@ -51,9 +51,44 @@ pub enum EventKind {
     StateChanged,
     ChannelSent,
     ChannelReceived,
+    /// An operation waiting on the target resource was dropped before it
+    /// completed. The event's backtrace is the drop site.
+    OperationCancelled,
+    /// The target was woken. The event's backtrace is the wake site.
+    Woken(WakeEvent),
+    /// One poll of the target future took longer than the long-poll threshold.
+    /// The event's backtrace is the poll site.
+    LongPoll(LongPollEvent),
+    /// The target future panicked. The event's backtrace is the panic site.
+    Panicked(PanicEvent),
     Custom(CustomEventKind),
 }
 
+#[derive(Facet)]
+pub struct PanicEvent {
+    /// Panic message, or a placeholder when the payload is not a string.
+    pub message: String,
+    /// `file:line:column` where the panic was raised, if known.
+    #[facet(skip_unless_truthy)]
+    pub location: Option<String>,
+}
+
+#[derive(Facet)]
+pub struct WakeEvent {
+    /// Entity being polled or operated on when the waker fired (e.g. the
+    /// sender whose `send` woke a receiver).
+    pub by: EntityId,
+    /// Resource the woken entity was waiting on, when known.
+    #[facet(skip_unless_truthy)]
+    pub via: Option<EntityId>,
+}
+
+#[derive(Facet)]
+pub struct LongPollEvent {
+    /// Wall time the poll took, in nanoseconds.
+    pub duration_ns: u64,
+}
+
 /// A user-defined event kind with arbitrary payload.
 ///
 /// Library consumers can emit custom events on any entity without modifying moire source.
@@ -79,6 +114,7 @@ crate::declare_event_kind_slots!(
     StateChangedKindSlot::StateChanged,
     ChannelSentKindSlot::ChannelSent,
     ChannelReceivedKindSlot::ChannelReceived,
+    OperationCancelledKindSlot::OperationCancelled,
 );
 
 #[derive(Facet)]
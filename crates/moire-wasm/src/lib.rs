@@ -26,6 +26,8 @@ pub mod __internal {
         }
     }
 
+    pub fn note_result<T, E>(_result: &Result<T, E>) {}
+
     pub fn instrument_future<F, O, M>(
         _name: impl Into<String>,
         fut: F,
@@ -110,10 +112,21 @@ pub mod sync {
 
     impl<T> Mutex<T> {
         #[inline]
-        pub fn new(_name: &'static str, value: T) -> Self {
+        pub fn new(_name: impl Into<String>, value: T) -> Self {
             Self(std::sync::Mutex::new(value))
         }
 
+        #[inline]
+        pub fn new_debug(name: impl Into<String>, value: T) -> Self
+        where
+            T: std::fmt::Debug,
+        {
+            Self::new(name, value)
+        }
+
+        #[inline]
+        pub fn rename(&self, _name: impl Into<String>) {}
+
         #[inline]
         pub fn lock(&self) -> std::sync::MutexGuard<'_, T> {
             self.0.lock().expect("wasm mutex poisoned; cannot continue")
@@ -136,6 +149,8 @@ pub mod sync {
             Self(std::sync::Arc::new(event_listener::Event::new()))
         }
 
+        pub fn rename(&self, _name: impl Into<String>) {}
+
         pub async fn notified(&self) {
             self.0.listen().await;
         }
@@ -213,6 +228,8 @@ pub mod sync {
     }
 
     impl<T> Sender<T> {
+        pub fn rename(&self, _name: impl Into<String>) {}
+
         pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
             match self {
                 Self::Bounded(s) => s.send(value).await,
@@ -256,6 +273,8 @@ pub mod sync {
     }
 
     impl<T> Receiver<T> {
+        pub fn rename(&self, _name: impl Into<String>) {}
+
         pub async fn recv(&mut self) -> Option<T> {
             match self {
                 Self::Bounded(r) => r.recv().await,
@@ -596,6 +615,8 @@ pub mod sync {
             }))
         }
 
+        pub fn rename(&self, _name: impl Into<String>) {}
+
         pub fn available_permits(&self) -> usize {
             self.0.available.load(Ordering::Relaxed)
         }
@@ -674,8 +695,22 @@ pub mod sync {
             pub use futures_channel::oneshot::Canceled as RecvError;
         }
 
-        /// Oneshot sender.
-        pub use futures_channel::oneshot::Sender;
+        /// Wrapper around `futures-channel` oneshot sender for API parity.
+        pub struct Sender<T>(futures_channel::oneshot::Sender<T>);
+
+        impl<T> Sender<T> {
+            pub fn rename(&self, _name: impl Into<String>) {}
+
+            pub fn send(self, value: T) -> Result<(), T> {
+                self.0.send(value)
+            }
+        }
+
+        impl<T> std::fmt::Debug for Sender<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
 
         /// Wrapper around `futures-channel` oneshot receiver for API parity.
         pub struct Receiver<T>(futures_channel::oneshot::Receiver<T>);
@@ -694,6 +729,8 @@ pub mod sync {
         }
 
         impl<T> Receiver<T> {
+            pub fn rename(&self, _name: impl Into<String>) {}
+
             pub fn try_recv(&mut self) -> Result<Option<T>, futures_channel::oneshot::Canceled> {
                 self.0.try_recv()
             }
@@ -702,7 +739,7 @@ pub mod sync {
         /// Create a oneshot channel.
         pub fn channel<T>(_name: impl Into<String>) -> (Sender<T>, Receiver<T>) {
             let (tx, rx) = futures_channel::oneshot::channel();
-            (tx, Receiver(rx))
+            (Sender(tx), Receiver(rx))
         }
     }
 }
@@ -742,6 +779,82 @@ pub mod custom {
     }
 }
 
+/// Redaction rules (wasm no-op backend).
+pub mod redact {
+    pub use moire_types::RedactionSummary;
+
+    pub const REDACTED: &str = "[redacted]";
+
+    /// No-op redaction rules on wasm, where nothing is reported.
+    #[derive(Clone, Default)]
+    pub struct Redaction;
+
+    impl Redaction {
+        pub fn none() -> Self {
+            Self
+        }
+
+        pub fn defaults() -> Self {
+            Self
+        }
+
+        pub fn from_env() -> Result<Self, String> {
+            Ok(Self)
+        }
+
+        pub fn allow_env(self, _pattern: impl Into<String>) -> Self {
+            self
+        }
+
+        pub fn deny_key(self, _pattern: impl Into<String>) -> Self {
+            self
+        }
+
+        pub fn scrub_literal(self, _needle: impl Into<String>) -> Self {
+            self
+        }
+
+        pub fn scrub_with(
+            self,
+            _name: impl Into<String>,
+            _scrub: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+        ) -> Self {
+            self
+        }
+
+        pub fn install(self) {}
+
+        pub fn summary(&self) -> RedactionSummary {
+            RedactionSummary::default()
+        }
+    }
+}
+
+/// Panic hook (wasm no-op backend).
+pub mod panic_hook {
+    use std::path::PathBuf;
+
+    /// No-op panic hook on wasm, where nothing is recorded.
+    #[derive(Clone, Default)]
+    pub struct PanicHook;
+
+    impl PanicHook {
+        pub fn new() -> Self {
+            Self
+        }
+
+        pub fn from_env() -> Result<Option<Self>, String> {
+            Ok(None)
+        }
+
+        pub fn write_snapshot_to(self, _dir: impl Into<PathBuf>) -> Self {
+            self
+        }
+
+        pub fn install(self) {}
+    }
+}
+
 /// Time utilities matching `moire::time` on native.
 pub mod time {
     use std::future::Future;
@@ -16,6 +16,7 @@ use crate::util::time::now_nanos;
 
 pub async fn api_connections(State(state): State<AppState>) -> impl IntoResponse {
     let guard = state.inner.lock().await;
+    let now_ns = now_nanos();
     let mut processes: Vec<ConnectedProcessInfo> = guard
         .connections
         .iter()
@@ -23,9 +24,12 @@ pub async fn api_connections(State(state): State<AppState>) -> impl IntoResponse
             let process_id = conn.process_id.clone()?;
             Some(ConnectedProcessInfo {
                 conn_id: *conn_id,
+                liveness: guard.runtime_liveness(&process_id, now_ns),
                 process_id,
                 process_name: conn.process_name.clone(),
                 pid: conn.pid,
+                redaction: conn.redaction.clone(),
+                config_errors: conn.config_errors.clone(),
             })
         })
         .collect();
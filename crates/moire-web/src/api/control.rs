@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use moire_types::{
+    ProcessControlAck, RuntimeControlRequest, RuntimeControlResponse, TimedOutProcess,
+};
+use moire_wire::{ControlRequest, ServerMessage, encode_server_message_default};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::app::{AppState, ControlPending};
+use crate::util::http::{json_error, json_ok};
+use crate::util::time::now_nanos;
+
+const CONTROL_TIMEOUT_MS: u64 = 5000;
+
+// r[impl wire.control]
+pub async fn api_control(State(state): State<AppState>, body: Bytes) -> impl IntoResponse {
+    let request: RuntimeControlRequest = match facet_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(error) => {
+            return json_error(
+                StatusCode::BAD_REQUEST,
+                format!("invalid request json: {error}"),
+            );
+        }
+    };
+    if request.changes.is_empty() {
+        return json_error(StatusCode::BAD_REQUEST, "changes must be non-empty");
+    }
+
+    let notify = Arc::new(Notify::new());
+    let (control_id, outbound) = {
+        let mut guard = state.inner.lock().await;
+        let control_id = guard.next_control_id;
+        guard.next_control_id += 1;
+        let outbound: Vec<_> = guard
+            .connections
+            .iter()
+            .filter(|(_, conn)| {
+                conn.process_id.is_some()
+                    && request
+                        .process_id
+                        .as_ref()
+                        .is_none_or(|wanted| conn.process_id.as_ref() == Some(wanted))
+            })
+            .map(|(conn_id, conn)| (*conn_id, conn.tx.clone()))
+            .collect();
+        if !outbound.is_empty() {
+            guard.pending_controls.insert(
+                control_id,
+                ControlPending {
+                    pending_conn_ids: outbound.iter().map(|(conn_id, _)| *conn_id).collect(),
+                    acks: HashMap::new(),
+                    notify: notify.clone(),
+                },
+            );
+        }
+        (control_id, outbound)
+    };
+
+    if outbound.is_empty() {
+        return json_ok(&RuntimeControlResponse {
+            control_id,
+            acks: vec![],
+            timed_out_processes: vec![],
+        });
+    }
+
+    info!(
+        control_id,
+        requested_connections = outbound.len(),
+        changes = request.changes.len(),
+        "runtime control requested via API"
+    );
+    let frame =
+        match encode_server_message_default(&ServerMessage::ControlRequest(ControlRequest {
+            control_id,
+            changes: request.changes,
+        })) {
+            Ok(frame) => frame,
+            Err(error) => {
+                state
+                    .inner
+                    .lock()
+                    .await
+                    .pending_controls
+                    .remove(&control_id);
+                return json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("failed to encode control request: {error}"),
+                );
+            }
+        };
+    for (conn_id, tx) in &outbound {
+        if let Err(error) = tx.try_send(frame.clone()) {
+            warn!(conn_id = %conn_id, %error, "failed to enqueue control request");
+        }
+    }
+
+    let _ =
+        tokio::time::timeout(Duration::from_millis(CONTROL_TIMEOUT_MS), notify.notified()).await;
+
+    let mut guard = state.inner.lock().await;
+    let now_ns = now_nanos();
+    let Some(pending) = guard.pending_controls.remove(&control_id) else {
+        return json_ok(&RuntimeControlResponse {
+            control_id,
+            acks: vec![],
+            timed_out_processes: vec![],
+        });
+    };
+    let mut acks = Vec::with_capacity(pending.acks.len());
+    for (conn_id, ack) in pending.acks {
+        let Some(conn) = guard.connections.get(&conn_id) else {
+            continue;
+        };
+        let Some(process_id) = conn.process_id.clone() else {
+            continue;
+        };
+        acks.push(ProcessControlAck {
+            process_id,
+            process_name: conn.process_name.clone(),
+            pid: conn.pid,
+            error: ack.error,
+            settings: ack.settings,
+        });
+    }
+    let timed_out_processes = pending
+        .pending_conn_ids
+        .iter()
+        .filter_map(|conn_id| {
+            let conn = guard.connections.get(conn_id)?;
+            let process_id = conn.process_id.clone()?;
+            Some(TimedOutProcess {
+                liveness: guard.runtime_liveness(&process_id, now_ns),
+                process_id,
+                process_name: conn.process_name.clone(),
+                pid: conn.pid,
+            })
+        })
+        .collect();
+    drop(guard);
+
+    acks.sort_by(|a, b| {
+        a.process_name
+            .cmp(&b.process_name)
+            .then_with(|| a.pid.cmp(&b.pid))
+    });
+    json_ok(&RuntimeControlResponse {
+        control_id,
+        acks,
+        timed_out_processes,
+    })
+}
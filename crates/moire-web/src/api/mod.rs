@@ -1,6 +1,8 @@
 pub mod connections;
+pub mod control;
 pub mod recording;
 pub mod snapshot;
 pub mod source;
 pub mod sql;
 pub mod theme;
+pub mod wire_trace;
@@ -225,18 +225,22 @@ pub async fn api_record_import(State(state): State<AppState>, body: Bytes) -> im
         Ok(v) => v,
         Err(e) => return json_error(StatusCode::BAD_REQUEST, format!("invalid import json: {e}")),
     };
+    match import_recording(&state, import).await {
+        Ok(response) => json_ok(&response),
+        Err(error) => json_error(StatusCode::BAD_REQUEST, error),
+    }
+}
 
+/// Replaces the current recording session with an exported one.
+pub async fn import_recording(
+    state: &AppState,
+    import: RecordingImportBody,
+) -> Result<RecordCurrentResponse, String> {
     if import.version != 1 {
-        return json_error(
-            StatusCode::BAD_REQUEST,
-            format!("unsupported export version: {}", import.version),
-        );
+        return Err(format!("unsupported export version: {}", import.version));
     }
 
-    let frames = match build_imported_frames(&import) {
-        Ok(frames) => frames,
-        Err(error) => return json_error(StatusCode::BAD_REQUEST, error),
-    };
+    let frames = build_imported_frames(&import)?;
 
     let approx_memory_bytes: u64 = frames.iter().map(|f| f.json.len() as u64).sum();
     let total_frames_captured = frames.len() as u32;
@@ -274,7 +278,7 @@ pub async fn api_record_import(State(state): State<AppState>, body: Bytes) -> im
 
     let guard = state.inner.lock().await;
     let rec = guard.recording.as_ref().unwrap();
-    json_ok(&RecordCurrentResponse {
+    Ok(RecordCurrentResponse {
         session: Some(recording_session_info(rec)),
     })
 }
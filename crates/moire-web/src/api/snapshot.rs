@@ -8,22 +8,22 @@ use axum::http::{StatusCode, header};
 use axum::response::IntoResponse;
 use moire_trace_types::FrameId;
 use moire_types::{
-    BacktraceFrameUnresolved, ProcessSnapshotView, SnapshotBacktraceFrame, SnapshotCutResponse,
-    SnapshotFrameRecord, SnapshotSymbolicationUpdate, TimedOutProcess,
+    BacktraceFrameUnresolved, ProcessSnapshotView, RuntimeLiveness, SnapshotBacktraceFrame,
+    SnapshotCutResponse, SnapshotFrameRecord, SnapshotSymbolicationUpdate, TimedOutProcess,
 };
 use moire_wire::{ServerMessage, SnapshotRequest, encode_server_message_default};
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 
 use crate::app::{AppState, ConnectionId, SnapshotPending, SnapshotStreamState, remember_snapshot};
-use crate::db::fetch_scope_entity_links_blocking;
+use crate::db::{fetch_scope_entity_links_blocking, load_stored_snapshots_blocking};
 use crate::snapshot::table::{
     SnapshotBacktraceTable, collect_snapshot_backtrace_ids, is_pending_frame,
     load_snapshot_backtrace_table,
 };
 use crate::symbolication::symbolicate_pending_frames_for_backtraces;
 use crate::util::http::{json_error, json_ok};
-use crate::util::time::now_ms;
+use crate::util::time::{now_ms, now_nanos};
 
 const SYMBOLICATION_STREAM_STALL_TICKS_LIMIT: u32 = 100;
 const SYMBOLICATION_UNRESOLVED_STALLED: &str =
@@ -268,6 +268,10 @@ async fn refresh_cached_snapshot_from_table(
 pub async fn take_snapshot_internal(state: &AppState) -> SnapshotCutResponse {
     const SNAPSHOT_TIMEOUT_MS: u64 = 5000;
 
+    if state.offline {
+        return take_stored_snapshot(state).await;
+    }
+
     let snapshot_id;
     let notify;
     let txs: Vec<(ConnectionId, moire_types::ProcessId, mpsc::Sender<Vec<u8>>)>;
@@ -371,9 +375,19 @@ pub async fn take_snapshot_internal(state: &AppState) -> SnapshotCutResponse {
     .await;
 
     let captured_at_unix_ms = now_ms();
-    let (pending, conn_info) = {
+    let (pending, conn_info, liveness) = {
         let mut guard = state.inner.lock().await;
         let pending = guard.pending_snapshots.remove(&snapshot_id);
+        let now_ns = now_nanos();
+        let liveness: HashMap<moire_types::ProcessId, RuntimeLiveness> = guard
+            .connections
+            .values()
+            .filter_map(|conn| {
+                let process_id = conn.process_id.clone()?;
+                let liveness = guard.runtime_liveness(&process_id, now_ns)?;
+                Some((process_id, liveness))
+            })
+            .collect();
         let conn_info: HashMap<ConnectionId, (moire_types::ProcessId, String, u32)> = guard
             .connections
             .iter()
@@ -388,7 +402,7 @@ pub async fn take_snapshot_internal(state: &AppState) -> SnapshotCutResponse {
                 ))
             })
             .collect();
-        (pending, conn_info)
+        (pending, conn_info, liveness)
     };
 
     let (processes, timed_out_processes) = match pending {
@@ -459,6 +473,7 @@ pub async fn take_snapshot_internal(state: &AppState) -> SnapshotCutResponse {
                             )
                         });
                     TimedOutProcess {
+                        liveness: liveness.get(&process_id).cloned(),
                         process_id,
                         process_name,
                         pid,
@@ -470,7 +485,7 @@ pub async fn take_snapshot_internal(state: &AppState) -> SnapshotCutResponse {
         }
     };
 
-    let mut response = SnapshotCutResponse {
+    let response = SnapshotCutResponse {
         snapshot_id,
         captured_at_unix_ms,
         processes,
@@ -484,6 +499,49 @@ pub async fn take_snapshot_internal(state: &AppState) -> SnapshotCutResponse {
         timed_out_count = response.timed_out_processes.len(),
         "snapshot request completed"
     );
+    finish_snapshot(state, response).await
+}
+
+/// Builds a snapshot from the database instead of asking live processes.
+// r[impl config.web.open]
+async fn take_stored_snapshot(state: &AppState) -> SnapshotCutResponse {
+    let snapshot_id = {
+        let mut guard = state.inner.lock().await;
+        let snapshot_id = guard.next_snapshot_id;
+        guard.next_snapshot_id += 1;
+        snapshot_id
+    };
+    let db = state.db.clone();
+    let processes = tokio::task::spawn_blocking(move || load_stored_snapshots_blocking(&db))
+        .await
+        .unwrap_or_else(|e| Err(format!("join sqlite: {e}")))
+        .unwrap_or_else(|e| {
+            error!(%e, "failed to load stored snapshot");
+            vec![]
+        });
+    let response = SnapshotCutResponse {
+        snapshot_id,
+        captured_at_unix_ms: now_ms(),
+        processes,
+        timed_out_processes: vec![],
+        backtraces: vec![],
+        frames: vec![],
+    };
+    info!(
+        snapshot_id,
+        process_count = response.processes.len(),
+        "stored snapshot loaded"
+    );
+    finish_snapshot(state, response).await
+}
+
+/// Attaches the backtrace table, registers the symbolication stream and caches
+/// the snapshot as the current one.
+async fn finish_snapshot(
+    state: &AppState,
+    mut response: SnapshotCutResponse,
+) -> SnapshotCutResponse {
+    let snapshot_id = response.snapshot_id;
     let backtrace_ids = collect_snapshot_backtrace_ids(&response);
     let backtrace_table = load_snapshot_backtrace_table(state.db.clone(), &backtrace_ids).await;
     response.backtraces = backtrace_table.backtraces;
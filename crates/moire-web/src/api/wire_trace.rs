@@ -0,0 +1,26 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use tracing::info;
+
+use crate::app::AppState;
+use crate::tcp::replay_wire_trace;
+use crate::util::http::{json_error, json_ok};
+
+/// Upper bound for an uploaded wire trace.
+pub const WIRE_TRACE_MAX_BYTES: usize = 1024 * 1024 * 1024;
+
+pub async fn api_wire_trace_import(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> impl IntoResponse {
+    info!(bytes = body.len(), "wire trace import requested");
+    match replay_wire_trace(&mut &body[..], &state).await {
+        Ok(response) => json_ok(&response),
+        Err(error) => json_error(
+            StatusCode::BAD_REQUEST,
+            format!("invalid wire trace: {error}"),
+        ),
+    }
+}
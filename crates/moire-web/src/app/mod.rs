@@ -3,10 +3,15 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::Router;
+use axum::extract::{DefaultBodyLimit, Request};
+use axum::http::StatusCode;
+use axum::middleware::{Next, from_fn, from_fn_with_state};
+use axum::response::Response;
 use axum::routing::{any, get, post};
 use tower_http::services::{ServeDir, ServeFile};
 
 use crate::api::connections::{api_connections, api_cut_status, api_trigger_cut};
+use crate::api::control::api_control;
 use crate::api::recording::{
     api_record_current, api_record_export, api_record_frame, api_record_import, api_record_start,
     api_record_stop,
@@ -15,12 +20,15 @@ use crate::api::snapshot::{api_snapshot, api_snapshot_current, api_snapshot_symb
 use crate::api::source::{api_source_preview, api_source_previews};
 use crate::api::sql::{api_query, api_sql};
 use crate::api::theme::api_arborium_theme_css;
+use crate::api::wire_trace::{WIRE_TRACE_MAX_BYTES, api_wire_trace_import};
+use crate::auth::{AuthToken, require_token};
 use crate::db::{Db, StoredModuleManifestEntry};
 use crate::proxy::proxy_vite;
 use crate::recording::session::RecordingState;
+use crate::util::http::json_error;
 use moire_trace_types::BacktraceId;
-use moire_types::{ProcessId, SnapshotCutResponse};
-use moire_wire::SnapshotReply;
+use moire_types::{EntityId, ProcessId, RedactionSummary, RuntimeLiveness, SnapshotCutResponse};
+use moire_wire::{ControlAck, Heartbeat, SnapshotReply};
 use tokio::sync::{Mutex, Notify, mpsc};
 
 pub mod ids;
@@ -32,6 +40,11 @@ pub struct AppState {
     pub db: Arc<Db>,
     pub dev_proxy: Option<DevProxyState>,
     pub frontend_dist: Option<PathBuf>,
+    /// When set, ingest connections, HTTP requests and MCP requests must present it.
+    pub auth_token: Option<AuthToken>,
+    /// Serving a file opened with `moire-web open`: there is no ingest listener and
+    /// snapshots are rebuilt from the database.
+    pub offline: bool,
 }
 
 #[derive(Clone)]
@@ -43,15 +56,18 @@ pub struct ServerState {
     pub next_conn_id: ConnectionId,
     pub next_cut_id: CutOrdinal,
     pub next_snapshot_id: i64,
+    pub next_control_id: i64,
     pub next_session_id: SessionOrdinal,
     pub connections: HashMap<ConnectionId, ConnectedProcess>,
     pub cuts: BTreeMap<moire_types::CutId, CutState>,
     pub pending_snapshots: HashMap<i64, SnapshotPending>,
+    pub pending_controls: HashMap<i64, ControlPending>,
     pub snapshot_streams: HashMap<i64, SnapshotStreamState>,
     pub last_snapshot_json: Option<String>,
     pub snapshot_history_ids: VecDeque<i64>,
     pub snapshot_history_json: BTreeMap<i64, String>,
     pub recording: Option<RecordingState>,
+    pub heartbeats: HashMap<ProcessId, HeartbeatState>,
 }
 
 pub struct ConnectedProcess {
@@ -60,9 +76,24 @@ pub struct ConnectedProcess {
     pub pid: u32,
     pub handshake_received: bool,
     pub module_manifest: Vec<StoredModuleManifestEntry>,
+    /// Redaction rules the process reported in its handshake.
+    pub redaction: Option<RedactionSummary>,
+    /// Settings the process rejected, as reported in its handshake.
+    pub config_errors: Vec<String>,
     pub tx: mpsc::Sender<Vec<u8>>,
 }
 
+/// Heartbeats with no runtime progress for this long mark the runtime as unresponsive.
+const RUNTIME_UNRESPONSIVE_AFTER_NS: i64 = 3_000_000_000;
+
+/// Latest heartbeat seen from a process.
+pub struct HeartbeatState {
+    pub received_at_ns: i64,
+    pub runtime_ticks: Option<u64>,
+    pub ticks_advanced_at_ns: i64,
+    pub last_active: Vec<EntityId>,
+}
+
 pub struct CutState {
     pub requested_at_ns: i64,
     pub pending_conn_ids: BTreeSet<ConnectionId>,
@@ -75,6 +106,12 @@ pub struct SnapshotPending {
     pub notify: Arc<Notify>,
 }
 
+pub struct ControlPending {
+    pub pending_conn_ids: BTreeSet<ConnectionId>,
+    pub acks: HashMap<ConnectionId, ControlAck>,
+    pub notify: Arc<Notify>,
+}
+
 pub struct SnapshotStreamState {
     pub backtrace_ids: Vec<BacktraceId>,
 }
@@ -85,15 +122,120 @@ impl ServerState {
             next_conn_id,
             next_cut_id: CutOrdinal::ONE,
             next_snapshot_id: 1,
+            next_control_id: 1,
             next_session_id: SessionOrdinal::ONE,
             connections: HashMap::new(),
             cuts: BTreeMap::new(),
             pending_snapshots: HashMap::new(),
+            pending_controls: HashMap::new(),
             snapshot_streams: HashMap::new(),
             last_snapshot_json: None,
             snapshot_history_ids: VecDeque::new(),
             snapshot_history_json: BTreeMap::new(),
             recording: None,
+            heartbeats: HashMap::new(),
+        }
+    }
+
+    /// Drops a connection from the live set and from every pending cut or snapshot,
+    /// returning the snapshot waiters that no longer have anyone to wait for.
+    /// Heartbeat state goes with the last connection of its process.
+    pub fn forget_connection(&mut self, conn_id: ConnectionId) -> Vec<Arc<Notify>> {
+        if let Some(process_id) = self
+            .connections
+            .remove(&conn_id)
+            .and_then(|conn| conn.process_id)
+            && !self
+                .connections
+                .values()
+                .any(|conn| conn.process_id.as_ref() == Some(&process_id))
+        {
+            self.heartbeats.remove(&process_id);
+        }
+        for cut in self.cuts.values_mut() {
+            cut.pending_conn_ids.remove(&conn_id);
+            cut.acks.remove(&conn_id);
+        }
+        let snapshot_waiters = self.pending_snapshots.values_mut().filter_map(|pending| {
+            if pending.pending_conn_ids.remove(&conn_id) && pending.pending_conn_ids.is_empty() {
+                Some(pending.notify.clone())
+            } else {
+                None
+            }
+        });
+        let control_waiters = self.pending_controls.values_mut().filter_map(|pending| {
+            if pending.pending_conn_ids.remove(&conn_id) && pending.pending_conn_ids.is_empty() {
+                Some(pending.notify.clone())
+            } else {
+                None
+            }
+        });
+        snapshot_waiters.chain(control_waiters).collect()
+    }
+
+    // r[impl wire.heartbeat]
+    pub fn record_heartbeat(&mut self, heartbeat: Heartbeat, now_ns: i64) {
+        let state = self
+            .heartbeats
+            .entry(heartbeat.process_id)
+            .or_insert_with(|| HeartbeatState {
+                received_at_ns: now_ns,
+                runtime_ticks: None,
+                ticks_advanced_at_ns: now_ns,
+                last_active: Vec::new(),
+            });
+        if state.runtime_ticks != heartbeat.runtime_ticks {
+            state.runtime_ticks = heartbeat.runtime_ticks;
+            state.ticks_advanced_at_ns = now_ns;
+        }
+        state.received_at_ns = now_ns;
+        state.last_active = heartbeat.last_active;
+    }
+
+    pub fn runtime_liveness(&self, process_id: &ProcessId, now_ns: i64) -> Option<RuntimeLiveness> {
+        let state = self.heartbeats.get(process_id)?;
+        let stalled_ns = now_ns.saturating_sub(state.ticks_advanced_at_ns).max(0);
+        let heartbeats_flowing =
+            now_ns.saturating_sub(state.received_at_ns) <= RUNTIME_UNRESPONSIVE_AFTER_NS;
+        Some(RuntimeLiveness {
+            runtime_unresponsive: state.runtime_ticks.is_some()
+                && heartbeats_flowing
+                && stalled_ns > RUNTIME_UNRESPONSIVE_AFTER_NS,
+            runtime_stalled_ms: (stalled_ns / 1_000_000) as u64,
+            last_active_entities: state.last_active.clone(),
+        })
+    }
+
+    /// Moves a live connection to another id, carrying over any cut or snapshot
+    /// bookkeeping that already references it.
+    pub fn rekey_connection(&mut self, from: ConnectionId, to: ConnectionId) {
+        let Some(conn) = self.connections.remove(&from) else {
+            return;
+        };
+        self.connections.insert(to, conn);
+        for cut in self.cuts.values_mut() {
+            if cut.pending_conn_ids.remove(&from) {
+                cut.pending_conn_ids.insert(to);
+            }
+            if let Some(ack) = cut.acks.remove(&from) {
+                cut.acks.insert(to, ack);
+            }
+        }
+        for pending in self.pending_snapshots.values_mut() {
+            if pending.pending_conn_ids.remove(&from) {
+                pending.pending_conn_ids.insert(to);
+            }
+            if let Some(reply) = pending.replies.remove(&from) {
+                pending.replies.insert(to, reply);
+            }
+        }
+        for pending in self.pending_controls.values_mut() {
+            if pending.pending_conn_ids.remove(&from) {
+                pending.pending_conn_ids.insert(to);
+            }
+            if let Some(ack) = pending.acks.remove(&from) {
+                pending.acks.insert(to, ack);
+            }
         }
     }
 }
@@ -104,21 +246,36 @@ impl AppState {
         next_conn_id: ConnectionId,
         dev_proxy: Option<DevProxyState>,
         frontend_dist: Option<PathBuf>,
+        auth_token: Option<AuthToken>,
     ) -> Self {
         Self {
             inner: Arc::new(Mutex::new(ServerState::new(next_conn_id))),
             db: Arc::new(db),
             dev_proxy,
             frontend_dist,
+            auth_token,
+            offline: false,
         }
     }
 }
 
 pub fn build_router(state: AppState) -> Router {
+    // Routes that reach live processes or load new data.
+    let mut writes = Router::new()
+        .route("/api/cuts", post(api_trigger_cut))
+        .route("/api/control", post(api_control))
+        .route("/api/record/start", post(api_record_start))
+        .route("/api/record/import", post(api_record_import))
+        .route(
+            "/api/wire-trace/import",
+            post(api_wire_trace_import).layer(DefaultBodyLimit::max(WIRE_TRACE_MAX_BYTES)),
+        );
+    if state.offline {
+        writes = writes.route_layer(from_fn(reject_read_only));
+    }
     let mut app = Router::new()
         .route("/health", get(health))
         .route("/api/connections", get(api_connections))
-        .route("/api/cuts", post(api_trigger_cut))
         .route("/api/cuts/{cut_id}", get(api_cut_status))
         .route("/api/sql", post(api_sql))
         .route("/api/query", post(api_query))
@@ -128,7 +285,6 @@ pub fn build_router(state: AppState) -> Router {
             "/api/snapshot/{snapshot_id}/symbolication/ws",
             get(api_snapshot_symbolication_ws),
         )
-        .route("/api/record/start", post(api_record_start))
         .route("/api/record/stop", post(api_record_stop))
         .route("/api/record/current", get(api_record_current))
         .route(
@@ -136,10 +292,10 @@ pub fn build_router(state: AppState) -> Router {
             get(api_record_frame),
         )
         .route("/api/record/current/export", get(api_record_export))
-        .route("/api/record/import", post(api_record_import))
         .route("/api/source/preview", get(api_source_preview))
         .route("/api/source/previews", post(api_source_previews))
-        .route("/api/arborium-theme.css", get(api_arborium_theme_css));
+        .route("/api/arborium-theme.css", get(api_arborium_theme_css))
+        .merge(writes);
     if state.dev_proxy.is_some() {
         app = app.fallback(any(proxy_vite));
     } else if let Some(frontend_dist) = &state.frontend_dist {
@@ -147,9 +303,21 @@ pub fn build_router(state: AppState) -> Router {
             .not_found_service(ServeFile::new(frontend_dist.join("index.html")));
         app = app.fallback_service(spa);
     }
+    if let Some(token) = state.auth_token.clone() {
+        app = app.layer(from_fn_with_state(token, require_token));
+    }
     app.with_state(state)
 }
 
+/// Answers for the write routes while an opened file is being served.
+// r[impl config.web.open]
+async fn reject_read_only(_request: Request, _next: Next) -> Response {
+    json_error(
+        StatusCode::CONFLICT,
+        "moire-web is serving an opened file read-only",
+    )
+}
+
 pub async fn health() -> &'static str {
     "ok"
 }
@@ -176,3 +344,93 @@ pub async fn remember_snapshot(state: &AppState, snapshot: &SnapshotCutResponse)
         guard.snapshot_history_json.remove(&oldest);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connected(state: &mut ServerState, conn_id: u64, process_id: &ProcessId) {
+        let (tx, _rx) = mpsc::channel(1);
+        state.connections.insert(
+            ConnectionId::new(conn_id),
+            ConnectedProcess {
+                process_id: Some(process_id.clone()),
+                process_name: String::from("proc"),
+                pid: 1,
+                handshake_received: true,
+                module_manifest: Vec::new(),
+                redaction: None,
+                config_errors: Vec::new(),
+                tx,
+            },
+        );
+    }
+
+    fn heartbeat(process_id: &ProcessId) -> Heartbeat {
+        Heartbeat {
+            process_id: process_id.clone(),
+            ptime_now_ms: 0,
+            runtime_ticks: Some(1),
+            last_active: Vec::new(),
+            token: None,
+        }
+    }
+
+    #[test]
+    fn forgetting_the_last_connection_drops_heartbeat_state() {
+        let process_id = ProcessId::new("heartbeating");
+        let mut state = ServerState::new(ConnectionId::new(1));
+        connected(&mut state, 1, &process_id);
+        connected(&mut state, 2, &process_id);
+        state.record_heartbeat(heartbeat(&process_id), 0);
+
+        state.forget_connection(ConnectionId::new(1));
+        assert!(state.runtime_liveness(&process_id, 0).is_some());
+
+        state.forget_connection(ConnectionId::new(2));
+        assert!(state.runtime_liveness(&process_id, 0).is_none());
+        assert!(state.heartbeats.is_empty());
+    }
+
+    #[test]
+    fn runtime_with_stuck_ticks_is_reported_stalled() {
+        const SECOND_NS: i64 = 1_000_000_000;
+        let process_id = ProcessId::new("stalling");
+        let mut state = ServerState::new(ConnectionId::new(1));
+        let stuck = |last_active: &[&str]| Heartbeat {
+            last_active: last_active.iter().map(|id| EntityId::new(*id)).collect(),
+            ..heartbeat(&process_id)
+        };
+
+        state.record_heartbeat(stuck(&[]), 0);
+        state.record_heartbeat(stuck(&[]), 2 * SECOND_NS);
+        let liveness = state.runtime_liveness(&process_id, 2 * SECOND_NS).unwrap();
+        assert!(!liveness.runtime_unresponsive);
+
+        // Heartbeats keep arriving but the runtime tick count never moves.
+        state.record_heartbeat(stuck(&["busy_future"]), 4 * SECOND_NS);
+        let liveness = state.runtime_liveness(&process_id, 4 * SECOND_NS).unwrap();
+        assert!(liveness.runtime_unresponsive);
+        assert_eq!(liveness.runtime_stalled_ms, 4_000);
+        assert_eq!(
+            liveness.last_active_entities,
+            vec![EntityId::new("busy_future")]
+        );
+
+        // Once heartbeats stop too, the process is gone rather than stalled.
+        let liveness = state.runtime_liveness(&process_id, 8 * SECOND_NS).unwrap();
+        assert!(!liveness.runtime_unresponsive);
+
+        // Progress clears the stall.
+        state.record_heartbeat(
+            Heartbeat {
+                runtime_ticks: Some(2),
+                ..heartbeat(&process_id)
+            },
+            9 * SECOND_NS,
+        );
+        let liveness = state.runtime_liveness(&process_id, 9 * SECOND_NS).unwrap();
+        assert!(!liveness.runtime_unresponsive);
+        assert_eq!(liveness.runtime_stalled_ms, 0);
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::util::http::json_error;
+
+const TOKEN_COOKIE: &str = "moire_token";
+const TOKEN_QUERY_PARAM: &str = "token";
+
+/// Shared secret that processes, HTTP clients and MCP clients must present.
+#[derive(Clone)]
+pub struct AuthToken(Arc<str>);
+
+impl AuthToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(Arc::from(token.into()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn from_env() -> Option<Self> {
+        std::env::var("MOIRE_TOKEN")
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .map(Self::new)
+    }
+
+    /// Compares in time independent of where the first mismatching byte is.
+    pub fn matches(&self, candidate: Option<&str>) -> bool {
+        let Some(candidate) = candidate else {
+            return false;
+        };
+        let expected = self.0.as_bytes();
+        let candidate = candidate.as_bytes();
+        if expected.len() != candidate.len() {
+            return false;
+        }
+        expected
+            .iter()
+            .zip(candidate)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+    }
+}
+
+/// Axum middleware that rejects requests without the server's token.
+///
+/// The token is accepted as `Authorization: Bearer <token>`, as the `moire_token`
+/// cookie, or as a `?token=` query parameter. The query parameter form sets the
+/// cookie, so opening `http://host:port/?token=...` once is enough for the UI.
+/// `/health` stays open for liveness probes.
+// r[impl config.web.token]
+pub async fn require_token(
+    State(token): State<AuthToken>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+    if token.matches(bearer_token(request.headers()))
+        || token.matches(cookie_token(request.headers()))
+    {
+        return next.run(request).await;
+    }
+    if token.matches(query_token(request.uri().query())) {
+        let mut response = next.run(request).await;
+        if let Ok(cookie) = HeaderValue::from_str(&format!(
+            "{TOKEN_COOKIE}={}; Path=/; HttpOnly; SameSite=Strict",
+            token.0
+        )) {
+            response.headers_mut().append(header::SET_COOKIE, cookie);
+        }
+        return response;
+    }
+    let mut response = json_error(StatusCode::UNAUTHORIZED, "missing or invalid token");
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        HeaderValue::from_static("Bearer realm=\"moire\""),
+    );
+    response.into_response()
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+}
+
+fn cookie_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            (name == TOKEN_COOKIE).then_some(value)
+        })
+}
+
+fn query_token(query: Option<&str>) -> Option<&str> {
+    query?.split('&').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        (name == TOKEN_QUERY_PARAM).then_some(value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_matches_only_exact_value() {
+        let token = AuthToken::new("s3cret");
+        assert!(token.matches(Some("s3cret")));
+        assert!(!token.matches(Some("s3cre")));
+        assert!(!token.matches(Some("s3creT")));
+        assert!(!token.matches(None));
+    }
+
+    #[test]
+    fn token_is_found_in_header_cookie_and_query() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer s3cret"),
+        );
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; moire_token=fromcookie"),
+        );
+        assert_eq!(bearer_token(&headers), Some("s3cret"));
+        assert_eq!(cookie_token(&headers), Some("fromcookie"));
+        assert_eq!(query_token(Some("a=1&token=fromquery")), Some("fromquery"));
+        assert_eq!(query_token(Some("tokens=1")), None);
+    }
+}
@@ -9,6 +9,8 @@ fn main() -> Result<(), String> {
     tsgen.add_type::<moire_types::ConnectedProcessInfo>();
     tsgen.add_type::<moire_types::TriggerCutResponse>();
     tsgen.add_type::<moire_types::CutStatusResponse>();
+    tsgen.add_type::<moire_types::RuntimeControlRequest>();
+    tsgen.add_type::<moire_types::RuntimeControlResponse>();
     tsgen.add_type::<moire_types::ApiError>();
     tsgen.add_type::<moire_types::SqlRequest>();
     tsgen.add_type::<moire_types::QueryRequest>();
@@ -18,6 +20,7 @@ fn main() -> Result<(), String> {
     tsgen.add_type::<moire_types::RecordStartRequest>();
     tsgen.add_type::<moire_types::RecordCurrentResponse>();
     tsgen.add_type::<moire_types::RecordingImportBody>();
+    tsgen.add_type::<moire_types::WireTraceImportResponse>();
     tsgen.add_type::<moire_types::SourcePreviewResponse>();
     tsgen.add_type::<moire_types::SourcePreviewBatchRequest>();
     tsgen.add_type::<moire_types::SourcePreviewBatchResponse>();
@@ -7,13 +7,17 @@ mod query;
 mod schema;
 
 pub use persist::{
-    BacktraceFramePersist, StoredModuleManifestEntry, backtrace_frames_for_store,
-    into_stored_module_manifest, persist_backtrace_record, persist_connection_closed,
-    persist_connection_module_manifest, persist_connection_upsert, persist_cut_ack,
-    persist_cut_request, persist_delta_batch,
+    BacktraceFramePersist, SessionResumeState, StoredModuleManifestEntry,
+    backtrace_frames_for_store, into_stored_module_manifest, load_session_resume,
+    persist_backtrace_record, persist_connection_closed, persist_connection_module_manifest,
+    persist_connection_resumed, persist_connection_upsert, persist_cut_ack, persist_cut_request,
+    persist_delta_batch,
 };
-pub use query::{fetch_scope_entity_links_blocking, query_named_blocking, sql_query_blocking};
-pub use schema::{init_sqlite, load_next_connection_id};
+pub use query::{
+    fetch_scope_entity_links_blocking, load_stored_snapshots_blocking, query_named_blocking,
+    sql_query_blocking,
+};
+pub use schema::{check_sqlite_schema, init_sqlite, load_next_connection_id};
 
 #[derive(Debug, Clone)]
 pub struct Db {
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use facet::Facet;
 use moire_trace_types::{BacktraceId, ModuleId, RelPc, RuntimeBase};
-use moire_types::{ConnectionId, ProcessId};
+use moire_types::{ConnectionId, ProcessId, SeqNo};
 use moire_wire::{BacktraceRecord, ModuleIdentity, ModuleManifestEntry};
 use rusqlite_facet::{ConnectionFacetExt, StatementFacetExt};
 
@@ -35,6 +35,27 @@ struct ConnectionUpsertParams {
     connected_at_ns: i64,
 }
 
+#[derive(Facet)]
+struct ConnectionResumedParams {
+    conn_id: ConnectionId,
+    process_name: String,
+    pid: u32,
+}
+
+#[derive(Facet)]
+struct SessionResumeRow {
+    previous_conn_id: Option<u64>,
+    next_seq_no: Option<u64>,
+    last_backtrace_id: Option<BacktraceId>,
+}
+
+/// What the server already holds for a process, used to resume its stream after a reconnect.
+pub struct SessionResumeState {
+    pub previous_conn_id: Option<ConnectionId>,
+    pub next_seq_no: SeqNo,
+    pub last_backtrace_id: Option<BacktraceId>,
+}
+
 #[derive(Facet)]
 struct ConnectionClosedParams {
     conn_id: ConnectionId,
@@ -259,6 +280,60 @@ pub async fn persist_connection_upsert(
     .map_err(|error| format!("join sqlite: {error}"))?
 }
 
+// r[impl wire.session-resume]
+pub async fn load_session_resume(
+    db: Arc<Db>,
+    process_id: ProcessId,
+) -> Result<SessionResumeState, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = db.open()?;
+        let row = conn
+            .facet_query_one_ref::<SessionResumeRow, _>(
+                "SELECT
+                   (SELECT MAX(conn_id) FROM connections WHERE process_id = :process_id) AS previous_conn_id,
+                   (SELECT next_seq_no FROM stream_cursors WHERE process_id = :process_id) AS next_seq_no,
+                   (SELECT MAX(backtrace_id) FROM backtraces WHERE process_id = :process_id) AS last_backtrace_id",
+                &ProcessIdParams { process_id },
+            )
+            .map_err(|error| format!("load session resume state: {error}"))?;
+        Ok::<SessionResumeState, String>(SessionResumeState {
+            previous_conn_id: row.previous_conn_id.map(ConnectionId::new),
+            next_seq_no: row.next_seq_no.map(SeqNo).unwrap_or(SeqNo::ZERO),
+            last_backtrace_id: row.last_backtrace_id,
+        })
+    })
+    .await
+    .map_err(|error| format!("join sqlite: {error}"))?
+}
+
+pub async fn persist_connection_resumed(
+    db: Arc<Db>,
+    conn_id: ConnectionId,
+    process_name: String,
+    pid: u32,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = db.open()?;
+        conn.facet_execute_ref(
+            "UPDATE connections
+             SET process_name = :process_name,
+                 pid = :pid,
+                 disconnected_at_ns = NULL,
+                 reconnect_count = reconnect_count + 1
+             WHERE conn_id = :conn_id",
+            &ConnectionResumedParams {
+                conn_id,
+                process_name,
+                pid,
+            },
+        )
+        .map_err(|error| format!("resume connection: {error}"))?;
+        Ok::<(), String>(())
+    })
+    .await
+    .map_err(|error| format!("join sqlite: {error}"))?
+}
+
 pub async fn persist_connection_closed(db: Arc<Db>, conn_id: ConnectionId) -> Result<(), String> {
     tokio::task::spawn_blocking(move || {
         let conn = db.open()?;
@@ -1,6 +1,9 @@
 use facet::Facet;
 use facet_value::Value;
-use moire_types::{ProcessId, ScopeEntityLink, SqlResponse};
+use moire_types::{
+    Edge, Entity, Event, ProcessId, ProcessSnapshotView, Scope, ScopeEntityLink, Snapshot,
+    SqlResponse,
+};
 use rusqlite_facet::ConnectionFacetExt;
 
 use crate::db::Db;
@@ -22,6 +25,115 @@ pub fn fetch_scope_entity_links_blocking(
     .map_err(|error| format!("query scope_entity_links: {error}"))
 }
 
+#[derive(Facet)]
+struct NoParams;
+
+#[derive(Facet)]
+struct ProcessIdParams {
+    process_id: ProcessId,
+}
+
+#[derive(Facet)]
+struct StoredProcessRow {
+    process_id: ProcessId,
+    process_name: String,
+    pid: u32,
+}
+
+#[derive(Facet)]
+struct JsonRow {
+    json: String,
+}
+
+fn load_json_rows<T: Facet<'static>>(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    process_id: &ProcessId,
+    what: &str,
+) -> Result<Vec<T>, String> {
+    conn.facet_query_ref::<JsonRow, _>(
+        sql,
+        &ProcessIdParams {
+            process_id: process_id.clone(),
+        },
+    )
+    .map_err(|error| format!("query {what}: {error}"))?
+    .into_iter()
+    .map(|row| {
+        facet_json::from_str::<T>(&row.json).map_err(|error| format!("decode {what}: {error}"))
+    })
+    .collect()
+}
+
+/// Rebuilds each known process's last state from the persisted change stream, for
+/// serving snapshots without live processes (`moire-web open`).
+// r[impl config.web.open]
+pub fn load_stored_snapshots_blocking(db: &Db) -> Result<Vec<ProcessSnapshotView>, String> {
+    let conn = db.open()?;
+    let processes = conn
+        .facet_query_ref::<StoredProcessRow, _>(
+            "SELECT c.process_id, c.process_name, c.pid
+             FROM connections c
+             WHERE c.conn_id = (SELECT MAX(conn_id) FROM connections WHERE process_id = c.process_id)
+             ORDER BY c.conn_id",
+            &NoParams,
+        )
+        .map_err(|error| format!("query stored processes: {error}"))?;
+
+    let mut views = Vec::with_capacity(processes.len());
+    for process in processes {
+        let process_id = process.process_id;
+        let entities: Vec<Entity> = load_json_rows(
+            &conn,
+            "SELECT entity_json AS json FROM entities WHERE process_id = :process_id",
+            &process_id,
+            "entities",
+        )?;
+        let scopes: Vec<Scope> = load_json_rows(
+            &conn,
+            "SELECT scope_json AS json FROM scopes WHERE process_id = :process_id",
+            &process_id,
+            "scopes",
+        )?;
+        let edges: Vec<Edge> = load_json_rows(
+            &conn,
+            "SELECT edge_json AS json FROM edges WHERE process_id = :process_id",
+            &process_id,
+            "edges",
+        )?;
+        let events: Vec<Event> = load_json_rows(
+            &conn,
+            "SELECT event_json AS json FROM events WHERE process_id = :process_id ORDER BY seq_no",
+            &process_id,
+            "events",
+        )?;
+        // The newest timestamp the process reported stands in for "now".
+        let ptime_now_ms = entities
+            .iter()
+            .flat_map(|entity| [Some(entity.birth), entity.removed_at])
+            .flatten()
+            .chain(events.iter().map(|event| event.at))
+            .map(|ptime| ptime.as_millis())
+            .max()
+            .unwrap_or(0);
+        let scope_entity_links = fetch_scope_entity_links_blocking(db, process_id.clone())?;
+        views.push(ProcessSnapshotView {
+            process_id,
+            process_name: process.process_name,
+            pid: process.pid,
+            ptime_now_ms,
+            snapshot: Snapshot {
+                entities,
+                scopes,
+                edges,
+                events,
+            },
+            scope_entity_links,
+        });
+    }
+    Ok(views)
+}
+
 pub fn sql_query_blocking(db: &Db, sql: &str) -> Result<SqlResponse, String> {
     let sql = sql.trim();
     if sql.is_empty() {
@@ -252,8 +364,25 @@ fn named_query_sql(name: &str, limit: u32) -> Result<String, String> {
              order by e.updated_at_ns asc \
              limit {limit}"
         )),
+        "wait-hotspots" => Ok(format!(
+            "select \
+             e.process_id as process_id, \
+             c.process_name, \
+             e.entity_id, \
+             json_extract(e.entity_json, '$.name') as entity_name, \
+             json_extract(e.entity_json, '$.wait_stats.count') as wait_count, \
+             json_extract(e.entity_json, '$.wait_stats.total_ns') as wait_total_ns, \
+             json_extract(e.entity_json, '$.wait_stats.max_ns') as wait_max_ns, \
+             json_extract(e.entity_json, '$.wait_stats.total_ns') / json_extract(e.entity_json, '$.wait_stats.count') as wait_mean_ns, \
+             json_extract(e.entity_json, '$.wait_stats.buckets') as wait_buckets \
+             from entities e \
+             left join connections c on c.process_id = e.process_id \
+             where json_extract(e.entity_json, '$.wait_stats') is not null \
+             order by wait_total_ns desc \
+             limit {limit}"
+        )),
         _ => Err(format!(
-            "unknown query pack: {name}. expected one of: blockers, blocked-senders, blocked-receivers, stalled-sends, channel-pressure, channel-health, scope-membership, missing-scope-links, stale-blockers"
+            "unknown query pack: {name}. expected one of: blockers, blocked-senders, blocked-receivers, stalled-sends, channel-pressure, channel-health, scope-membership, missing-scope-links, stale-blockers, wait-hotspots"
         )),
     }
 }
@@ -5,7 +5,7 @@ use rusqlite_facet::ConnectionFacetExt;
 
 use crate::db::Db;
 
-const DB_SCHEMA_VERSION: i64 = 6;
+const DB_SCHEMA_VERSION: i64 = 7;
 
 #[derive(Facet)]
 struct NoParams;
@@ -20,18 +20,34 @@ struct MaxConnIdRow {
     max_conn_id: i64,
 }
 
+fn read_user_version(conn: &Connection) -> Result<i64, String> {
+    conn.facet_query_one_ref::<UserVersionRow, _>(
+        "SELECT user_version AS user_version FROM pragma_user_version",
+        &NoParams,
+    )
+    .map(|row| row.user_version)
+    .map_err(|error| format!("read sqlite user_version: {error}"))
+}
+
+/// Fails unless `db` was written with the current schema. Unlike [`init_sqlite`],
+/// this never resets an older database, so it is safe on someone else's file.
+pub fn check_sqlite_schema(db: &Db) -> Result<(), String> {
+    let user_version = read_user_version(&db.open()?)?;
+    if user_version != DB_SCHEMA_VERSION {
+        return Err(format!(
+            "database schema version {} does not match supported {}",
+            user_version, DB_SCHEMA_VERSION
+        ));
+    }
+    Ok(())
+}
+
 pub fn init_sqlite(db: &Db) -> Result<(), String> {
     let conn = db.open()?;
     conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
         .map_err(|error| format!("init sqlite pragmas: {error}"))?;
 
-    let user_version = conn
-        .facet_query_one_ref::<UserVersionRow, _>(
-            "SELECT user_version AS user_version FROM pragma_user_version",
-            &NoParams,
-        )
-        .map_err(|error| format!("read sqlite user_version: {error}"))?
-        .user_version;
+    let user_version = read_user_version(&conn)?;
 
     if user_version > DB_SCHEMA_VERSION {
         return Err(format!(
@@ -105,7 +121,8 @@ fn managed_schema_sql() -> &'static str {
         process_name TEXT NOT NULL,
         pid INTEGER NOT NULL,
         connected_at_ns INTEGER NOT NULL,
-        disconnected_at_ns INTEGER
+        disconnected_at_ns INTEGER,
+        reconnect_count INTEGER NOT NULL DEFAULT 0
     );
     CREATE INDEX IF NOT EXISTS idx_connections_process_id
         ON connections (process_id);
@@ -1,10 +1,13 @@
 pub mod api;
 pub mod app;
+pub mod auth;
 pub mod db;
 pub mod mcp;
+pub mod offline;
 pub mod proxy;
 pub mod recording;
 pub mod snapshot;
 pub mod symbolication;
 pub mod tcp;
+pub mod tls;
 pub mod util;
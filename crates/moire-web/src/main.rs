@@ -1,15 +1,23 @@
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 
 use facet::Facet;
 use figue as args;
-use moire_types::{CutStatusResponse, QueryRequest, SqlRequest, TriggerCutResponse};
+use moire_types::{
+    CutStatusResponse, KindDetailSetting, ProcessId, QueryRequest, RuntimeControlRequest,
+    RuntimeSettingChange, SqlRequest, TriggerCutResponse,
+};
 use moire_web::app::{AppState, DevProxyState, build_router};
+use moire_web::auth::AuthToken;
 use moire_web::db::{Db, init_sqlite, load_next_connection_id};
 use moire_web::mcp::run_mcp_server;
+use moire_web::offline::{
+    OpenedFormat, ScratchDbFiles, detect_opened_format, load_opened_file, prepare_offline_db,
+};
 use moire_web::proxy::{DEFAULT_VITE_ADDR, start_vite_dev_server};
 use moire_web::tcp::run_tcp_acceptor;
+use moire_web::tls::{serve_router, tls_acceptor_from_env, tls_client_config_from_env};
 use tokio::net::TcpListener;
 use tracing::{error, info};
 
@@ -21,6 +29,25 @@ struct ServerCli {
     dev: bool,
 }
 
+#[derive(Facet, Debug)]
+struct OpenCli {
+    #[facet(flatten)]
+    builtins: args::FigueBuiltins,
+    #[facet(args::subcommand)]
+    command: OpenCommand,
+}
+
+#[derive(Facet, Debug)]
+#[repr(u8)]
+enum OpenCommand {
+    Open {
+        #[facet(args::named, default)]
+        dev: bool,
+        #[facet(args::positional)]
+        file: String,
+    },
+}
+
 #[derive(Facet, Debug)]
 struct ClientCli {
     #[facet(flatten)]
@@ -58,6 +85,32 @@ enum ClientCommand {
         #[facet(args::named, default)]
         url: Option<String>,
     },
+    Replay {
+        #[facet(args::named, default)]
+        url: Option<String>,
+        #[facet(args::positional)]
+        file: String,
+    },
+    Control {
+        #[facet(args::named, default)]
+        url: Option<String>,
+        #[facet(args::named, default)]
+        process_id: Option<String>,
+        #[facet(args::named, default)]
+        push_interval_ms: Option<u64>,
+        #[facet(args::named, default)]
+        backtrace_capture: Option<String>,
+        #[facet(args::named, default)]
+        max_events: Option<u32>,
+        #[facet(args::named, default)]
+        detail_on: Option<String>,
+        #[facet(args::named, default)]
+        detail_off: Option<String>,
+        #[facet(args::named, default)]
+        long_poll_threshold_ms: Option<u64>,
+        #[facet(args::named, default)]
+        send_provenance: Option<String>,
+    },
 }
 
 const REAPER_PIPE_FD_ENV: &str = "MOIRE_REAPER_PIPE_FD";
@@ -103,7 +156,21 @@ fn main() {
         .build()
         .expect("failed to build tokio runtime")
         .block_on(async {
-            if let Err(err) = run_server().await {
+            let result = if cli_args.first().map(String::as_str) == Some("open") {
+                match parse_open_cli() {
+                    Ok(OpenCli {
+                        command: OpenCommand::Open { dev, file },
+                        ..
+                    }) => run_server(dev, Some(PathBuf::from(file))).await,
+                    Err(err) => Err(err),
+                }
+            } else {
+                match parse_server_cli() {
+                    Ok(cli) => run_server(cli.dev, None).await,
+                    Err(err) => Err(err),
+                }
+            };
+            if let Err(err) = result {
                 eprintln!("{err}");
                 std::process::exit(1);
             }
@@ -111,7 +178,10 @@ fn main() {
 }
 
 fn is_client_command(value: &str) -> bool {
-    matches!(value, "cut" | "sql" | "query" | "snapshot")
+    matches!(
+        value,
+        "cut" | "sql" | "query" | "snapshot" | "replay" | "control"
+    )
 }
 
 #[cfg(unix)]
@@ -134,9 +204,9 @@ fn reaper_main(pipe_fd: libc::c_int, pgid: libc::pid_t) {
     }
 }
 
-async fn run_server() -> Result<(), String> {
-    let cli = parse_server_cli()?;
-
+/// Runs the server. With `open`, it serves that file read-only instead of
+/// accepting live processes.
+async fn run_server(dev: bool, open: Option<PathBuf>) -> Result<(), String> {
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
@@ -155,14 +225,35 @@ async fn run_server() -> Result<(), String> {
     // r[impl config.web.db-path]
     let db_path =
         PathBuf::from(std::env::var("MOIRE_DB").unwrap_or_else(|_| "moire-web.sqlite".into()));
-    let db = Db::new(db_path);
+    // r[impl config.web.open]
+    let opened: Option<(PathBuf, OpenedFormat)> = match open {
+        Some(file) => {
+            let format = detect_opened_format(&file)?;
+            Some((file, format))
+        }
+        None => None,
+    };
+    let db = match &opened {
+        Some((file, format)) => prepare_offline_db(file, *format)?,
+        None => Db::new(db_path),
+    };
+    let _scratch_files = opened.as_ref().map(|_| ScratchDbFiles::new(&db));
     init_sqlite(&db).map_err(|e| format!("failed to init sqlite at {:?}: {e}", db.path()))?;
     let next_conn_id = load_next_connection_id(&db)
         .map_err(|e| format!("failed to load next connection id at {:?}: {e}", db.path()))?;
+    // r[impl config.web.token]
+    let auth_token = AuthToken::from_env();
+    // r[impl config.web.tls]
+    let tls = tls_acceptor_from_env()?;
+    info!(
+        token_required = auth_token.is_some(),
+        tls = tls.is_some(),
+        "moire-web listener security"
+    );
 
     let mut dev_vite_child = None;
     let mut frontend_dist = None;
-    let dev_proxy = if cli.dev {
+    let dev_proxy = if dev {
         let child = start_vite_dev_server(&vite_addr).await?;
         info!(vite_addr = %vite_addr, "moire-web --dev launched Vite");
         dev_vite_child = Some(child);
@@ -176,17 +267,40 @@ async fn run_server() -> Result<(), String> {
         None
     };
 
-    let state = AppState::new(db, next_conn_id, dev_proxy, frontend_dist.clone());
+    let token_required = auth_token.is_some();
+    let mut state = AppState::new(
+        db,
+        next_conn_id,
+        dev_proxy,
+        frontend_dist.clone(),
+        auth_token,
+    );
 
-    let tcp_listener = TcpListener::bind(&tcp_addr)
-        .await
-        .map_err(|e| format!("failed to bind TCP on {tcp_addr}: {e}"))?;
-    info!(%tcp_addr, %next_conn_id, "moire-web TCP ingest listener ready");
+    let tcp_listener = match &opened {
+        Some((file, format)) => {
+            state.offline = true;
+            load_opened_file(&state, file, *format).await?;
+            info!(
+                file = %file.display(),
+                format = format.as_str(),
+                working_db = %state.db.path().display(),
+                "moire-web opened file read-only; no ingest listener"
+            );
+            None
+        }
+        None => {
+            let tcp_listener = TcpListener::bind(&tcp_addr)
+                .await
+                .map_err(|e| format!("failed to bind TCP on {tcp_addr}: {e}"))?;
+            info!(%tcp_addr, %next_conn_id, "moire-web TCP ingest listener ready");
+            Some(tcp_listener)
+        }
+    };
 
     let http_listener = TcpListener::bind(&http_addr)
         .await
         .map_err(|e| format!("failed to bind HTTP on {http_addr}: {e}"))?;
-    if cli.dev {
+    if dev {
         info!(%http_addr, vite_addr = %vite_addr, "moire-web HTTP API + Vite proxy ready");
     } else {
         let dist = frontend_dist
@@ -204,23 +318,34 @@ async fn run_server() -> Result<(), String> {
     info!(%mcp_addr, "moire-web MCP listener ready");
     print_startup_hints(
         &http_addr,
-        &tcp_addr,
+        tcp_listener.as_ref().map(|_| tcp_addr.as_str()),
         &mcp_addr,
-        if cli.dev { Some(&vite_addr) } else { None },
+        if dev { Some(&vite_addr) } else { None },
         frontend_dist.as_deref(),
+        tls.is_some(),
+        token_required,
     );
 
     let app = build_router(state.clone());
 
     let _dev_vite_child = dev_vite_child;
+    let ingest = async {
+        match tcp_listener {
+            Some(listener) => run_tcp_acceptor(listener, tls.clone(), state.clone()).await,
+            None => std::future::pending().await,
+        }
+    };
     tokio::select! {
-        _ = run_tcp_acceptor(tcp_listener, state.clone()) => {}
-        result = axum::serve(http_listener, app) => {
+        _ = ingest => {}
+        _ = tokio::signal::ctrl_c() => {
+            info!("moire-web shutting down");
+        }
+        result = serve_router(http_listener, tls.clone(), app) => {
             if let Err(e) = result {
                 error!(%e, "HTTP server error");
             }
         }
-        result = run_mcp_server(mcp_listener, state.clone()) => {
+        result = run_mcp_server(mcp_listener, tls.clone(), state.clone()) => {
             if let Err(e) = result {
                 error!(%e, "MCP server error");
             }
@@ -246,6 +371,23 @@ fn parse_server_cli() -> Result<ServerCli, String> {
     Ok(cli.value)
 }
 
+fn parse_open_cli() -> Result<OpenCli, String> {
+    let figue_config = args::builder::<OpenCli>()
+        .map_err(|e| format!("failed to build CLI schema: {e}"))?
+        .cli(|cli| cli.strict())
+        .help(|h| {
+            h.program_name("moire")
+                .description("Serve a moire-web database, wire trace or recording export read-only")
+                .version(option_env!("CARGO_PKG_VERSION").unwrap_or("dev"))
+        })
+        .build();
+    let cli = args::Driver::new(figue_config)
+        .run()
+        .into_result()
+        .map_err(|e| e.to_string())?;
+    Ok(cli.value)
+}
+
 fn run_client() -> Result<(), String> {
     let cli = parse_client_cli()?;
     match cli.command {
@@ -257,6 +399,50 @@ fn run_client() -> Result<(), String> {
         ClientCommand::Sql { url, query } => run_sql(url, query),
         ClientCommand::Query { url, name, limit } => run_query_pack(url, name, limit),
         ClientCommand::Snapshot { url } => run_snapshot(url),
+        ClientCommand::Replay { url, file } => run_replay(url, file),
+        ClientCommand::Control {
+            url,
+            process_id,
+            push_interval_ms,
+            backtrace_capture,
+            max_events,
+            detail_on,
+            detail_off,
+            long_poll_threshold_ms,
+            send_provenance,
+        } => {
+            let mut changes = Vec::new();
+            if let Some(interval_ms) = push_interval_ms {
+                changes.push(RuntimeSettingChange::PushIntervalMs(interval_ms));
+            }
+            if let Some(value) = backtrace_capture {
+                changes.push(RuntimeSettingChange::BacktraceCapture(parse_on_off(
+                    &value,
+                )?));
+            }
+            if let Some(max_events) = max_events {
+                changes.push(RuntimeSettingChange::MaxEvents(max_events));
+            }
+            if let Some(kind) = detail_on {
+                changes.push(RuntimeSettingChange::KindDetail(KindDetailSetting {
+                    kind,
+                    enabled: true,
+                }));
+            }
+            if let Some(kind) = detail_off {
+                changes.push(RuntimeSettingChange::KindDetail(KindDetailSetting {
+                    kind,
+                    enabled: false,
+                }));
+            }
+            if let Some(threshold_ms) = long_poll_threshold_ms {
+                changes.push(RuntimeSettingChange::LongPollThresholdMs(threshold_ms));
+            }
+            if let Some(value) = send_provenance {
+                changes.push(RuntimeSettingChange::SendProvenance(parse_on_off(&value)?));
+            }
+            run_control(url, process_id, changes)
+        }
     }
 }
 
@@ -266,7 +452,9 @@ fn parse_client_cli() -> Result<ClientCli, String> {
         .cli(|cli| cli.strict())
         .help(|h| {
             h.program_name("moire")
-                .description("CLI for moire-web cuts and graph queries")
+                .description(
+                    "CLI for moire-web cuts, graph queries, wire trace replay and runtime control",
+                )
                 .version(option_env!("CARGO_PKG_VERSION").unwrap_or("dev"))
         })
         .build();
@@ -359,7 +547,7 @@ fn run_snapshot(url: Option<String>) -> Result<(), String> {
     let base_url = base_url.trim_end_matches('/');
     let current_url = format!("{base_url}/api/snapshot/current");
 
-    let response = match ureq::get(&current_url).call() {
+    let response = match authorized(http_agent()?.get(&current_url)).call() {
         Ok(response) => response
             .into_string()
             .map_err(|e| format!("read GET response body: {e}"))?,
@@ -379,8 +567,83 @@ fn run_snapshot(url: Option<String>) -> Result<(), String> {
     Ok(())
 }
 
+fn run_replay(url: Option<String>, file: String) -> Result<(), String> {
+    let base_url = url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    let trace = std::fs::read(&file).map_err(|e| format!("read wire trace {file}: {e}"))?;
+    let url = format!("{}/api/wire-trace/import", base_url.trim_end_matches('/'));
+    let response = authorized(http_agent()?.post(&url))
+        .set("content-type", "application/octet-stream")
+        .send_bytes(&trace)
+        .map_err(|e| format!("POST {url}: {e}"))?
+        .into_string()
+        .map_err(|e| format!("read POST response body: {e}"))?;
+    let pretty = facet_json::to_string_pretty(
+        &facet_json::from_str::<facet_value::Value>(&response)
+            .map_err(|e| format!("decode replay response as json: {e}"))?,
+    )
+    .map_err(|e| format!("pretty replay response: {e}"))?;
+    println!("{pretty}");
+    Ok(())
+}
+
+fn run_control(
+    url: Option<String>,
+    process_id: Option<String>,
+    changes: Vec<RuntimeSettingChange>,
+) -> Result<(), String> {
+    if changes.is_empty() {
+        return Err(String::from(
+            "nothing to change: pass --push-interval-ms, --backtrace-capture, --max-events, --detail-on, --detail-off or --long-poll-threshold-ms",
+        ));
+    }
+    let base_url = url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    let req = RuntimeControlRequest {
+        process_id: process_id.map(ProcessId::new),
+        changes,
+    };
+    let body = facet_json::to_string(&req).map_err(|e| format!("encode control request: {e}"))?;
+    let url = format!("{}/api/control", base_url.trim_end_matches('/'));
+    let response = http_post_json(&url, &body)?;
+    let pretty = facet_json::to_string_pretty(
+        &facet_json::from_str::<facet_value::Value>(&response)
+            .map_err(|e| format!("decode control response as json: {e}"))?,
+    )
+    .map_err(|e| format!("pretty control response: {e}"))?;
+    println!("{pretty}");
+    Ok(())
+}
+
+fn parse_on_off(value: &str) -> Result<bool, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "on" | "true" | "1" => Ok(true),
+        "off" | "false" | "0" => Ok(false),
+        other => Err(format!("expected on/off, got {other:?}")),
+    }
+}
+
+fn http_agent() -> Result<ureq::Agent, String> {
+    static AGENT: OnceLock<Result<ureq::Agent, String>> = OnceLock::new();
+    AGENT
+        .get_or_init(|| {
+            let mut builder = ureq::AgentBuilder::new();
+            if let Some(tls_config) = tls_client_config_from_env()? {
+                builder = builder.tls_config(tls_config);
+            }
+            Ok(builder.build())
+        })
+        .clone()
+}
+
+/// Adds `MOIRE_TOKEN` as a bearer token when the server requires one.
+fn authorized(request: ureq::Request) -> ureq::Request {
+    match AuthToken::from_env() {
+        Some(token) => request.set("authorization", &format!("Bearer {}", token.as_str())),
+        None => request,
+    }
+}
+
 fn http_get_text(url: &str) -> Result<String, String> {
-    let response = ureq::get(url)
+    let response = authorized(http_agent()?.get(url))
         .call()
         .map_err(|e| format!("GET {url}: {e}"))?;
     response
@@ -389,7 +652,7 @@ fn http_get_text(url: &str) -> Result<String, String> {
 }
 
 fn http_post_json(url: &str, body: &str) -> Result<String, String> {
-    let response = ureq::post(url)
+    let response = authorized(http_agent()?.post(url))
         .set("content-type", "application/json")
         .send_string(body)
         .map_err(|e| format!("POST {url}: {e}"))?;
@@ -400,11 +663,19 @@ fn http_post_json(url: &str, body: &str) -> Result<String, String> {
 
 fn print_startup_hints(
     http_addr: &str,
-    tcp_addr: &str,
+    tcp_addr: Option<&str>,
     mcp_addr: &str,
     vite_addr: Option<&str>,
     frontend_dist: Option<&Path>,
+    tls: bool,
+    token_required: bool,
 ) {
+    let scheme = if tls { "https" } else { "http" };
+    let token_query = if token_required {
+        "/?token=$MOIRE_TOKEN"
+    } else {
+        ""
+    };
     let mode = if vite_addr.is_some() {
         "dev proxy"
     } else {
@@ -424,11 +695,29 @@ fn print_startup_hints(
 
     println!("  moire-web ready ({mode})");
     println!();
-    println!("  \x1b[32mOpen in browser: http://{http_addr}\x1b[0m");
-    println!("  MCP endpoint: \x1b[32mhttp://{mcp_addr}/mcp\x1b[0m");
+    println!("  \x1b[32mOpen in browser: {scheme}://{http_addr}{token_query}\x1b[0m");
+    println!("  MCP endpoint: \x1b[32m{scheme}://{mcp_addr}/mcp\x1b[0m");
+    if token_required {
+        println!(
+            "  HTTP and MCP clients must send \x1b[32mAuthorization: Bearer $MOIRE_TOKEN\x1b[0m"
+        );
+    }
     println!();
+    let Some(tcp_addr) = tcp_addr else {
+        println!("  Serving an opened file read-only; no ingest listener.");
+        println!();
+        println!();
+        return;
+    };
     println!("  Connect apps with:");
-    println!("    \x1b[32mMOIRE_DASHBOARD={tcp_addr}\x1b[0m <your-binary>");
+    let mut connect_env = format!("MOIRE_DASHBOARD={tcp_addr}");
+    if token_required {
+        connect_env.push_str(" MOIRE_TOKEN=...");
+    }
+    if tls {
+        connect_env.push_str(" MOIRE_TLS_CA=<ca.pem>");
+    }
+    println!("    \x1b[32m{connect_env}\x1b[0m <your-binary>");
     println!();
     println!();
 }
@@ -6,6 +6,7 @@ use std::time::Duration;
 use async_trait::async_trait;
 use axum::extract::State;
 use axum::http::{HeaderMap, Method, Uri};
+use axum::middleware::from_fn_with_state;
 use axum::response::Response;
 use axum::routing::get;
 use axum::{Extension, Router};
@@ -13,8 +14,9 @@ use facet::Facet;
 use moire_trace_types::{BacktraceId, FrameId};
 use moire_types::{
     BacktraceFrameResolved, BacktraceFrameUnresolved, CutId, EdgeKind, Entity, EntityBody,
-    EntityId, ProcessId, ProcessSnapshotView, SnapshotBacktrace, SnapshotBacktraceFrame,
-    SnapshotCutResponse, TriggerCutResponse,
+    EntityId, Event, EventKind, EventTarget, FutureOutcome, ProcessId, ProcessSnapshotView,
+    QueueLatency, QueuedBy, SnapshotBacktrace, SnapshotBacktraceFrame, SnapshotCutResponse,
+    TriggerCutResponse, WaitStats, WaiterWaitStats, WakeEvent,
 };
 use moire_wire::{ServerMessage, encode_server_message_default};
 use rust_mcp_sdk::id_generator::{FastIdGenerator, UuidGenerator};
@@ -32,16 +34,19 @@ use rust_mcp_sdk::{TransportOptions, tool_box};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map as JsonMap, Value as JsonValue};
 use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
 use tracing::{info, warn};
 
 use crate::api::snapshot::take_snapshot_internal;
 use crate::api::source::lookup_source_text_location_in_db;
 use crate::app::{AppState, CutState, remember_snapshot};
+use crate::auth::require_token;
 use crate::db::persist_cut_request;
 use crate::snapshot::table::{
     is_pending_frame, load_snapshot_backtrace_table, lookup_frame_source_by_raw,
 };
 use crate::symbolication::symbolicate_pending_frames_for_backtraces;
+use crate::tls::serve_router;
 use crate::util::time::now_nanos;
 use moire_source_context::{cut_source_compact, extract_enclosing_fn, extract_target_statement};
 
@@ -129,7 +134,7 @@ pub struct EntityTool {
 
 #[mcp_tool(
     name = "moire_channel_state",
-    description = "Return channel-oriented state for one channel entity or all channels, including waiter counts and source context."
+    description = "Return channel-oriented state for one channel entity or all channels, including waiter counts, queue latency, queued messages by producer and callsite, and source context."
 )]
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ChannelStateTool {
@@ -141,7 +146,7 @@ pub struct ChannelStateTool {
 
 #[mcp_tool(
     name = "moire_task_state",
-    description = "Return future/task-oriented state, including awaiting target, scopes, and source context."
+    description = "Return future/task-oriented state, including awaiting target, outcome, poll time, last waker, spawn ancestry, scopes, and source context."
 )]
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct TaskStateTool {
@@ -348,6 +353,10 @@ struct McpEntityResponse {
     pub entity_name: String,
     pub entity_kind: String,
     pub entity_body_json: String,
+    #[facet(skip_unless_truthy)]
+    pub wait_stats: Option<WaitStats>,
+    #[facet(skip_unless_truthy)]
+    pub waiter_wait_stats: Vec<WaiterWaitStats>,
     pub incoming_wait_edges: Vec<McpChainEdge>,
     pub outgoing_wait_edges: Vec<McpChainEdge>,
     pub scope_ids: Vec<String>,
@@ -375,6 +384,17 @@ struct McpChannelState {
     pub occupancy: Option<u32>,
     pub sender_waiters: u32,
     pub receiver_waiters: u32,
+    /// Sends (on senders) or receives (on receivers) dropped while waiting.
+    pub cancelled: u32,
+    /// Send-to-receive delay, on senders of channels with latency tracking on.
+    #[facet(skip_unless_truthy)]
+    pub latency: Option<QueueLatency>,
+    /// How long the oldest queued message has been waiting.
+    #[facet(skip_unless_truthy)]
+    pub oldest_queued_age_ms: Option<u64>,
+    /// Queued messages grouped by producer and send site, largest first.
+    #[facet(skip_unless_truthy)]
+    pub queued_by: Vec<McpQueuedBy>,
     #[facet(skip_unless_truthy)]
     pub lifecycle_hints: Option<String>,
     #[facet(skip_unless_truthy)]
@@ -400,6 +420,21 @@ struct McpTaskState {
     pub entry_frame_ids: Vec<u64>,
     #[facet(skip_unless_truthy)]
     pub awaiting_on_entity_id: Option<String>,
+    #[facet(skip_unless_truthy)]
+    pub outcome: Option<String>,
+    /// Entity that most recently woke this task, and what it was waiting on.
+    #[facet(skip_unless_truthy)]
+    pub last_woken_by: Option<String>,
+    #[facet(skip_unless_truthy)]
+    pub last_woken_via: Option<String>,
+    /// Wall time spent in `poll`, in total and for the longest single poll.
+    #[facet(skip_unless_truthy)]
+    pub poll_total_ns: Option<u64>,
+    #[facet(skip_unless_truthy)]
+    pub poll_max_ns: Option<u64>,
+    /// Spawner chain, nearest first.
+    #[facet(skip_unless_truthy)]
+    pub ancestry: Vec<McpTaskAncestor>,
     pub scope_ids: Vec<String>,
     #[facet(skip_unless_truthy)]
     pub source: Option<McpSourceContext>,
@@ -407,6 +442,28 @@ struct McpTaskState {
     pub sources: Vec<McpSourceContext>,
 }
 
+#[derive(Facet)]
+struct McpQueuedBy {
+    pub count: u32,
+    #[facet(skip_unless_truthy)]
+    pub producer_id: Option<String>,
+    /// Unset when the producer is no longer in the snapshot.
+    #[facet(skip_unless_truthy)]
+    pub producer_name: Option<String>,
+    pub backtrace_id: u64,
+    /// First application frame of the send site, as `function (file:line)`.
+    #[facet(skip_unless_truthy)]
+    pub callsite: Option<String>,
+}
+
+#[derive(Facet)]
+struct McpTaskAncestor {
+    pub entity_id: String,
+    /// Unset when the spawner is no longer in the snapshot.
+    #[facet(skip_unless_truthy)]
+    pub name: Option<String>,
+}
+
 #[derive(Facet)]
 struct McpSourceContextResponse {
     pub snapshot_id: i64,
@@ -687,6 +744,15 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
                         "Long wait chain roots; waiting_on edges that never clear.",
                     ),
                 },
+                McpHelpEntityKind {
+                    kind: String::from("stream"),
+                    means: String::from(
+                        "A named Stream; pollers wait on it and it waits on what it reads from.",
+                    ),
+                    hang_signal: String::from(
+                        "waiting_on edges into the stream while last_item_at stops advancing.",
+                    ),
+                },
                 McpHelpEntityKind {
                     kind: String::from("mpsc_tx / mpsc_rx"),
                     means: String::from("Bounded/unbounded MPSC channel endpoints."),
@@ -712,12 +778,21 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
                     hang_signal: String::from("rx waiting and tx never reaches send."),
                 },
                 McpHelpEntityKind {
-                    kind: String::from("lock / semaphore / notify / once_cell"),
+                    kind: String::from("lock / semaphore / notify / once_cell / barrier"),
                     means: String::from("Synchronization primitives."),
                     hang_signal: String::from(
                         "Cycles through holders/waiters or no external wake source.",
                     ),
                 },
+                McpHelpEntityKind {
+                    kind: String::from("cancellation_token"),
+                    means: String::from(
+                        "Shutdown signal; cancels edges point from a token to its child tokens.",
+                    ),
+                    hang_signal: String::from(
+                        "cancelled_at set while waiter_count > 0: a waiter has not been polled since cancellation.",
+                    ),
+                },
                 McpHelpEntityKind {
                     kind: String::from("net_* / request / response"),
                     means: String::from("I/O and RPC boundary operations."),
@@ -1185,6 +1260,8 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
             entity_kind: entity_kind_name(&located.1.body).to_owned(),
             entity_body_json: facet_json::to_string(&located.1.body)
                 .map_err(|error| format!("encode entity body json: {error}"))?,
+            wait_stats: located.1.wait_stats.clone(),
+            waiter_wait_stats: located.1.waiter_wait_stats.clone(),
             incoming_wait_edges: incoming,
             outgoing_wait_edges: outgoing,
             scope_ids,
@@ -1203,12 +1280,20 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
             .ensure_symbolication_ready(self.load_snapshot(snapshot_id).await?)
             .await?;
         let (nodes, edges, _adjacency, _indegree) = self.build_wait_graph(&snapshot)?;
+        let backtrace_index = backtrace_index(&snapshot);
+        let frame_catalog = frame_catalog(&snapshot);
         let sources = self
             .load_source_for_nodes(&snapshot, nodes.values())
             .await?;
 
         let mut channels = Vec::new();
         for process in &snapshot.processes {
+            let entities_by_id = process
+                .snapshot
+                .entities
+                .iter()
+                .map(|entity| (entity.id.as_str(), entity))
+                .collect::<HashMap<_, _>>();
             for entity in &process.snapshot.entities {
                 if !is_channel_entity(&entity.body) {
                     continue;
@@ -1222,6 +1307,7 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
                 let (capacity, occupancy, lifecycle_hints, channel_kind) =
                     channel_metrics(&entity.body);
                 let (sender_waiters, receiver_waiters) = count_waiters(&edges, &nodes, entity);
+                let latency = channel_queue_latency(&entity.body);
                 let node_key = compose_node_key(&process.process_id, &entity.id);
                 let node = nodes.get(&node_key);
                 channels.push(McpChannelState {
@@ -1233,6 +1319,39 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
                     occupancy,
                     sender_waiters,
                     receiver_waiters,
+                    cancelled: channel_cancelled_ops(&entity.body),
+                    latency: latency.cloned(),
+                    oldest_queued_age_ms: latency
+                        .and_then(|latency| latency.oldest_queued_at)
+                        .map(|at| process.ptime_now_ms.saturating_sub(at.as_millis())),
+                    queued_by: channel_queued_by(&entity.body)
+                        .iter()
+                        .map(|group| {
+                            let callsite = selected_frames_for_backtrace_id(
+                                group.backtrace.as_u64(),
+                                &backtrace_index,
+                                &frame_catalog,
+                                0,
+                                1,
+                            )
+                            .first()
+                            .and_then(|frame_id| frame_label(*frame_id, &frame_catalog));
+                            McpQueuedBy {
+                                count: group.count,
+                                producer_id: group
+                                    .producer
+                                    .as_ref()
+                                    .map(|id| id.as_str().to_owned()),
+                                producer_name: group
+                                    .producer
+                                    .as_ref()
+                                    .and_then(|id| entities_by_id.get(id.as_str()))
+                                    .map(|producer| producer.name.clone()),
+                                backtrace_id: group.backtrace.as_u64(),
+                                callsite,
+                            }
+                        })
+                        .collect(),
                     lifecycle_hints,
                     source: node.and_then(|n| source_for_node(n, &sources)),
                     sources: node
@@ -1278,6 +1397,12 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
 
         let mut tasks = Vec::new();
         for process in &snapshot.processes {
+            let entities_by_id = process
+                .snapshot
+                .entities
+                .iter()
+                .map(|entity| (entity.id.as_str(), entity))
+                .collect::<HashMap<_, _>>();
             for entity in &process.snapshot.entities {
                 if !is_task_entity(&entity.body) {
                     continue;
@@ -1304,6 +1429,10 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
                     .map(|link| link.scope_id.clone())
                     .collect::<Vec<_>>();
 
+                let last_wake = last_wake_event(&process.snapshot.events, &entity.id);
+                let ancestry = task_ancestry(entity, &entities_by_id);
+                let (poll_total_ns, poll_max_ns) = future_poll_times(&entity.body);
+
                 let node_key = compose_node_key(&process.process_id, &entity.id);
                 let node = nodes.get(&node_key);
                 let entry_frame_ids = selected_frames_for_entity(
@@ -1323,6 +1452,14 @@ Use the returned snapshot_id for all follow-up calls to stay on one coherent cut
                         .map(|frame_id| frame_id.as_u64())
                         .collect(),
                     awaiting_on_entity_id: awaiting,
+                    outcome: future_outcome(&entity.body).map(String::from),
+                    last_woken_by: last_wake.map(|wake| wake.by.as_str().to_owned()),
+                    last_woken_via: last_wake
+                        .and_then(|wake| wake.via.as_ref())
+                        .map(|via| via.as_str().to_owned()),
+                    poll_total_ns,
+                    poll_max_ns,
+                    ancestry,
                     scope_ids,
                     source: node.and_then(|n| source_for_node(n, &sources)),
                     sources: node
@@ -1567,6 +1704,11 @@ Call moire_backtrace first to list frame_ids for a backtrace."
     }
 
     async fn trigger_cut(&self) -> Result<TriggerCutResponse, String> {
+        if self.state.offline {
+            return Err(String::from(
+                "moire-web is serving an opened file read-only; there are no live processes to cut",
+            ));
+        }
         let (cut_id, cut_id_string, now_ns, requested_connections, outbound) = {
             let mut guard = self.state.inner.lock().await;
             let cut_num = guard.next_cut_id;
@@ -2068,10 +2210,15 @@ impl ServerHandler for MoireMcpHandler {
     }
 }
 
-pub async fn run_mcp_server(listener: TcpListener, state: AppState) -> Result<(), String> {
+pub async fn run_mcp_server(
+    listener: TcpListener,
+    tls: Option<TlsAcceptor>,
+    state: AppState,
+) -> Result<(), String> {
     let local_addr = listener
         .local_addr()
         .map_err(|error| format!("resolve mcp listener addr: {error}"))?;
+    let auth_token = state.auth_token.clone();
     let handler = MoireMcpHandler::new(state);
     let app_state = Arc::new(McpAppState {
         session_store: Arc::new(InMemorySessionStore::new()),
@@ -2089,7 +2236,7 @@ pub async fn run_mcp_server(listener: TcpListener, state: AppState) -> Result<()
 
     let http_handler = Arc::new(McpHttpHandler::new(vec![]));
 
-    let app = Router::new()
+    let mut app = Router::new()
         .route(
             DEFAULT_MCP_ENDPOINT,
             get(handle_streamable_http_get)
@@ -2098,6 +2245,10 @@ pub async fn run_mcp_server(listener: TcpListener, state: AppState) -> Result<()
         )
         .with_state(app_state)
         .layer(Extension(http_handler));
+    // r[impl config.web.token]
+    if let Some(token) = auth_token {
+        app = app.layer(from_fn_with_state(token, require_token));
+    }
 
     info!(
         endpoint = %DEFAULT_MCP_ENDPOINT,
@@ -2105,7 +2256,7 @@ pub async fn run_mcp_server(listener: TcpListener, state: AppState) -> Result<()
         "moire-web MCP Streamable HTTP ready"
     );
 
-    axum::serve(listener, app)
+    serve_router(listener, tls, app)
         .await
         .map_err(|error| format!("MCP server failed: {error}"))
 }
@@ -2354,6 +2505,26 @@ fn render_entity_markdown(response: &McpEntityResponse) -> String {
     );
     let _ = writeln!(out, "scope_ids: {}", response.scope_ids.join(", "));
     let _ = writeln!(out, "entity_body: {}", response.entity_body_json);
+    if let Some(stats) = response.wait_stats.as_ref() {
+        let _ = writeln!(
+            out,
+            "waits: count={} total={:.3}ms max={:.3}ms buckets={:?}",
+            stats.count,
+            stats.total_ns as f64 / 1e6,
+            stats.max_ns as f64 / 1e6,
+            stats.buckets
+        );
+    }
+    for waiter in &response.waiter_wait_stats {
+        let _ = writeln!(
+            out,
+            "  by {}: count={} total={:.3}ms max={:.3}ms",
+            waiter.waiter.as_str(),
+            waiter.stats.count,
+            waiter.stats.total_ns as f64 / 1e6,
+            waiter.stats.max_ns as f64 / 1e6,
+        );
+    }
     append_source_set(
         &mut out,
         "sources",
@@ -2411,6 +2582,40 @@ fn render_channel_state_markdown(response: &McpChannelStateResponse) -> String {
             "  waiters: senders={} receivers={}",
             channel.sender_waiters, channel.receiver_waiters
         );
+        if channel.cancelled > 0 {
+            let _ = writeln!(out, "  cancelled while waiting: {}", channel.cancelled);
+        }
+        if let Some(latency) = channel.latency.as_ref()
+            && latency.count > 0
+        {
+            let _ = writeln!(
+                out,
+                "  queue latency: count={} mean={:.3}ms recent={:.3}ms max={:.3}ms",
+                latency.count,
+                latency.total_ns as f64 / latency.count as f64 / 1e6,
+                latency.recent_ns as f64 / 1e6,
+                latency.max_ns as f64 / 1e6
+            );
+        }
+        if let Some(age_ms) = channel.oldest_queued_age_ms {
+            let _ = writeln!(out, "  oldest queued message: {age_ms}ms old");
+        }
+        if !channel.queued_by.is_empty() {
+            let _ = writeln!(out, "  queued by:");
+            for group in &channel.queued_by {
+                let producer = match (group.producer_name.as_ref(), group.producer_id.as_ref()) {
+                    (Some(name), Some(id)) => format!("{name} ({id})"),
+                    (None, Some(id)) => format!("{id} (gone)"),
+                    _ => String::from("no task"),
+                };
+                let callsite = group.callsite.as_deref().unwrap_or("unknown callsite");
+                let _ = writeln!(
+                    out,
+                    "  - {} from {producer} at {callsite} [backtrace {}]",
+                    group.count, group.backtrace_id
+                );
+            }
+        }
         if let Some(hints) = channel.lifecycle_hints.as_ref() {
             let _ = writeln!(out, "  lifecycle: {hints}");
         }
@@ -2451,6 +2656,38 @@ fn render_task_state_markdown(response: &McpTaskStateResponse) -> String {
         if let Some(awaiting) = task.awaiting_on_entity_id.as_ref() {
             let _ = writeln!(out, "  awaiting_on_entity_id: {awaiting}");
         }
+        if let Some(outcome) = task.outcome.as_ref() {
+            let _ = writeln!(out, "  outcome: {outcome}");
+        }
+        if let Some(by) = task.last_woken_by.as_ref() {
+            match task.last_woken_via.as_ref() {
+                Some(via) => {
+                    let _ = writeln!(out, "  last woken by: {by} (via {via})");
+                }
+                None => {
+                    let _ = writeln!(out, "  last woken by: {by}");
+                }
+            }
+        }
+        if let (Some(total_ns), Some(max_ns)) = (task.poll_total_ns, task.poll_max_ns) {
+            let _ = writeln!(
+                out,
+                "  poll time: total={:.3}ms max={:.3}ms",
+                total_ns as f64 / 1e6,
+                max_ns as f64 / 1e6
+            );
+        }
+        if !task.ancestry.is_empty() {
+            let chain = task
+                .ancestry
+                .iter()
+                .map(|ancestor| match ancestor.name.as_ref() {
+                    Some(name) => format!("{name} ({})", ancestor.entity_id),
+                    None => format!("{} (gone)", ancestor.entity_id),
+                })
+                .collect::<Vec<_>>();
+            let _ = writeln!(out, "  spawned by: {}", chain.join(" <- "));
+        }
         let _ = writeln!(out, "  scope_ids: {}", task.scope_ids.join(", "));
         append_source_set(
             &mut out,
@@ -2952,6 +3189,8 @@ fn node_has_external_wake_source(kind: &str) -> bool {
             | "oneshot_rx"
             | "notify"
             | "semaphore"
+            | "barrier"
+            | "cancellation_token"
             | "net_accept"
             | "net_read"
             | "request"
@@ -2999,6 +3238,9 @@ fn entity_kind_name(body: &EntityBody) -> &'static str {
         EntityBody::Semaphore(_) => "semaphore",
         EntityBody::Notify(_) => "notify",
         EntityBody::OnceCell(_) => "once_cell",
+        EntityBody::Barrier(_) => "barrier",
+        EntityBody::CancellationToken(_) => "cancellation_token",
+        EntityBody::Stream(_) => "stream",
         EntityBody::Command(_) => "command",
         EntityBody::FileOp(_) => "file_op",
         EntityBody::NetConnect(_) => "net_connect",
@@ -3030,6 +3272,61 @@ fn is_task_entity(body: &EntityBody) -> bool {
     matches!(body, EntityBody::Future(_))
 }
 
+/// Follows `spawned_by` up from `entity`, stopping at the first spawner that is
+/// no longer in the snapshot.
+fn task_ancestry(entity: &Entity, entities_by_id: &HashMap<&str, &Entity>) -> Vec<McpTaskAncestor> {
+    let mut ancestry = Vec::new();
+    let mut seen = HashSet::from([entity.id.as_str()]);
+    let mut current = entity;
+    while let EntityBody::Future(future) = &current.body
+        && let Some(parent_id) = future.spawned_by.as_ref()
+        && seen.insert(parent_id.as_str())
+    {
+        let parent = entities_by_id.get(parent_id.as_str()).copied();
+        ancestry.push(McpTaskAncestor {
+            entity_id: parent_id.as_str().to_owned(),
+            name: parent.map(|parent| parent.name.clone()),
+        });
+        let Some(parent) = parent else {
+            break;
+        };
+        current = parent;
+    }
+    ancestry
+}
+
+fn future_poll_times(body: &EntityBody) -> (Option<u64>, Option<u64>) {
+    match body {
+        EntityBody::Future(future) => (future.poll_total_ns, future.poll_max_ns),
+        _ => (None, None),
+    }
+}
+
+fn last_wake_event<'a>(events: &'a [Event], entity_id: &EntityId) -> Option<&'a WakeEvent> {
+    events
+        .iter()
+        .filter(|event| matches!(&event.target, EventTarget::Entity(id) if id == entity_id))
+        .filter_map(|event| match &event.kind {
+            EventKind::Woken(wake) => Some((event.at, wake)),
+            _ => None,
+        })
+        .max_by_key(|(at, _)| *at)
+        .map(|(_, wake)| wake)
+}
+
+fn future_outcome(body: &EntityBody) -> Option<&'static str> {
+    let EntityBody::Future(future) = body else {
+        return None;
+    };
+    future.outcome.map(|outcome| match outcome {
+        FutureOutcome::Ok => "ok",
+        FutureOutcome::Error => "error",
+        FutureOutcome::CancelledByDrop => "cancelled_by_drop",
+        FutureOutcome::Aborted => "aborted",
+        FutureOutcome::Panicked => "panicked",
+    })
+}
+
 fn channel_metrics(body: &EntityBody) -> (Option<u32>, Option<u32>, Option<String>, &'static str) {
     match body {
         EntityBody::MpscTx(tx) => (
@@ -3075,6 +3372,46 @@ fn channel_metrics(body: &EntityBody) -> (Option<u32>, Option<u32>, Option<Strin
     }
 }
 
+fn channel_cancelled_ops(body: &EntityBody) -> u32 {
+    match body {
+        EntityBody::MpscTx(tx) => tx.cancelled_sends,
+        EntityBody::MpscRx(rx) => rx.cancelled_recvs,
+        EntityBody::BroadcastRx(rx) => rx.cancelled_recvs,
+        EntityBody::WatchRx(rx) => rx.cancelled_recvs,
+        EntityBody::OneshotRx(rx) => rx.cancelled_recvs,
+        _ => 0,
+    }
+}
+
+fn channel_queued_by(body: &EntityBody) -> &[QueuedBy] {
+    match body {
+        EntityBody::MpscTx(tx) => &tx.queued_by,
+        EntityBody::BroadcastTx(tx) => &tx.queued_by,
+        _ => &[],
+    }
+}
+
+fn frame_label(
+    frame_id: FrameId,
+    frame_catalog: &HashMap<u64, &SnapshotBacktraceFrame>,
+) -> Option<String> {
+    let SnapshotBacktraceFrame::Resolved(frame) = frame_catalog.get(&frame_id.as_u64())? else {
+        return None;
+    };
+    Some(match frame.line {
+        Some(line) => format!("{} ({}:{line})", frame.function_name, frame.source_file),
+        None => format!("{} ({})", frame.function_name, frame.source_file),
+    })
+}
+
+fn channel_queue_latency(body: &EntityBody) -> Option<&QueueLatency> {
+    match body {
+        EntityBody::MpscTx(tx) => tx.latency.as_ref(),
+        EntityBody::BroadcastTx(tx) => tx.latency.as_ref(),
+        _ => None,
+    }
+}
+
 fn snapshot_entity_keys(snapshot: &SnapshotCutResponse) -> HashSet<String> {
     let mut out = HashSet::new();
     for process in &snapshot.processes {
@@ -3367,6 +3704,7 @@ fn convert_response(response: axum::http::Response<GenericBody>) -> Response {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use moire_types::FutureEntity;
 
     #[test]
     fn strongly_connected_components_finds_cycle_cluster() {
@@ -3397,6 +3735,38 @@ mod tests {
         assert_eq!(components[2], vec![String::from("e")]);
     }
 
+    #[test]
+    fn task_ancestry_follows_spawners_until_one_is_gone() {
+        let backtrace = BacktraceId::next().expect("backtrace id");
+        let spawned = |name: &str, parent: Option<&Entity>| {
+            Entity::new(
+                backtrace,
+                name,
+                EntityBody::Future(FutureEntity {
+                    spawned_by: parent.map(|parent| parent.id.clone()),
+                    ..FutureEntity::default()
+                }),
+            )
+        };
+        let gone = spawned("gone", None);
+        let root = spawned("root", Some(&gone));
+        let child = spawned("child", Some(&root));
+        let grandchild = spawned("grandchild", Some(&child));
+        let entities_by_id = [&root, &child, &grandchild]
+            .into_iter()
+            .map(|entity| (entity.id.as_str(), entity))
+            .collect::<HashMap<_, _>>();
+
+        let ancestry = task_ancestry(&grandchild, &entities_by_id);
+        let names = ancestry
+            .iter()
+            .map(|ancestor| ancestor.name.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![Some("child"), Some("root"), None]);
+        assert_eq!(ancestry[2].entity_id, gone.id.as_str());
+        assert!(task_ancestry(&gone, &entities_by_id).is_empty());
+    }
+
     #[test]
     fn external_wake_source_kind_classification_is_strict() {
         assert!(node_has_external_wake_source("mpsc_rx"));
@@ -0,0 +1,255 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use moire_types::RecordingImportBody;
+use tracing::info;
+
+use crate::api::recording::import_recording;
+use crate::api::snapshot::take_snapshot_internal;
+use crate::app::AppState;
+use crate::db::{Db, check_sqlite_schema};
+use crate::tcp::replay_wire_trace;
+
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// What kind of file `moire-web open` was given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenedFormat {
+    /// A `moire-web.sqlite` database copied from another machine.
+    Sqlite,
+    /// A wire trace recorded with `MOIRE_DASHBOARD=file:...`.
+    WireTrace,
+    /// A recording export (`RecordingImportBody` JSON).
+    Recording,
+}
+
+impl OpenedFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sqlite => "sqlite",
+            Self::WireTrace => "wire trace",
+            Self::Recording => "recording export",
+        }
+    }
+}
+
+/// Tells the formats apart by their first bytes.
+pub fn detect_opened_format(path: &Path) -> Result<OpenedFormat, String> {
+    let mut head = Vec::with_capacity(SQLITE_HEADER.len());
+    std::fs::File::open(path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?
+        .take(SQLITE_HEADER.len() as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| format!("read {}: {e}", path.display()))?;
+    if head.starts_with(SQLITE_HEADER) {
+        return Ok(OpenedFormat::Sqlite);
+    }
+    if head.starts_with(&moire_wire::encode_protocol_magic()) {
+        return Ok(OpenedFormat::WireTrace);
+    }
+    if head
+        .iter()
+        .find(|byte| !byte.is_ascii_whitespace())
+        .is_some_and(|byte| *byte == b'{')
+    {
+        return Ok(OpenedFormat::Recording);
+    }
+    Err(format!(
+        "{} is not a moire-web database, wire trace or recording export",
+        path.display()
+    ))
+}
+
+/// The database to serve an opened file from. The opened file itself is never
+/// written: a database is copied to a scratch location first, and the other
+/// formats are loaded into a fresh scratch database.
+// r[impl config.web.open]
+pub fn prepare_offline_db(path: &Path, format: OpenedFormat) -> Result<Db, String> {
+    let work_path =
+        std::env::temp_dir().join(format!("moire-web-open-{}.sqlite", std::process::id()));
+    for stale in sqlite_files(&work_path) {
+        let _ = std::fs::remove_file(stale);
+    }
+    if format == OpenedFormat::Sqlite {
+        for (from, to) in sqlite_files(path).into_iter().zip(sqlite_files(&work_path)) {
+            if from.exists() {
+                std::fs::copy(&from, &to)
+                    .map_err(|e| format!("copy {} to {}: {e}", from.display(), to.display()))?;
+            }
+        }
+        let db = Db::new(work_path);
+        check_sqlite_schema(&db).map_err(|e| format!("{}: {e}", path.display()))?;
+        return Ok(db);
+    }
+    Ok(Db::new(work_path))
+}
+
+/// Deletes the scratch database of an opened file when dropped.
+pub struct ScratchDbFiles {
+    path: PathBuf,
+}
+
+impl ScratchDbFiles {
+    pub fn new(db: &Db) -> Self {
+        Self {
+            path: db.path().to_path_buf(),
+        }
+    }
+}
+
+impl Drop for ScratchDbFiles {
+    fn drop(&mut self) {
+        for file in sqlite_files(&self.path) {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
+
+/// The database file and its write-ahead log companions.
+fn sqlite_files(path: &Path) -> [PathBuf; 3] {
+    let with_suffix = |suffix: &str| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+    [path.to_path_buf(), with_suffix("-wal"), with_suffix("-shm")]
+}
+
+/// Loads an opened wire trace or recording export into `state`, then takes a
+/// first snapshot so the UI and MCP tools have something to show right away.
+pub async fn load_opened_file(
+    state: &AppState,
+    path: &Path,
+    format: OpenedFormat,
+) -> Result<(), String> {
+    match format {
+        OpenedFormat::Sqlite => {}
+        OpenedFormat::WireTrace => {
+            let file = tokio::fs::File::open(path)
+                .await
+                .map_err(|e| format!("open {}: {e}", path.display()))?;
+            let imported = replay_wire_trace(&mut tokio::io::BufReader::new(file), state)
+                .await
+                .map_err(|e| format!("replay {}: {e}", path.display()))?;
+            info!(
+                process_id = %imported.process_id.as_str(),
+                process_name = %imported.process_name,
+                truncated = imported.truncated,
+                "wire trace loaded"
+            );
+        }
+        OpenedFormat::Recording => {
+            let body = tokio::fs::read(path)
+                .await
+                .map_err(|e| format!("read {}: {e}", path.display()))?;
+            let import: RecordingImportBody = facet_json::from_slice(&body)
+                .map_err(|e| format!("decode recording export {}: {e}", path.display()))?;
+            let current = import_recording(state, import).await?;
+            info!(
+                frames = current.session.map_or(0, |session| session.frame_count),
+                "recording export loaded"
+            );
+            return Ok(());
+        }
+    }
+    let snapshot = take_snapshot_internal(state).await;
+    info!(
+        processes = snapshot.processes.len(),
+        "initial snapshot built from stored state"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opened_formats_are_told_apart_by_their_first_bytes() {
+        let dir = std::env::temp_dir();
+        let cases: [(&str, Vec<u8>, Option<OpenedFormat>); 4] = [
+            (
+                "db",
+                b"SQLite format 3\0rest-of-header".to_vec(),
+                Some(OpenedFormat::Sqlite),
+            ),
+            (
+                "trace",
+                moire_wire::encode_protocol_magic().to_vec(),
+                Some(OpenedFormat::WireTrace),
+            ),
+            (
+                "export",
+                b"\n  {\"version\":1}".to_vec(),
+                Some(OpenedFormat::Recording),
+            ),
+            ("other", b"hello".to_vec(), None),
+        ];
+        for (name, contents, expected) in cases {
+            let path = dir.join(format!(
+                "moire-web-open-format-{}-{name}",
+                std::process::id()
+            ));
+            std::fs::write(&path, contents).expect("write test file");
+            assert_eq!(detect_opened_format(&path).ok(), expected, "{name}");
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn scratch_database_is_deleted_on_drop() {
+        let path = std::env::temp_dir().join(format!(
+            "moire-web-open-scratch-{}.sqlite",
+            std::process::id()
+        ));
+        for file in sqlite_files(&path) {
+            std::fs::write(&file, b"scratch").expect("write scratch file");
+        }
+        drop(ScratchDbFiles::new(&Db::new(path.clone())));
+        assert!(sqlite_files(&path).iter().all(|file| !file.exists()));
+    }
+
+    #[tokio::test]
+    async fn opened_files_reject_writes() {
+        let db_path = std::env::temp_dir().join(format!(
+            "moire-web-open-read-only-{}.sqlite",
+            std::process::id()
+        ));
+        let db = Db::new(db_path.clone());
+        crate::db::init_sqlite(&db).expect("init sqlite");
+        let mut state = AppState::new(db, crate::app::ConnectionId::new(1), None, None, None);
+        state.offline = true;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let base = format!("http://{}", listener.local_addr().expect("local addr"));
+        tokio::spawn(async move {
+            axum::serve(listener, crate::app::build_router(state))
+                .await
+                .expect("serve");
+        });
+
+        let statuses = tokio::task::spawn_blocking(move || {
+            let status = |request: ureq::Request, body: &str| match request.send_string(body) {
+                Ok(response) => response.status(),
+                Err(ureq::Error::Status(status, _)) => status,
+                Err(error) => panic!("request failed: {error}"),
+            };
+            [
+                status(ureq::get(&format!("{base}/health")), ""),
+                status(ureq::post(&format!("{base}/api/cuts")), ""),
+                status(ureq::post(&format!("{base}/api/control")), "{}"),
+                status(ureq::post(&format!("{base}/api/record/start")), "{}"),
+                status(ureq::post(&format!("{base}/api/record/import")), "{}"),
+                status(ureq::post(&format!("{base}/api/wire-trace/import")), ""),
+            ]
+        })
+        .await
+        .expect("requests");
+        assert_eq!(statuses, [200, 409, 409, 409, 409, 409]);
+
+        for file in sqlite_files(&db_path) {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
@@ -502,6 +502,11 @@ fn resolve_frame_symbolication(
             job.module_path
         ));
     }
+    if job.module_path == moire_wire::CAPTURE_DISABLED_MODULE_PATH {
+        return unresolved(String::from(
+            "backtrace capture was disabled when this was created",
+        ));
+    }
 
     let state = match module_cache.entry(job.module_path.clone()) {
         Entry::Occupied(entry) => entry.into_mut(),
@@ -1,27 +1,42 @@
 use std::path::Path as FsPath;
-use std::sync::Arc;
 
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{Notify, mpsc};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
 use tracing::{debug, error, info, warn};
 
 use crate::app::{AppState, ConnectedProcess, ConnectionId};
 use crate::db::{
-    backtrace_frames_for_store, into_stored_module_manifest, persist_backtrace_record,
-    persist_connection_closed, persist_connection_module_manifest, persist_connection_upsert,
-    persist_cut_ack, persist_delta_batch,
+    backtrace_frames_for_store, into_stored_module_manifest, load_session_resume,
+    persist_backtrace_record, persist_connection_closed, persist_connection_module_manifest,
+    persist_connection_resumed, persist_connection_upsert, persist_cut_ack, persist_delta_batch,
+};
+use crate::tls::accept_tls;
+use crate::util::time::now_nanos;
+use moire_types::WireTraceImportResponse;
+use moire_wire::{
+    ClientMessage, ServerMessage, SessionResume, decode_client_message_default,
+    decode_protocol_magic, encode_server_message_default,
 };
-use moire_wire::{ClientMessage, decode_client_message_default, decode_protocol_magic};
 
-pub async fn run_tcp_acceptor(listener: TcpListener, state: AppState) {
+pub async fn run_tcp_acceptor(listener: TcpListener, tls: Option<TlsAcceptor>, state: AppState) {
     loop {
         match listener.accept().await {
             Ok((stream, addr)) => {
                 info!(%addr, "TCP connection accepted");
                 let st = state.clone();
+                let tls = tls.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = handle_conn(stream, st).await {
+                    // r[impl config.web.tls]
+                    let result = match tls {
+                        Some(acceptor) => match accept_tls(&acceptor, stream).await {
+                            Ok(stream) => handle_conn(stream, st).await,
+                            Err(e) => Err(e),
+                        },
+                        None => handle_conn(stream, st).await,
+                    };
+                    if let Err(e) = result {
                         error!(%addr, %e, "connection error");
                     }
                 });
@@ -31,74 +46,142 @@ pub async fn run_tcp_acceptor(listener: TcpListener, state: AppState) {
     }
 }
 
-async fn handle_conn(stream: TcpStream, state: AppState) -> Result<(), String> {
-    let (mut reader, mut writer) = stream.into_split();
+async fn handle_conn<S>(stream: S, state: AppState) -> Result<(), String>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (mut reader, mut writer) = tokio::io::split(stream);
     let (msg_tx, mut msg_rx) = mpsc::channel::<Vec<u8>>(32);
-
-    let conn_id = {
-        let mut guard = state.inner.lock().await;
-        let conn_id = guard.next_conn_id;
-        guard.next_conn_id = conn_id.next();
-        guard.connections.insert(
-            conn_id,
-            ConnectedProcess {
-                process_id: None,
-                process_name: format!("unknown-{conn_id}"),
-                pid: 0,
-                handshake_received: false,
-                module_manifest: Vec::new(),
-                tx: msg_tx,
-            },
-        );
-        conn_id
-    };
+    let mut conn_id = register_connection(&state, msg_tx).await;
 
     let writer_handle = tokio::spawn(async move {
         while let Some(frame) = msg_rx.recv().await {
-            if writer.write_all(&frame).await.is_err() {
+            if writer.write_all(&frame).await.is_err() || writer.flush().await.is_err() {
                 break;
             }
         }
     });
 
-    let read_result = read_messages(conn_id, &mut reader, &state).await;
+    let read_result = read_messages(&mut conn_id, &mut reader, &state, MessageSource::Live).await;
+    close_connection(&state, conn_id).await;
 
-    let to_notify: Vec<Arc<Notify>> = {
-        let mut guard = state.inner.lock().await;
-        guard.connections.remove(&conn_id);
-        for cut in guard.cuts.values_mut() {
-            cut.pending_conn_ids.remove(&conn_id);
-            cut.acks.remove(&conn_id);
-        }
-        guard
-            .pending_snapshots
-            .values_mut()
-            .filter_map(|pending| {
-                if pending.pending_conn_ids.remove(&conn_id) && pending.pending_conn_ids.is_empty()
-                {
-                    Some(pending.notify.clone())
-                } else {
-                    None
-                }
+    writer_handle.abort();
+    read_result.map(|_| ())
+}
+
+/// Replays a wire trace recorded with `MOIRE_DASHBOARD=file:...` or
+/// `MOIRE_RECORD_FILE`, as if the process had been connected live.
+///
+/// The trace goes through the same path as a live connection, so the process,
+/// its module manifest, backtraces and delta batches all end up in the database.
+/// Whoever calls this has already been authenticated, so the handshake in the
+/// trace (which never carries a token) is not checked against `MOIRE_TOKEN`.
+/// Each replay is a connection of its own: it never resumes an earlier session
+/// of the same process.
+// r[impl wire.trace-file]
+pub async fn replay_wire_trace(
+    reader: &mut (impl AsyncRead + Unpin),
+    state: &AppState,
+) -> Result<WireTraceImportResponse, String> {
+    // There is no process to talk back to, so server messages are discarded.
+    let (msg_tx, mut msg_rx) = mpsc::channel::<Vec<u8>>(32);
+    tokio::spawn(async move { while msg_rx.recv().await.is_some() {} });
+    let mut conn_id = register_connection(state, msg_tx).await;
+    let read_result = read_messages(&mut conn_id, reader, state, MessageSource::Replay).await;
+    let process = {
+        let guard = state.inner.lock().await;
+        guard.connections.get(&conn_id).and_then(|conn| {
+            Some(WireTraceImportResponse {
+                conn_id,
+                process_id: conn.process_id.clone()?,
+                process_name: conn.process_name.clone(),
+                pid: conn.pid,
+                truncated: false,
             })
-            .collect()
+        })
     };
+    close_connection(state, conn_id).await;
+    let end = read_result?;
+    info!(conn_id = %conn_id, truncated = end == StreamEnd::Truncated, "wire trace replayed");
+    let mut process = process.ok_or_else(|| String::from("wire trace contains no handshake"))?;
+    process.truncated = end == StreamEnd::Truncated;
+    Ok(process)
+}
+
+async fn register_connection(state: &AppState, tx: mpsc::Sender<Vec<u8>>) -> ConnectionId {
+    let mut guard = state.inner.lock().await;
+    let conn_id = guard.next_conn_id;
+    guard.next_conn_id = conn_id.next();
+    guard.connections.insert(
+        conn_id,
+        ConnectedProcess {
+            process_id: None,
+            process_name: format!("unknown-{conn_id}"),
+            pid: 0,
+            handshake_received: false,
+            module_manifest: Vec::new(),
+            redaction: None,
+            config_errors: Vec::new(),
+            tx,
+        },
+    );
+    conn_id
+}
+
+async fn close_connection(state: &AppState, conn_id: ConnectionId) {
+    let to_notify = state.inner.lock().await.forget_connection(conn_id);
     for notify in to_notify {
         notify.notify_one();
     }
     if let Err(e) = persist_connection_closed(state.db.clone(), conn_id).await {
         warn!(conn_id = %conn_id, %e, "failed to persist connection close");
     }
+}
 
-    writer_handle.abort();
-    read_result
+/// How a stream of client messages ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamEnd {
+    /// EOF between two frames.
+    Clean,
+    /// EOF partway through a frame, as when a process dies mid-write or a
+    /// trace file is cut short. Everything before that frame was applied.
+    Truncated,
+}
+
+/// Reads into `buf` until it is full or the reader hits EOF, returning how
+/// many bytes were read.
+async fn read_until_eof(
+    reader: &mut (impl AsyncRead + Unpin),
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]).await? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
 }
 
+/// Where a stream of client messages comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageSource {
+    /// A process connected over TCP: its handshake is checked against the
+    /// token and may resume the process's earlier session.
+    Live,
+    /// A recorded wire trace, uploaded by someone already authenticated. It
+    /// stays on the connection id it was registered with.
+    Replay,
+}
+
+/// Reads client messages until EOF.
 async fn read_messages(
-    conn_id: ConnectionId,
-    reader: &mut tokio::net::tcp::OwnedReadHalf,
+    current_conn_id: &mut ConnectionId,
+    reader: &mut (impl AsyncRead + Unpin),
     state: &AppState,
-) -> Result<(), String> {
+    source: MessageSource,
+) -> Result<StreamEnd, String> {
     let mut magic = [0u8; 4];
     reader
         .read_exact(&mut magic)
@@ -107,13 +190,20 @@ async fn read_messages(
     decode_protocol_magic(magic).map_err(|e| format!("invalid protocol magic: {e}"))?;
 
     loop {
+        // A resumed handshake can move this connection back to its earlier id.
+        let conn_id = *current_conn_id;
         let mut len_buf = [0u8; 4];
-        if let Err(e) = reader.read_exact(&mut len_buf).await {
-            if e.kind() == std::io::ErrorKind::UnexpectedEof {
+        match read_until_eof(reader, &mut len_buf).await {
+            Ok(0) => {
                 debug!(conn_id = %conn_id, "connection closed (EOF)");
-                return Ok(());
+                return Ok(StreamEnd::Clean);
+            }
+            Ok(4) => {}
+            Ok(_) => {
+                warn!(conn_id = %conn_id, "stream ended inside a frame length");
+                return Ok(StreamEnd::Truncated);
             }
-            return Err(format!("read frame len: {e}"));
+            Err(e) => return Err(format!("read frame len: {e}")),
         }
 
         let payload_len = u32::from_be_bytes(len_buf) as usize;
@@ -122,10 +212,16 @@ async fn read_messages(
         }
 
         let mut payload = vec![0u8; payload_len];
-        reader
-            .read_exact(&mut payload)
+        let read = read_until_eof(reader, &mut payload)
             .await
             .map_err(|e| format!("read frame payload: {e}"))?;
+        if read < payload_len {
+            warn!(
+                conn_id = %conn_id,
+                read, payload_len, "stream ended inside a frame payload"
+            );
+            return Ok(StreamEnd::Truncated);
+        }
 
         let mut framed = Vec::with_capacity(4 + payload_len);
         framed.extend_from_slice(&len_buf);
@@ -133,33 +229,91 @@ async fn read_messages(
         let message = decode_client_message_default(&framed)
             .map_err(|e| format!("decode client message: {e}"))?;
 
+        // r[impl config.web.token]
+        if state.auth_token.is_some()
+            && source == MessageSource::Live
+            && !matches!(
+                message,
+                ClientMessage::Handshake(_) | ClientMessage::Heartbeat(_)
+            )
+        {
+            let authenticated = state
+                .inner
+                .lock()
+                .await
+                .connections
+                .get(&conn_id)
+                .is_some_and(|conn| conn.handshake_received);
+            if !authenticated {
+                return Err(format!(
+                    "protocol violation: message before authenticated handshake on conn {conn_id}"
+                ));
+            }
+        }
+
         match message {
             ClientMessage::Handshake(handshake) => {
                 validate_handshake(&handshake)
                     .map_err(|e| format!("reject handshake for conn {conn_id}: {e}"))?;
+                if source == MessageSource::Live {
+                    check_token(state, handshake.token.as_deref())
+                        .map_err(|e| format!("reject handshake for conn {conn_id}: {e}"))?;
+                }
                 let process_id = handshake.process_id.clone();
                 let process_name = handshake.process_name.to_string();
                 let pid = handshake.pid;
                 let module_manifest_entries = handshake.module_manifest.len();
+                let redaction = handshake.redaction;
+                let config_errors = handshake.config_errors;
                 let stored_manifest = into_stored_module_manifest(handshake.module_manifest);
+                let first_handshake = {
+                    let guard = state.inner.lock().await;
+                    guard
+                        .connections
+                        .get(&conn_id)
+                        .is_some_and(|conn| !conn.handshake_received)
+                };
+                // r[impl wire.session-resume]
+                let resume = if first_handshake && source == MessageSource::Live {
+                    Some(load_session_resume(state.db.clone(), process_id.clone()).await?)
+                } else {
+                    None
+                };
                 let mut guard = state.inner.lock().await;
+                let resumed_conn_id = resume
+                    .as_ref()
+                    .and_then(|resume| resume.previous_conn_id)
+                    .filter(|previous| !guard.connections.contains_key(previous));
+                if let Some(previous) = resumed_conn_id {
+                    guard.rekey_connection(conn_id, previous);
+                    *current_conn_id = previous;
+                }
+                let conn_id = *current_conn_id;
                 if let Some(conn) = guard.connections.get_mut(&conn_id) {
                     conn.process_id = Some(process_id.clone());
                     conn.process_name = process_name.clone();
                     conn.pid = pid;
                     conn.handshake_received = true;
                     conn.module_manifest = stored_manifest.clone();
+                    conn.redaction = redaction.clone();
+                    conn.config_errors = config_errors.clone();
                 }
+                let tx = guard.connections.get(&conn_id).map(|conn| conn.tx.clone());
                 drop(guard);
-                if let Err(e) = persist_connection_upsert(
-                    state.db.clone(),
-                    conn_id,
-                    process_id.clone(),
-                    process_name.clone(),
-                    pid,
-                )
-                .await
-                {
+                let persisted = if resumed_conn_id.is_some() {
+                    persist_connection_resumed(state.db.clone(), conn_id, process_name.clone(), pid)
+                        .await
+                } else {
+                    persist_connection_upsert(
+                        state.db.clone(),
+                        conn_id,
+                        process_id.clone(),
+                        process_name.clone(),
+                        pid,
+                    )
+                    .await
+                };
+                if let Err(e) = persisted {
                     warn!(conn_id = %conn_id, %e, "failed to persist handshake");
                 }
                 if let Err(e) = persist_connection_module_manifest(
@@ -171,11 +325,38 @@ async fn read_messages(
                 {
                     warn!(conn_id = %conn_id, %e, "failed to persist module manifest");
                 }
+                if let Some(resume) = resume {
+                    info!(
+                        conn_id = %conn_id,
+                        reconnect = resumed_conn_id.is_some(),
+                        next_seq_no = resume.next_seq_no.0,
+                        "resuming session"
+                    );
+                    let frame = encode_server_message_default(&ServerMessage::SessionResume(
+                        SessionResume {
+                            next_seq_no: resume.next_seq_no,
+                            last_backtrace_id: resume.last_backtrace_id,
+                        },
+                    ))
+                    .map_err(|e| format!("encode session resume: {e}"))?;
+                    if let Some(tx) = tx
+                        && tx.send(frame).await.is_err()
+                    {
+                        return Err(format!(
+                            "writer closed before session resume on conn {conn_id}"
+                        ));
+                    }
+                }
                 info!(
                     conn_id = %conn_id,
                     process_id = %process_id.as_str(),
-                    process_name, pid, module_manifest_entries, "handshake accepted"
+                    process_name, pid, module_manifest_entries,
+                    redacted = redaction.is_some(),
+                    "handshake accepted"
                 );
+                for error in &config_errors {
+                    warn!(conn_id = %conn_id, process_name, %error, "process ignored a setting");
+                }
             }
             ClientMessage::SnapshotReply(reply) => {
                 info!(
@@ -270,6 +451,61 @@ async fn read_messages(
                     warn!(conn_id = %conn_id, %e, "failed to persist cut ack");
                 }
             }
+            ClientMessage::ControlAck(ack) => {
+                let notify_opt = {
+                    let mut guard = state.inner.lock().await;
+                    if let Some(pending) = guard.pending_controls.get_mut(&ack.control_id) {
+                        if let Some(error) = &ack.error {
+                            warn!(
+                                conn_id = %conn_id,
+                                control_id = ack.control_id,
+                                %error,
+                                "process rejected part of a control request"
+                            );
+                        }
+                        pending.pending_conn_ids.remove(&conn_id);
+                        pending.acks.insert(conn_id, ack);
+                        if pending.pending_conn_ids.is_empty() {
+                            Some(pending.notify.clone())
+                        } else {
+                            None
+                        }
+                    } else {
+                        debug!(
+                            conn_id = %conn_id,
+                            control_id = ack.control_id,
+                            "control ack for unknown id"
+                        );
+                        None
+                    }
+                };
+                if let Some(notify) = notify_opt {
+                    notify.notify_one();
+                }
+            }
+            ClientMessage::Heartbeat(heartbeat) => {
+                if source == MessageSource::Live {
+                    check_token(state, heartbeat.token.as_deref())
+                        .map_err(|e| format!("reject heartbeat for conn {conn_id}: {e}"))?;
+                }
+                let mut guard = state.inner.lock().await;
+                // Heartbeats come in on a side connection of their own; it never
+                // handshakes and must not hold up cuts or snapshots.
+                let is_side_channel = guard
+                    .connections
+                    .get(&conn_id)
+                    .is_some_and(|conn| !conn.handshake_received);
+                let to_notify = if is_side_channel {
+                    guard.forget_connection(conn_id)
+                } else {
+                    Vec::new()
+                };
+                guard.record_heartbeat(heartbeat, now_nanos());
+                drop(guard);
+                for notify in to_notify {
+                    notify.notify_one();
+                }
+            }
             ClientMessage::Error(msg) => {
                 warn!(
                     conn_id = %conn_id,
@@ -328,6 +564,13 @@ async fn read_messages(
     }
 }
 
+fn check_token(state: &AppState, token: Option<&str>) -> Result<(), String> {
+    match &state.auth_token {
+        Some(expected) if !expected.matches(token) => Err(String::from("missing or invalid token")),
+        _ => Ok(()),
+    }
+}
+
 fn validate_handshake(handshake: &moire_wire::Handshake) -> Result<(), String> {
     if handshake.process_id.as_str().trim().is_empty() {
         return Err("process_id must be non-empty".to_string());
@@ -374,3 +617,138 @@ fn validate_handshake(handshake: &moire_wire::Handshake) -> Result<(), String> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthToken;
+    use crate::db::{Db, init_sqlite, load_session_resume};
+    use moire_types::{ProcessId, PullChangesResponse, SeqNo, StreamId};
+    use moire_wire::{Handshake, encode_client_message_default, encode_protocol_magic};
+
+    #[tokio::test]
+    async fn wire_trace_replays_like_a_live_connection() {
+        let db_path = std::env::temp_dir().join(format!(
+            "moire-web-replay-test-{}-{}.sqlite",
+            std::process::id(),
+            now_nanos()
+        ));
+        let db = Db::new(db_path.clone());
+        init_sqlite(&db).expect("init sqlite");
+        let state = AppState::new(
+            db,
+            ConnectionId::new(1),
+            None,
+            None,
+            Some(AuthToken::new("s3cret")),
+        );
+
+        let process_id = ProcessId::new("replayed-process");
+        let mut trace = encode_protocol_magic().to_vec();
+        for message in [
+            ClientMessage::Handshake(Handshake {
+                process_id: process_id.clone(),
+                process_name: String::from("replayed"),
+                pid: 42,
+                args: vec![],
+                env: vec![],
+                module_manifest: vec![],
+                token: None,
+                redaction: None,
+                config_errors: vec![],
+            }),
+            ClientMessage::DeltaBatch(PullChangesResponse {
+                stream_id: StreamId(String::from("replayed-process")),
+                from_seq_no: SeqNo::ZERO,
+                next_seq_no: SeqNo(7),
+                changes: vec![],
+                truncated: false,
+                compacted_before_seq_no: None,
+            }),
+        ] {
+            trace.extend(encode_client_message_default(&message).expect("encode message"));
+        }
+
+        let imported = replay_wire_trace(&mut &trace[..], &state)
+            .await
+            .expect("replay trace");
+        assert_eq!(imported.process_id.as_str(), "replayed-process");
+        assert_eq!(imported.process_name, "replayed");
+        assert_eq!(imported.pid, 42);
+        assert!(state.inner.lock().await.connections.is_empty());
+
+        let resume = load_session_resume(state.db.clone(), process_id)
+            .await
+            .expect("load session resume");
+        assert_eq!(resume.next_seq_no, SeqNo(7));
+        assert_eq!(resume.previous_conn_id, Some(imported.conn_id));
+
+        // Replaying the same trace again is a new connection, not a resume of
+        // the first replay.
+        let again = replay_wire_trace(&mut &trace[..], &state)
+            .await
+            .expect("replay trace again");
+        assert_ne!(again.conn_id, imported.conn_id);
+        let resume = load_session_resume(state.db.clone(), ProcessId::new("replayed-process"))
+            .await
+            .expect("load session resume");
+        assert_eq!(resume.previous_conn_id, Some(again.conn_id));
+
+        let _ = std::fs::remove_file(db_path);
+    }
+
+    #[tokio::test]
+    async fn wire_trace_cut_short_mid_frame_imports_what_came_before() {
+        let db_path = std::env::temp_dir().join(format!(
+            "moire-web-truncated-test-{}-{}.sqlite",
+            std::process::id(),
+            now_nanos()
+        ));
+        let db = Db::new(db_path.clone());
+        init_sqlite(&db).expect("init sqlite");
+        let state = AppState::new(db, ConnectionId::new(1), None, None, None);
+
+        let mut trace = encode_protocol_magic().to_vec();
+        trace.extend(
+            encode_client_message_default(&ClientMessage::Handshake(Handshake {
+                process_id: ProcessId::new("truncated-process"),
+                process_name: String::from("truncated"),
+                pid: 7,
+                args: vec![],
+                env: vec![],
+                module_manifest: vec![],
+                token: None,
+                redaction: None,
+                config_errors: vec![],
+            }))
+            .expect("encode handshake"),
+        );
+        let batch =
+            encode_client_message_default(&ClientMessage::DeltaBatch(PullChangesResponse {
+                stream_id: StreamId(String::from("truncated-process")),
+                from_seq_no: SeqNo::ZERO,
+                next_seq_no: SeqNo(3),
+                changes: vec![],
+                truncated: false,
+                compacted_before_seq_no: None,
+            }))
+            .expect("encode delta batch");
+
+        for cut in [2, batch.len() - 1] {
+            let mut cut_trace = trace.clone();
+            cut_trace.extend_from_slice(&batch[..cut]);
+            let imported = replay_wire_trace(&mut &cut_trace[..], &state)
+                .await
+                .expect("a trace cut short mid-frame still imports");
+            assert!(imported.truncated, "cut after {cut} bytes");
+            assert_eq!(imported.process_name, "truncated");
+        }
+
+        let imported = replay_wire_trace(&mut &trace[..], &state)
+            .await
+            .expect("replay trace");
+        assert!(!imported.truncated);
+
+        let _ = std::fs::remove_file(db_path);
+    }
+}
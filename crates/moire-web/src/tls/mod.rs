@@ -0,0 +1,146 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::serve::Listener;
+use moire_wire::non_empty_env;
+pub use moire_wire::tls_client_config_from_env;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::{self, ServerConfig};
+use tokio_rustls::server::TlsStream;
+use tracing::{debug, error};
+
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const TLS_ACCEPT_BACKLOG: usize = 64;
+
+fn ring_provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+// r[impl config.web.tls]
+pub fn tls_acceptor_from_env() -> Result<Option<TlsAcceptor>, String> {
+    match (
+        non_empty_env("MOIRE_TLS_CERT"),
+        non_empty_env("MOIRE_TLS_KEY"),
+    ) {
+        (None, None) => Ok(None),
+        (Some(cert_path), Some(key_path)) => {
+            load_tls_acceptor(Path::new(&cert_path), Path::new(&key_path)).map(Some)
+        }
+        _ => Err(String::from(
+            "MOIRE_TLS_CERT and MOIRE_TLS_KEY must be set together",
+        )),
+    }
+}
+
+pub fn load_tls_acceptor(cert_path: &Path, key_path: &Path) -> Result<TlsAcceptor, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .map_err(|e| format!("read certificate {}: {e}", cert_path.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("parse certificate {}: {e}", cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(format!(
+            "certificate file {} contains no certificates",
+            cert_path.display()
+        ));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("read private key {}: {e}", key_path.display()))?;
+    let config = ServerConfig::builder_with_provider(ring_provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("tls protocol versions: {e}"))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("tls certificate/key pair: {e}"))?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Finishes the TLS handshake for an accepted connection.
+pub async fn accept_tls(
+    acceptor: &TlsAcceptor,
+    stream: TcpStream,
+) -> Result<TlsStream<TcpStream>, String> {
+    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+        Ok(Ok(stream)) => Ok(stream),
+        Ok(Err(e)) => Err(format!("tls handshake: {e}")),
+        Err(_) => Err(String::from("tls handshake timed out")),
+    }
+}
+
+/// A listener for `axum::serve` that hands out connections once their TLS
+/// handshake is done. Handshakes run concurrently, so one slow client cannot
+/// hold up the others.
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, acceptor: TlsAcceptor) -> std::io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (tx, incoming) = mpsc::channel(TLS_ACCEPT_BACKLOG);
+        tokio::spawn(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        error!(%e, "TLS listener accept failed");
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        continue;
+                    }
+                };
+                if tx.is_closed() {
+                    return;
+                }
+                let acceptor = acceptor.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match accept_tls(&acceptor, stream).await {
+                        Ok(stream) => {
+                            let _ = tx.send((stream, addr)).await;
+                        }
+                        Err(e) => debug!(%addr, %e, "rejected TLS connection"),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            local_addr,
+            incoming,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(accepted) => accepted,
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Serves `app` on `listener`, over TLS when an acceptor is configured.
+pub async fn serve_router(
+    listener: TcpListener,
+    tls: Option<TlsAcceptor>,
+    app: Router,
+) -> std::io::Result<()> {
+    match tls {
+        Some(acceptor) => axum::serve(TlsListener::new(listener, acceptor)?, app).await,
+        None => axum::serve(listener, app).await,
+    }
+}
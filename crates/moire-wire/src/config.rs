@@ -0,0 +1,47 @@
+//! Environment configuration read by both ends of the connection.
+
+/// Reads `name`, trimmed, treating an unset or blank variable as absent.
+pub fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Environment variables that are never sent to the dashboard, whatever the
+/// redaction rules say.
+pub const HANDSHAKE_EXCLUDED_ENV: &[&str] = &["MOIRE_TOKEN"];
+
+/// Client config trusting the PEM bundle in `MOIRE_TLS_CA`, or `None` when
+/// it is unset.
+#[cfg(feature = "tls")]
+pub fn tls_client_config_from_env()
+-> Result<Option<std::sync::Arc<tokio_rustls::rustls::ClientConfig>>, String> {
+    use std::sync::Arc;
+    use tokio_rustls::rustls::pki_types::CertificateDer;
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
+
+    let Some(ca_path) = non_empty_env("MOIRE_TLS_CA") else {
+        return Ok(None);
+    };
+    let mut roots = RootCertStore::empty();
+    let certs = CertificateDer::pem_file_iter(&ca_path)
+        .map_err(|e| format!("read MOIRE_TLS_CA {ca_path}: {e}"))?;
+    for cert in certs {
+        let cert = cert.map_err(|e| format!("parse MOIRE_TLS_CA {ca_path}: {e}"))?;
+        roots
+            .add(cert)
+            .map_err(|e| format!("trust certificate from {ca_path}: {e}"))?;
+    }
+    if roots.is_empty() {
+        return Err(format!("MOIRE_TLS_CA {ca_path} contains no certificates"));
+    }
+    let config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("tls protocol versions: {e}"))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+    Ok(Some(Arc::new(config)))
+}
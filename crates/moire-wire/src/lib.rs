@@ -1,12 +1,22 @@
 use facet::Facet;
+use moire_trace_types::BacktraceId;
 pub use moire_trace_types::{
     BacktraceRecord, FrameKey as BacktraceFrameKey, ModuleId, RelPc, RuntimeBase,
 };
-use moire_types::{CutAck, CutRequest, ProcessId, PullChangesResponse, Snapshot};
+use moire_types::{
+    CutAck, CutRequest, EntityId, ProcessId, PullChangesResponse, RedactionSummary,
+    RuntimeSettingChange, RuntimeSettings, SeqNo, Snapshot,
+};
 use std::fmt;
 
+mod config;
+pub use config::*;
+
 pub const DEFAULT_MAX_FRAME_BYTES: usize = 128 * 1024 * 1024;
 pub const PROTOCOL_MAGIC: u32 = 0x4D4F4952;
+/// Module path of the one-frame placeholder backtrace a process reports for
+/// everything created while backtrace capture is turned off.
+pub const CAPTURE_DISABLED_MODULE_PATH: &str = "<backtrace-capture-disabled>";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FrameCodecError {
@@ -147,6 +157,15 @@ pub struct Handshake {
     pub args: Vec<String>,
     pub env: Vec<String>,
     pub module_manifest: Vec<ModuleManifestEntry>,
+    /// Shared secret from `MOIRE_TOKEN`, checked by servers that require one.
+    #[facet(skip_unless_truthy)]
+    pub token: Option<String>,
+    /// Redaction rules applied to `args`, `env` and entity payloads.
+    #[facet(skip_unless_truthy)]
+    pub redaction: Option<RedactionSummary>,
+    /// Settings the process rejected and ignored, one message each.
+    #[facet(default, skip_unless_truthy)]
+    pub config_errors: Vec<String>,
 }
 
 // r[impl wire.magic]
@@ -165,6 +184,16 @@ pub fn decode_protocol_magic(bytes: [u8; 4]) -> Result<(), WireError> {
     Ok(())
 }
 
+// r[impl wire.session-resume]
+#[derive(Facet)]
+pub struct SessionResume {
+    /// First sequence number the server has not persisted for this process yet.
+    pub next_seq_no: SeqNo,
+    /// Highest backtrace id the server already stored for this process, if any.
+    #[facet(skip_unless_truthy)]
+    pub last_backtrace_id: Option<BacktraceId>,
+}
+
 #[derive(Facet)]
 pub struct SnapshotRequest {
     pub snapshot_id: i64,
@@ -180,6 +209,39 @@ pub struct SnapshotReply {
     pub snapshot: Option<Snapshot>,
 }
 
+// r[impl wire.heartbeat]
+#[derive(Facet)]
+pub struct Heartbeat {
+    pub process_id: ProcessId,
+    /// Process-relative milliseconds at the moment the heartbeat was sent.
+    pub ptime_now_ms: u64,
+    /// Number of liveness probes the instrumented runtime has executed so far.
+    /// Absent until the process has polled its first instrumented future.
+    #[facet(skip_unless_truthy)]
+    pub runtime_ticks: Option<u64>,
+    /// Futures polled most recently, newest first.
+    pub last_active: Vec<EntityId>,
+    /// Heartbeats travel on their own connection, so they carry the token too.
+    #[facet(skip_unless_truthy)]
+    pub token: Option<String>,
+}
+
+// r[impl wire.control]
+#[derive(Facet)]
+pub struct ControlRequest {
+    pub control_id: i64,
+    pub changes: Vec<RuntimeSettingChange>,
+}
+
+#[derive(Facet)]
+pub struct ControlAck {
+    pub control_id: i64,
+    /// Why one or more changes were rejected. Accepted changes still apply.
+    #[facet(skip_unless_truthy)]
+    pub error: Option<String>,
+    pub settings: RuntimeSettings,
+}
+
 #[derive(Facet)]
 pub struct ClientError {
     pub process_name: String,
@@ -201,6 +263,8 @@ pub enum ClientMessage {
     SnapshotReply(SnapshotReply),
     DeltaBatch(PullChangesResponse),
     CutAck(CutAck),
+    ControlAck(ControlAck),
+    Heartbeat(Heartbeat),
     Error(ClientError),
 }
 
@@ -211,6 +275,8 @@ pub enum ClientMessage {
 pub enum ServerMessage {
     SnapshotRequest(SnapshotRequest),
     CutRequest(CutRequest),
+    SessionResume(SessionResume),
+    ControlRequest(ControlRequest),
 }
 
 pub fn encode_client_message(
@@ -264,7 +330,7 @@ pub fn decode_server_message_default(frame: &[u8]) -> Result<ServerMessage, Wire
 #[cfg(test)]
 mod tests {
     use super::*;
-    use moire_trace_types::{BacktraceId, ModuleId};
+    use moire_trace_types::ModuleId;
     use moire_types::{CutId, ProcessId, SeqNo, Snapshot, StreamCursor, StreamId};
 
     fn client_payload_json(message: &ClientMessage) -> String {
@@ -299,6 +365,13 @@ mod tests {
                 identity: ModuleIdentity::DebugId("debugid:def456".into()),
                 arch: "aarch64".into(),
             }],
+            token: None,
+            redaction: Some(RedactionSummary {
+                allow_env: vec![],
+                deny_keys: vec!["*TOKEN*".into()],
+                scrubbers: vec!["url-credentials".into()],
+            }),
+            config_errors: vec![],
         }));
         assert!(
             json.contains(
@@ -308,6 +381,11 @@ mod tests {
         assert!(json.contains(r#""module_id":"#));
         assert!(json.contains(r#""module_path":"/usr/lib/libvixenfs_swift.dylib""#));
         assert!(json.contains(r#""runtime_base":4294967296"#));
+        assert!(!json.contains(r#""token""#));
+        assert!(!json.contains(r#""config_errors""#));
+        assert!(json.contains(
+            r#""redaction":{"allow_env":[],"deny_keys":["*TOKEN*"],"scrubbers":["url-credentials"]}"#
+        ));
     }
 
     #[test]
@@ -372,6 +450,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn client_heartbeat_wire_shape() {
+        let json = client_payload_json(&ClientMessage::Heartbeat(Heartbeat {
+            process_id: ProcessId::new("0011223344556677"),
+            ptime_now_ms: 1234,
+            runtime_ticks: Some(9),
+            last_active: vec![EntityId::new("FUTURE#1")],
+            token: None,
+        }));
+        assert_eq!(
+            json,
+            r#"{"heartbeat":{"process_id":"0011223344556677","ptime_now_ms":1234,"runtime_ticks":9,"last_active":["FUTURE#1"]}}"#
+        );
+    }
+
+    #[test]
+    fn client_heartbeat_with_token_wire_shape() {
+        let json = client_payload_json(&ClientMessage::Heartbeat(Heartbeat {
+            process_id: ProcessId::new("0011223344556677"),
+            ptime_now_ms: 1234,
+            runtime_ticks: None,
+            last_active: vec![],
+            token: Some("s3cret".into()),
+        }));
+        assert_eq!(
+            json,
+            r#"{"heartbeat":{"process_id":"0011223344556677","ptime_now_ms":1234,"last_active":[],"token":"s3cret"}}"#
+        );
+    }
+
+    #[test]
+    fn client_control_ack_wire_shape() {
+        let json = client_payload_json(&ClientMessage::ControlAck(ControlAck {
+            control_id: 3,
+            error: None,
+            settings: RuntimeSettings {
+                push_interval_ms: 250,
+                backtrace_capture: false,
+                max_events: 1024,
+                detailed_kinds: vec!["mpsc_tx".into()],
+                long_poll_threshold_ms: 10,
+                send_provenance: true,
+            },
+        }));
+        assert_eq!(
+            json,
+            r#"{"control_ack":{"control_id":3,"settings":{"push_interval_ms":250,"backtrace_capture":false,"max_events":1024,"detailed_kinds":["mpsc_tx"],"long_poll_threshold_ms":10,"send_provenance":true}}}"#
+        );
+    }
+
+    #[test]
+    fn server_control_request_wire_shape() {
+        let json = server_payload_json(&ServerMessage::ControlRequest(ControlRequest {
+            control_id: 3,
+            changes: vec![
+                RuntimeSettingChange::PushIntervalMs(250),
+                RuntimeSettingChange::KindDetail(moire_types::KindDetailSetting {
+                    kind: "mpsc_tx".into(),
+                    enabled: true,
+                }),
+            ],
+        }));
+        assert_eq!(
+            json,
+            r#"{"control_request":{"control_id":3,"changes":[{"push_interval_ms":250},{"kind_detail":{"kind":"mpsc_tx","enabled":true}}]}}"#
+        );
+    }
+
     #[test]
     fn server_snapshot_request_wire_shape() {
         let json = server_payload_json(&ServerMessage::SnapshotRequest(SnapshotRequest {
@@ -391,4 +537,26 @@ mod tests {
         }));
         assert_eq!(json, r#"{"cut_request":{"cut_id":"cut-1"}}"#);
     }
+
+    #[test]
+    fn server_session_resume_wire_shape() {
+        let json = server_payload_json(&ServerMessage::SessionResume(SessionResume {
+            next_seq_no: SeqNo(42),
+            last_backtrace_id: None,
+        }));
+        assert_eq!(json, r#"{"session_resume":{"next_seq_no":42}}"#);
+
+        let backtrace_id = BacktraceId::next().expect("valid backtrace id");
+        let json = server_payload_json(&ServerMessage::SessionResume(SessionResume {
+            next_seq_no: SeqNo(42),
+            last_backtrace_id: Some(backtrace_id),
+        }));
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"session_resume":{{"next_seq_no":42,"last_backtrace_id":{}}}}}"#,
+                backtrace_id.as_u64()
+            )
+        );
+    }
 }
@@ -40,12 +40,37 @@
 //! MOIRE_DASHBOARD=127.0.0.1:9119 ./your-binary
 //! ```
 //!
+//! The push connection runs on its own thread and runtime, so a wedged application
+//! runtime can still be inspected. Set `MOIRE_DASHBOARD_RUNTIME=app` to run it on
+//! your application's runtime instead.
+//!
+//! If `moire-web` requires a token, set `MOIRE_TOKEN` to the same value. If it
+//! serves TLS, enable the `tls` feature and set `MOIRE_TLS_CA` to a PEM file with
+//! the certificate to trust.
+//!
+//! Without a dashboard (CI runs, repros on someone else's machine), record to a
+//! file and import it later with `moire-web replay <file>`:
+//!
+//! ```text
+//! MOIRE_DASHBOARD=file:/tmp/trace.moire ./your-binary
+//! ```
+//!
+//! To capture a process that is already stuck, set `MOIRE_DUMP_SIGNAL=USR2` and
+//! send it that signal: it writes a snapshot to `MOIRE_DUMP_DIR` (default: the temp
+//! directory) that `moire-web open <file>` can analyze.
+//!
+//! ```text
+//! MOIRE_DUMP_SIGNAL=USR2 ./your-binary &
+//! kill -USR2 $!
+//! ```
+//!
 //! # Cargo features
 //!
 //! | Feature | Effect |
 //! |---------|--------|
 //! | *(default, none)* | All wrappers compile to pass-throughs; no instrumentation overhead. |
 //! | `diagnostics` | Enables backtrace capture, entity tracking, and live dashboard push. |
+//! | `tokio-util` | Adds `sync::CancellationToken` and `sync::DropGuard`, wrapping `tokio_util::sync`. |
 //!
 //! Without `diagnostics`, setting `MOIRE_DASHBOARD` emits a warning and does not connect.
 //!
@@ -53,11 +78,30 @@
 //!
 //! - **Tasks**: [`task::JoinSet`]
 //! - **Channels**: [`sync::mpsc`], [`sync::broadcast`], [`sync::oneshot`], [`sync::watch`]
-//! - **Synchronization**: [`sync::Mutex`], [`sync::RwLock`], [`sync::Semaphore`], [`sync::Notify`], [`sync::OnceCell`]
+//! - **Synchronization**: [`sync::Mutex`], [`sync::RwLock`], [`sync::Semaphore`], [`sync::Notify`], [`sync::OnceCell`], [`sync::Barrier`]
+//! - **Streams**: [`stream::StreamExt::named`], [`stream::ReceiverStream`]
 //! - **Processes**: [`process::Command`]
 //! - **Time**: [`time::sleep`], [`time::interval`]
 //! - **RPC**: [`rpc::rpc_request`], [`rpc::rpc_response_for`] (used by Roam)
 //!
+//! Command environments, RPC payloads and the handshake's `argv`/environment are
+//! redacted before they leave the process; see [`redact::Redaction`] and
+//! `MOIRE_REDACT`.
+//!
+//! Task panics are recorded on the task's future entity once
+//! [`panic_hook::PanicHook`] is installed, or `MOIRE_PANIC_HOOK=record` is set;
+//! `MOIRE_PANIC_HOOK=dump` also writes a snapshot of the graph at the panic.
+//!
+//! Every instrumented future keeps its total and longest poll time, and a poll
+//! slower than `MOIRE_LONG_POLL_MS` (default 10, `0` turns it off) records a
+//! `long_poll` event, which usually means blocking code inside an async fn.
+//!
+//! Channels can also measure how long messages sit in their queue: turn on
+//! detail for `mpsc_tx` or `broadcast_tx` (`moire-web control --detail-on
+//! mpsc_tx`) and the sender entity keeps latency stats, the age of the oldest
+//! queued message, and which producers and send sites the queued messages came
+//! from.
+//!
 //! # Platform backends
 //!
 //! This crate re-exports the right backend for the current target:
@@ -278,21 +278,32 @@ const MOCK_SNAPSHOT: SnapshotCutResponse = {
             birth: birth(LAB_SERVER_PTIME_NOW, 3_600_000),
             backtrace: 101005,
             name: "Mutex<GlobalState>",
-            body: { lock: { kind: "mutex" } },
+            body: {
+              lock: {
+                kind: "mutex",
+                readers: 0,
+                writer_held: true,
+                readers_waiting: 0,
+                writers_waiting: 1,
+                acquisitions: 412,
+                contended_acquisitions: 37,
+                max_hold_ns: 48_000_000,
+              },
+            },
           },
           {
             id: "ch_tx",
             birth: birth(LAB_SERVER_PTIME_NOW, 3_590_000),
             backtrace: 101006,
             name: "mpsc.send",
-            body: { mpsc_tx: { queue_len: 0, capacity: 128 } },
+            body: { mpsc_tx: { queue_len: 0, capacity: 128, cancelled_sends: 0 } },
           },
           {
             id: "ch_rx",
             birth: birth(LAB_SERVER_PTIME_NOW, 3_590_000),
             backtrace: 101007,
             name: "mpsc.recv",
-            body: { mpsc_rx: {} },
+            body: { mpsc_rx: { cancelled_recvs: 0 } },
           },
           {
             id: "future_store",
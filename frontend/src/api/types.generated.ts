@@ -71,6 +71,25 @@ export interface SourcePreviewBatchRequest {
   frame_ids: FrameId[];
 }
 
+/** API response for importing a wire trace recorded with `MOIRE_DASHBOARD=file:...`. */
+export interface WireTraceImportResponse {
+  conn_id: ConnectionId;
+  process_id: ProcessId;
+  process_name: string;
+  pid: number;
+  /**
+   * The trace ended partway through a frame. Everything before that frame
+   * was imported.
+   */
+  truncated: boolean;
+}
+
+export type ProcessId = string;
+
+export type String = string;
+
+export type ConnectionId = number;
+
 export interface RecordingImportBody {
   version: number;
   session: RecordingSessionInfo;
@@ -110,8 +129,6 @@ export type RecordingSessionStatus = "recording" | "stopped";
 
 export type SessionId = string;
 
-export type String = string;
-
 export interface RecordCurrentResponse {
   session?: RecordingSessionInfo;
 }
@@ -181,9 +198,20 @@ export interface TimedOutProcess {
   process_id: ProcessId;
   process_name: string;
   pid: number;
+  liveness?: RuntimeLiveness;
 }
 
-export type ProcessId = string;
+/** Runtime liveness derived from a process's heartbeats. */
+export interface RuntimeLiveness {
+  /** Heartbeats keep arriving but the runtime stopped running liveness probes. */
+  runtime_unresponsive: boolean;
+  /** Milliseconds since the runtime last ran a liveness probe. */
+  runtime_stalled_ms: number;
+  /** Futures the runtime polled most recently, newest first. */
+  last_active_entities: EntityId[];
+}
+
+export type EntityId = string;
 
 /** Per-process envelope inside a snapshot cut. */
 export interface ProcessSnapshotView {
@@ -229,6 +257,10 @@ export type EventKind =
   | "state_changed"
   | "channel_sent"
   | "channel_received"
+  | "operation_cancelled"
+  | { woken: WakeEvent }
+  | { long_poll: LongPollEvent }
+  | { panicked: PanicEvent }
   | { custom: CustomEventKind };
 
 /**
@@ -247,14 +279,34 @@ export interface CustomEventKind {
 
 export type Json = string;
 
+export interface PanicEvent {
+  /** Panic message, or a placeholder when the payload is not a string. */
+  message: string;
+  /** `file:line:column` where the panic was raised, if known. */
+  location?: string;
+}
+
+export interface LongPollEvent {
+  /** Wall time the poll took, in nanoseconds. */
+  duration_ns: number;
+}
+
+export interface WakeEvent {
+  /**
+   * Entity being polled or operated on when the waker fired (e.g. the
+   * sender whose `send` woke a receiver).
+   */
+  by: EntityId;
+  /** Resource the woken entity was waiting on, when known. */
+  via?: EntityId;
+}
+
 export type EventTarget =
   | { entity: EntityId }
   | { scope: ScopeId };
 
 export type ScopeId = string;
 
-export type EntityId = string;
-
 export type PTime = number;
 
 export type EventId = string;
@@ -271,7 +323,7 @@ export interface Edge {
   kind: EdgeKind;
 }
 
-export type EdgeKind = "polls" | "waiting_on" | "paired_with" | "held_by";
+export type EdgeKind = "polls" | "waiting_on" | "paired_with" | "held_by" | "cancels";
 
 /** A scope groups execution context over time (for example process/thread/task/connection). */
 export interface Scope {
@@ -327,6 +379,38 @@ export interface Entity {
   name: string;
   /** More specific info about the entity (depending on its kind) */
   body: EntityBody;
+  /** Time operations spent waiting on this entity. Unset until one waits. */
+  wait_stats?: WaitStats;
+  /**
+   * The same waits broken down by the actor that waited, busiest first.
+   * At most [`WaiterWaitStats::MAX_WAITERS`] actors are kept.
+   */
+  waiter_wait_stats?: WaiterWaitStats[];
+}
+
+/**
+ * Completed waits on a resource by one actor: the edge `waiter → resource`
+ * while it was `waiting_on`.
+ */
+export interface WaiterWaitStats {
+  waiter: EntityId;
+  stats: WaitStats;
+}
+
+/**
+ * Completed waits on a resource: each one runs from the operation's first
+ * `Pending` until it completes or is dropped.
+ */
+export interface WaitStats {
+  count: number;
+  total_ns: number;
+  max_ns: number;
+  /**
+   * Log-scale histogram: `buckets[0]` counts waits under 1µs, `buckets[i]`
+   * waits of `2^(i-1)` to `2^i` µs. The last of [`WaitStats::BUCKETS`]
+   * also counts anything longer. Trailing empty buckets are left out.
+   */
+  buckets: number[];
 }
 
 export type EntityBody =
@@ -343,6 +427,9 @@ export type EntityBody =
   | { semaphore: SemaphoreEntity }
   | { notify: NotifyEntity }
   | { once_cell: OnceCellEntity }
+  | { barrier: BarrierEntity }
+  | { cancellation_token: CancellationTokenEntity }
+  | { stream: StreamEntity }
   | { command: CommandEntity }
   | { file_op: FileOpEntity }
   | { net_connect: NetConnectEntity }
@@ -467,11 +554,53 @@ export interface CommandEntity {
   env: string[];
 }
 
+export interface StreamEntity {
+  /** Items yielded so far. */
+  items: number;
+  /** When the last item was yielded. */
+  last_item_at?: PTime;
+  /**
+   * Sum of the gaps between consecutive items, in nanoseconds. The first
+   * gap runs from the first poll to the first item.
+   */
+  item_gap_total_ns: number;
+  /** Longest gap between consecutive items, in nanoseconds. */
+  item_gap_max_ns: number;
+  /** The stream has returned `None`. */
+  finished: boolean;
+}
+
+export interface CancellationTokenEntity {
+  /** When this token was cancelled, directly or through a parent. */
+  cancelled_at?: PTime;
+  /** Number of `cancelled()` futures currently waiting on this token. */
+  waiter_count: number;
+  /**
+   * Times cancellation was observed, through `cancelled()` completing or
+   * `is_cancelled()` returning true.
+   */
+  observed: number;
+}
+
+export interface BarrierEntity {
+  /** Number of tasks that must call `wait` before they are all released. */
+  target: number;
+  /** Tasks that have arrived in the current round and are still waiting. */
+  waiting: number;
+  /** Rounds completed so far. */
+  generations: number;
+}
+
 export interface OnceCellEntity {
   /** Number of tasks currently waiting for initialization. */
   waiter_count: number;
   /** Current once-cell lifecycle state. */
   state: OnceCellState;
+  /**
+   * Debug rendering of the value once set, for cells created with
+   * `new_debug`.
+   */
+  value?: string;
 }
 
 export type OnceCellState = "empty" | "initializing" | "initialized";
@@ -488,38 +617,118 @@ export interface SemaphoreEntity {
   handed_out_permits: number;
 }
 
-export type OneshotRxEntity = object;
+export interface OneshotRxEntity {
+  /** Receives dropped while waiting for the value. */
+  cancelled_recvs: number;
+}
 
 export interface OneshotTxEntity {
   sent: boolean;
 }
 
-export type WatchRxEntity = object;
+export interface WatchRxEntity {
+  /** `changed()` calls dropped while waiting for a new value. */
+  cancelled_recvs: number;
+}
 
 export interface WatchTxEntity {
   last_update_at?: PTime;
+  /**
+   * Debug rendering of the current value, for channels created with
+   * `channel_debug`.
+   */
+  value?: string;
 }
 
 export interface BroadcastRxEntity {
   lag: number;
+  /** Receives dropped while waiting for a message. */
+  cancelled_recvs: number;
 }
 
 export interface BroadcastTxEntity {
   capacity: number;
+  /**
+   * Time messages spent queued, across all receivers. Only kept with
+   * `broadcast_tx` detail enabled.
+   */
+  latency?: QueueLatency;
+  /**
+   * Messages some receiver has yet to get, by producer and send site,
+   * largest group first. Only kept with `broadcast_tx` detail enabled.
+   */
+  queued_by: QueuedBy[];
 }
 
-export type MpscRxEntity = object;
+/**
+ * Messages still queued on a channel that were sent by the same producer from
+ * the same place.
+ */
+export interface QueuedBy {
+  /** Task or future that sent them, if the send happened inside one. */
+  producer?: EntityId;
+  /** Where they were sent from. */
+  backtrace: BacktraceId;
+  count: number;
+}
+
+/** Delay between a message being sent and received. */
+export interface QueueLatency {
+  count: number;
+  total_ns: number;
+  max_ns: number;
+  /**
+   * Moving average weighted towards the last [`QueueLatency::RECENT_WINDOW`]
+   * messages or so.
+   */
+  recent_ns: number;
+  /** When the oldest message still queued was sent, if any is. */
+  oldest_queued_at?: PTime;
+}
+
+export interface MpscRxEntity {
+  /** Receives dropped while waiting for a message. */
+  cancelled_recvs: number;
+}
 
 export interface MpscTxEntity {
   /** Current queue length. */
   queue_len: number;
   /** Configured capacity (`None` for unbounded). */
   capacity?: number;
+  /** Sends dropped while waiting for capacity. */
+  cancelled_sends: number;
+  /** Time messages spent queued. Only kept with `mpsc_tx` detail enabled. */
+  latency?: QueueLatency;
+  /**
+   * Queued messages by producer and send site, largest group first. Only
+   * kept with `mpsc_tx` detail enabled.
+   */
+  queued_by: QueuedBy[];
 }
 
 export interface LockEntity {
   /** Kind of lock primitive. */
   kind: LockKind;
+  /** Read guards currently held. Always zero for mutexes. */
+  readers: number;
+  /** Whether an exclusive guard (a mutex guard or a write guard) is held. */
+  writer_held: boolean;
+  /** Acquisitions waiting for a read guard. */
+  readers_waiting: number;
+  /** Acquisitions waiting for an exclusive guard. */
+  writers_waiting: number;
+  /** Guards handed out so far. */
+  acquisitions: number;
+  /** Acquisitions that found the lock taken and had to wait. */
+  contended_acquisitions: number;
+  /** Longest time any guard was held, in nanoseconds. */
+  max_hold_ns: number;
+  /**
+   * Debug rendering of the protected value, for locks created with
+   * `new_debug`.
+   */
+  value?: string;
 }
 
 export type LockKind = "mutex" | "rw_lock" | "other";
@@ -531,8 +740,26 @@ export interface FutureEntity {
    * and the callsite is shown instead.
    */
   skip_entry_frames?: number;
+  /** How the future ended. Unset while it is still running. */
+  outcome?: FutureOutcome;
+  /** Where the future was aborted or dropped, for those outcomes. */
+  outcome_backtrace?: BacktraceId;
+  /**
+   * Entity that was being polled when this task was spawned. Kept after the
+   * spawner is gone, so task trees survive their parents.
+   */
+  spawned_by?: EntityId;
+  /**
+   * Wall time spent inside `poll` so far, in nanoseconds. Unset until the
+   * first poll.
+   */
+  poll_total_ns?: number;
+  /** Longest single `poll`, in nanoseconds. */
+  poll_max_ns?: number;
 }
 
+export type FutureOutcome = "ok" | "error" | "cancelled_by_drop" | "aborted" | "panicked";
+
 export interface SqlResponse {
   columns: string[];
   rows: unknown[];
@@ -552,6 +779,54 @@ export interface ApiError {
   error: string;
 }
 
+export interface RuntimeControlResponse {
+  control_id: number;
+  acks: ProcessControlAck[];
+  timed_out_processes: TimedOutProcess[];
+}
+
+export interface ProcessControlAck {
+  process_id: ProcessId;
+  process_name: string;
+  pid: number;
+  /** Set when the process rejected at least one change. */
+  error?: string;
+  /** Settings in effect after the changes were applied. */
+  settings: RuntimeSettings;
+}
+
+/** The live settings of an instrumented process. */
+export interface RuntimeSettings {
+  push_interval_ms: number;
+  backtrace_capture: boolean;
+  max_events: number;
+  detailed_kinds: string[];
+  long_poll_threshold_ms: number;
+  send_provenance: boolean;
+}
+
+/** Body of `POST /api/control`. */
+export interface RuntimeControlRequest {
+  /** Only send the changes to this process. All connected processes otherwise. */
+  process_id?: ProcessId;
+  changes: RuntimeSettingChange[];
+}
+
+/** One live setting change for an instrumented process. */
+export type RuntimeSettingChange =
+  | { push_interval_ms: number }
+  | { backtrace_capture: boolean }
+  | { max_events: number }
+  | { kind_detail: KindDetailSetting }
+  | { long_poll_threshold_ms: number }
+  | { send_provenance: boolean };
+
+export interface KindDetailSetting {
+  /** Entity kind name, e.g. `mpsc_tx`. */
+  kind: string;
+  enabled: boolean;
+}
+
 export interface CutStatusResponse {
   cut_id: CutId;
   requested_at_ns: number;
@@ -560,8 +835,6 @@ export interface CutStatusResponse {
   pending_conn_ids: ConnectionId[];
 }
 
-export type ConnectionId = number;
-
 export type CutId = string;
 
 export interface TriggerCutResponse {
@@ -575,6 +848,26 @@ export interface ConnectedProcessInfo {
   process_id: ProcessId;
   process_name: string;
   pid: number;
+  liveness?: RuntimeLiveness;
+  redaction?: RedactionSummary;
+  /** Settings the process rejected, as reported in its handshake. */
+  config_errors?: string[];
+}
+
+/**
+ * Redaction rules a process applied before sending anything, as reported in
+ * its handshake. Literal scrubbers are listed as `literal`, never by value.
+ */
+export interface RedactionSummary {
+  /**
+   * Environment variable name patterns that are sent; everything else is redacted.
+   * Empty means every variable not denied is sent.
+   */
+  allow_env: string[];
+  /** Key patterns whose values are redacted in env, args and JSON payloads. */
+  deny_keys: string[];
+  /** Value scrubbers, by name. */
+  scrubbers: string[];
 }
 
 /** API response for connected processes. */
@@ -5,9 +5,23 @@ import { formatProcessLabel } from "../processLabel";
 import type { ConnectedProcessInfo, ConnectionsResponse } from "../api/types.generated";
 import "./ProcessModal.css";
 
+function formatRuntimeLiveness(r: ConnectedProcessInfo): React.ReactNode {
+  const liveness = r.liveness;
+  if (!liveness) return "—";
+  if (!liveness.runtime_unresponsive) return "ok";
+  const stalledSecs = Math.round(liveness.runtime_stalled_ms / 1000);
+  const lastActive = liveness.last_active_entities.slice(0, 3).join(", ");
+  return (
+    <span className="process-runtime-unresponsive">
+      unresponsive for {stalledSecs}s{lastActive ? ` · last active: ${lastActive}` : ""}
+    </span>
+  );
+}
+
 const PROCESS_COLUMNS: readonly Column<ConnectedProcessInfo>[] = [
   { key: "conn_id", label: "Conn", width: "60px", render: (r) => r.conn_id },
   { key: "process", label: "Process", render: (r) => formatProcessLabel(r.process_name, r.pid) },
+  { key: "runtime", label: "Runtime", render: formatRuntimeLiveness },
 ];
 
 export function ProcessModal({
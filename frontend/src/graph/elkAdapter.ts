@@ -222,6 +222,8 @@ export function edgeStyle(edge: EdgeDef): EdgeStyle {
       return { stroke, strokeWidth, strokeDasharray: "4 4" };
     case "paired_with":
       return { stroke, strokeWidth, strokeDasharray: "6 3" };
+    case "cancels":
+      return { stroke, strokeWidth, strokeDasharray: "1 3" };
   }
 }
 
@@ -236,6 +238,8 @@ export function edgeTooltip(edge: EdgeDef, sourceName: string, targetName: strin
       return `${sourceName} currently grants permits to ${targetName}`;
     case "paired_with":
       return `Paired: ${sourceName} ↔ ${targetName}`;
+    case "cancels":
+      return `Cancelling ${sourceName} also cancels ${targetName}`;
   }
 }
 
@@ -248,6 +252,7 @@ export function edgeEventNodeLabel(kind: EdgeDef["kind"]): string | null {
     case "held_by":
       return "held by";
     case "paired_with":
+    case "cancels":
       return null;
   }
 }
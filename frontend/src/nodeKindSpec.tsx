@@ -193,6 +193,18 @@ export const NODE_KIND_SPECS: Record<string, NodeKindSpec> = {
     category: "sync",
     icon: iconFactory(Cube),
   },
+  barrier: {
+    canonical: "barrier",
+    displayName: "Barrier",
+    category: "sync",
+    icon: iconFactory(Users),
+  },
+  cancellation_token: {
+    canonical: "cancellation_token",
+    displayName: "Cancellation Token",
+    category: "sync",
+    icon: iconFactory(Shield),
+  },
   request: {
     canonical: "request",
     displayName: "Request",
@@ -247,6 +259,12 @@ export const NODE_KIND_SPECS: Record<string, NodeKindSpec> = {
     category: "async",
     icon: iconFactory(Stack),
   },
+  stream: {
+    canonical: "stream",
+    displayName: "Stream",
+    category: "async",
+    icon: iconFactory(Queue),
+  },
   command: {
     canonical: "command",
     displayName: "Command",
@@ -581,7 +581,18 @@ export function useStorybookState() {
         krate: "tokio",
         name: "store.state_lock",
         kind: "lock",
-        body: { lock: { kind: "mutex" } },
+        body: {
+          lock: {
+            kind: "mutex",
+            readers: 0,
+            writer_held: true,
+            readers_waiting: 0,
+            writers_waiting: 1,
+            acquisitions: 412,
+            contended_acquisitions: 37,
+            max_hold_ns: 48_000_000,
+          },
+        },
         status: { label: "held", tone: "crit" as const },
       },
       {
@@ -423,7 +423,22 @@ export function bodyToKind(body: EntityBody): string {
 
 // f[impl display.entity.status]
 export function deriveStatus(body: EntityBody): { label: string; tone: Tone } {
-  if ("future" in body) return { label: "polling", tone: "neutral" };
+  if ("future" in body) {
+    switch (body.future.outcome) {
+      case "ok":
+        return { label: "completed", tone: "ok" };
+      case "error":
+        return { label: "error", tone: "crit" };
+      case "cancelled_by_drop":
+        return { label: "cancelled", tone: "neutral" };
+      case "aborted":
+        return { label: "aborted", tone: "warn" };
+      case "panicked":
+        return { label: "panicked", tone: "crit" };
+      default:
+        return { label: "polling", tone: "neutral" };
+    }
+  }
   if ("request" in body) return { label: "in_flight", tone: "warn" };
   if ("response" in body) {
     const s = (body as ResponseBody).response.status;
@@ -433,7 +448,17 @@ export function deriveStatus(body: EntityBody): { label: string; tone: Tone } {
     if ("error" in s) return { label: "error", tone: "crit" };
     return { label: "pending", tone: "warn" };
   }
-  if ("lock" in body) return { label: "unlocked", tone: "ok" };
+  if ("lock" in body) {
+    const { readers, writer_held, readers_waiting, writers_waiting } = body.lock;
+    const waiting = readers_waiting + writers_waiting;
+    const tone = waiting > 0 ? "crit" : "warn";
+    if (writer_held) return { label: waiting > 0 ? `held, ${waiting} waiting` : "held", tone };
+    if (readers > 0) {
+      const label = `${readers} reader${readers === 1 ? "" : "s"}`;
+      return { label: writers_waiting > 0 ? `${label}, writer waiting` : label, tone };
+    }
+    return { label: "unlocked", tone: "ok" };
+  }
   if ("mpsc_tx" in body || "mpsc_rx" in body) return { label: "active", tone: "ok" };
   if ("broadcast_tx" in body) return { label: "active", tone: "ok" };
   if ("broadcast_rx" in body) {
@@ -462,6 +487,24 @@ export function deriveStatus(body: EntityBody): { label: string; tone: Tone } {
     if (s === "initializing") return { label: "initializing", tone: "warn" };
     return { label: "empty", tone: "neutral" };
   }
+  if ("barrier" in body) {
+    const { target, waiting } = body.barrier;
+    return waiting > 0
+      ? { label: `${waiting}/${target} arrived`, tone: "warn" }
+      : { label: "idle", tone: "neutral" };
+  }
+  if ("cancellation_token" in body) {
+    const { cancelled_at, waiter_count } = body.cancellation_token;
+    if (cancelled_at == null) return { label: "active", tone: "ok" };
+    return waiter_count > 0
+      ? { label: "cancelled, unobserved", tone: "crit" }
+      : { label: "cancelled", tone: "neutral" };
+  }
+  if ("stream" in body) {
+    return body.stream.finished
+      ? { label: "finished", tone: "neutral" }
+      : { label: "open", tone: "ok" };
+  }
   if ("command" in body) return { label: "running", tone: "neutral" };
   if ("file_op" in body) return { label: body.file_op.op, tone: "ok" };
   if ("net_connect" in body || "net_accept" in body || "net_read" in body || "net_write" in body) {
@@ -487,6 +530,9 @@ export function deriveStat(body: EntityBody): string | undefined {
   if ("once_cell" in body) {
     return body.once_cell.waiter_count > 0 ? `${body.once_cell.waiter_count} waiter` : undefined;
   }
+  if ("stream" in body) {
+    return `${body.stream.items} items`;
+  }
   return undefined;
 }
 
@@ -997,12 +1043,19 @@ const EVENT_KIND_DISPLAY: Record<string, string> = {
   state_changed: "State Changed",
   channel_sent: "Channel Sent",
   channel_received: "Channel Received",
+  operation_cancelled: "Operation Cancelled",
+  panicked: "Panicked",
+  woken: "Woken",
+  long_poll: "Long Poll",
 };
 
 export function eventKindKey(kind: EventKind): string {
   if (typeof kind === "object" && "custom" in kind) {
     return `custom:${kind.custom.kind}`;
   }
+  if (typeof kind === "object" && "panicked" in kind) return "panicked";
+  if (typeof kind === "object" && "woken" in kind) return "woken";
+  if (typeof kind === "object" && "long_poll" in kind) return "long_poll";
   return kind;
 }
 
@@ -1010,7 +1063,7 @@ export function eventKindDisplayName(kind: EventKind): string {
   if (typeof kind === "object" && "custom" in kind) {
     return kind.custom.display_name;
   }
-  return EVENT_KIND_DISPLAY[kind] ?? kind;
+  return EVENT_KIND_DISPLAY[eventKindKey(kind)] ?? eventKindKey(kind);
 }
 
 export function extractEvents(snapshot: SnapshotCutResponse): EventDef[] {